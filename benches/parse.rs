@@ -0,0 +1,101 @@
+//! Times `parse` and `parse_bytes` on large configs and on a corpus of real
+//! ones. Run with `cargo bench`.
+//!
+//! The corpus is the configs in `tests`, plus every file in the directory
+//! named by `OVPNFILE_BENCH_CORPUS` if it's set. To compare a change with
+//! the code before it, run the bench at both commits with the same corpus.
+extern crate ovpnfile;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 50;
+
+/// A server config with many `push` and `route` lines and large inline
+/// certificates
+fn large_config() -> String {
+    let mut config = String::from("port 1194\nproto udp\ndev tun\nserver 10.8.0.0 255.255.0.0\n");
+    for i in 0..5000 {
+        config.push_str(&format!("push \"route 10.{}.{}.0 255.255.255.0\" # route {}\n", i / 256, i % 256, i));
+        config.push_str(&format!("route 172.{}.{}.0 255.255.255.0\n", 16 + i / 256 % 16, i % 256));
+    }
+    for tag in ["ca", "cert", "key"].iter() {
+        config.push_str(&format!("<{}>\n", tag));
+        for _ in 0..2000 {
+            config.push_str("MIIDdzCCAl+gAwIBAgIUJ9dD0JQ0n0NTZr6DnT7yWn1mUjcwDQYJKoZIhvcNAQELBQAwGjEYMBYGA1UEAwwP\n");
+        }
+        config.push_str(&format!("</{}>\n", tag));
+    }
+    config
+}
+
+/// A config of options with several arguments each, where the time taken
+/// is mostly splitting and storing arguments
+fn argument_heavy_config() -> String {
+    let mut config = String::new();
+    for i in 0..20000 {
+        config.push_str(&format!("route 10.{}.{}.0 255.255.255.0 10.8.0.1 {}\n", i / 256 % 256, i % 256, i % 100));
+        config.push_str(&format!("remote vpn{}.example.com 1194 udp\n", i));
+    }
+    config
+}
+
+/// The files in `dir`, or only those ending in `.ovpn` if `ovpn_only`
+fn read_configs(dir: &Path, ovpn_only: bool) -> Vec<Vec<u8>> {
+    let mut paths: Vec<_> = fs::read_dir(dir).expect("Error reading corpus directory")
+        .map(|entry| entry.expect("Error reading corpus directory").path())
+        .filter(|path| path.is_file() && (!ovpn_only || path.extension() == Some("ovpn".as_ref())))
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| fs::read(path).expect("Error reading corpus file")).collect()
+}
+
+/// The configs parsed by the corpus bench
+fn corpus() -> Vec<Vec<u8>> {
+    let mut configs = read_configs(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests"), true);
+    if let Some(dir) = env::var_os("OVPNFILE_BENCH_CORPUS") {
+        configs.extend(read_configs(Path::new(&dir), false));
+    }
+    configs
+}
+
+/// The fastest of `ITERATIONS` runs of `f`, which is less affected by other
+/// work on the machine than the mean
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    (0..ITERATIONS).map(|_| {
+        let start = Instant::now();
+        f();
+        start.elapsed()
+    }).min().unwrap()
+}
+
+fn main() {
+    for &(name, ref config) in [("large", large_config()), ("argument heavy", argument_heavy_config())].iter() {
+        let read = time(|| {
+            ovpnfile::parse(config.as_bytes()).unwrap();
+        });
+        let bytes = time(|| {
+            ovpnfile::parse_bytes(config.as_bytes()).unwrap();
+        });
+        println!("{} config: {} bytes, {} lines", name, config.len(), config.lines().count());
+        println!("    parse:       {:?}", read);
+        println!("    parse_bytes: {:?}", bytes);
+    }
+
+    let configs = corpus();
+    let read = time(|| {
+        for config in configs.iter() {
+            ovpnfile::parse(&config[..]).unwrap();
+        }
+    });
+    let bytes = time(|| {
+        for config in configs.iter() {
+            ovpnfile::parse_bytes(&config[..]).unwrap();
+        }
+    });
+    println!("corpus: {} configs, {} bytes", configs.len(), configs.iter().map(|c| c.len()).sum::<usize>());
+    println!("    parse:       {:?}", read);
+    println!("    parse_bytes: {:?}", bytes);
+}
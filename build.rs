@@ -0,0 +1,308 @@
+//! Generates the directive table from the option synopses in `ovpn_commands`.
+//!
+//! Each line of `ovpn_commands` is an option as it appears in the openvpn man
+//! page, followed by optional annotations. For example
+//!
+//! ```text
+//! --remote host [port] [proto]
+//! --explicit-exit-notify [n:u8]
+//! --tls-crypt-v2 file [cookie_mode:TlsCryptV2CookieMode] @inline @since 2.5
+//! --redirect-gateway flags...
+//! --comp-lzo [mode] @deprecated 2.4
+//! --mssfix [max] [flag:MssfixFlag@2.6]
+//! ```
+//!
+//! Arguments in square brackets are optional, `name:Type` gives the type an
+//! argument is parsed into (`String` otherwise), `name=a|b` lists the values
+//! a `String` argument can take and `name...` takes all of the remaining
+//! arguments. An argument ending in `@X.Y` was added to the option in that
+//! version of openvpn. The annotations are
+//!
+//! * `@inline` - the option can have inline file contents
+//! * `@since`, `@deprecated`, `@removed` - the openvpn version in which the
+//!   option was introduced, deprecated or removed
+//! * `@replaced-by` - the option to use instead of a deprecated option
+//! * `@manual` - the option is parsed by hand written code rather than the
+//!   `define_config_directives` macro
+//! * `@pushable` - a server can push the option to clients
+//! * `@ccd` - the option can be used in a `client-config-dir` file
+//! * `@repeatable` - each use of the option adds to the earlier ones rather
+//!   than replacing them
+//!
+//! The output is a `define_config_directives` invocation plus the lists of
+//! inline, pushable, client-config-dir and repeatable options, the argument
+//! values and argument versions tables, the table of directive specs and the
+//! version table.
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+struct Arg {
+    name: String,
+    argtype: Option<String>,
+    optional: bool,
+    varargs: bool,
+    choices: Vec<String>,
+    since: Option<String>,
+}
+
+struct Directive {
+    command: String,
+    args: Vec<Arg>,
+    inline: bool,
+    manual: bool,
+    pushable: bool,
+    ccd: bool,
+    repeatable: bool,
+    since: Option<String>,
+    deprecated: Option<String>,
+    removed: Option<String>,
+    replaced_by: Option<String>,
+}
+
+fn rust_name(command: &str) -> String {
+    command.split('-').map(|part| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }).collect()
+}
+
+fn parse_arg(token: &str) -> Arg {
+    let optional = token.starts_with('[') && token.ends_with(']');
+    let token = token.trim_start_matches('[').trim_end_matches(']');
+    let mut parts = token.splitn(2, '@');
+    let token = parts.next().unwrap();
+    let since = parts.next().map(|v| v.to_string());
+    let varargs = token.ends_with("...");
+    let token = token.trim_end_matches("...");
+    let mut parts = token.splitn(2, '=');
+    let token = parts.next().unwrap();
+    let choices = parts.next().map_or(Vec::new(), |c| c.split('|').map(|c| c.to_string()).collect());
+    let mut parts = token.splitn(2, ':');
+    let name = parts.next().unwrap().replace('-', "_");
+    let argtype = parts.next().map(|t| t.to_string());
+    Arg{name, argtype, optional, varargs, choices, since}
+}
+
+fn parse_directive(line_no: usize, line: &str) -> Directive {
+    let mut tokens = line.split_whitespace();
+    let command = tokens.next().unwrap();
+    if !command.starts_with("--") {
+        panic!("ovpn_commands line {}: expected an option, got '{}'", line_no, command);
+    }
+    let mut directive = Directive{
+        command: command[2..].to_string(),
+        args: Vec::new(),
+        inline: false,
+        manual: false,
+        pushable: false,
+        ccd: false,
+        repeatable: false,
+        since: None,
+        deprecated: None,
+        removed: None,
+        replaced_by: None,
+    };
+    while let Some(token) = tokens.next() {
+        let mut value = || {
+            tokens.next()
+                .unwrap_or_else(|| panic!("ovpn_commands line {}: {} needs a value", line_no, token))
+                .to_string()
+        };
+        match token {
+            "@inline" => directive.inline = true,
+            "@manual" => directive.manual = true,
+            "@pushable" => directive.pushable = true,
+            "@ccd" => directive.ccd = true,
+            "@repeatable" => directive.repeatable = true,
+            "@since" => directive.since = Some(value()),
+            "@deprecated" => directive.deprecated = Some(value()),
+            "@removed" => directive.removed = Some(value()),
+            "@replaced-by" => directive.replaced_by = Some(value()),
+            annotation if annotation.starts_with('@') => {
+                panic!("ovpn_commands line {}: unknown annotation {}", line_no, annotation)
+            },
+            arg => directive.args.push(parse_arg(arg)),
+        }
+    }
+    directive
+}
+
+fn typed_arg(arg: &Arg) -> String {
+    match arg.argtype {
+        Some(ref argtype) => format!("{}: {}", arg.name, argtype),
+        None => arg.name.clone(),
+    }
+}
+
+fn macro_entry(directive: &Directive) -> String {
+    let name = format!("command: \"{}\", rust_name: {}", directive.command, rust_name(&directive.command));
+    let args = &directive.args;
+    if args.len() == 1 && args[0].varargs {
+        let kind = if args[0].optional { "optional_varargs" } else { "varargs" };
+        return format!("{{{}, {}: {}}}", name, kind, args[0].name);
+    }
+    let optional: Vec<String> = args.iter().filter(|a| a.optional).map(typed_arg).collect();
+    if directive.inline && !args.is_empty() && args[0].name == "file" {
+        if optional.is_empty() {
+            return format!("{{{}, inline_file: true}}", name);
+        }
+        return format!("{{{}, inline_file: true, optional_args: [{}]}}", name, optional.join(", "));
+    }
+    let required: Vec<String> = args.iter().filter(|a| !a.optional).map(typed_arg).collect();
+    format!("{{{}, args: [{}], optional_args: [{}]}}", name, required.join(", "), optional.join(", "))
+}
+
+fn argument_spec(arg: &Arg) -> String {
+    let choices: Vec<String> = arg.choices.iter().map(|c| format!("\"{}\"", c)).collect();
+    format!(
+        "ArgumentSpec{{name: \"{}\", type_name: {}, optional: {}, varargs: {}, choices: &[{}], introduced: {}}}",
+        arg.name,
+        arg.argtype.as_ref().map_or("None".to_string(), |t| format!("Some(\"{}\")", t)),
+        arg.optional,
+        arg.varargs,
+        choices.join(", "),
+        version_expr(&arg.since),
+    )
+}
+
+fn version_value(version: &str) -> String {
+    let mut parts = version.split('.');
+    let major: u8 = parts.next().and_then(|p| p.parse().ok()).expect("invalid version");
+    let minor: u8 = parts.next().and_then(|p| p.parse().ok()).expect("invalid version");
+    format!("Version::new({}, {})", major, minor)
+}
+
+fn version_expr(version: &Option<String>) -> String {
+    match *version {
+        Some(ref version) => format!("Some({})", version_value(version)),
+        None => "None".to_string(),
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=ovpn_commands");
+    let description = fs::read_to_string("ovpn_commands").expect("unable to read ovpn_commands");
+    let directives: Vec<Directive> = description.lines().enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|&(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_no, line)| parse_directive(line_no, line))
+        .collect();
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("directives.rs");
+    let mut out = fs::File::create(out_path).unwrap();
+
+    writeln!(out, "define_config_directives!{{").unwrap();
+    for directive in directives.iter().filter(|d| !d.manual) {
+        writeln!(out, "    {},", macro_entry(directive)).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "/// Options which can have inline file contents").unwrap();
+    writeln!(out, "pub(crate) const INLINE_FILE_OPTIONS: &[&str] = &[").unwrap();
+    for directive in directives.iter().filter(|d| d.inline) {
+        writeln!(out, "    \"{}\",", directive.command).unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+
+    writeln!(out, "/// Options which a server can push to clients").unwrap();
+    writeln!(out, "pub(crate) const PUSHABLE_OPTIONS: &[&str] = &[").unwrap();
+    for directive in directives.iter().filter(|d| d.pushable) {
+        writeln!(out, "    \"{}\",", directive.command).unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+
+    writeln!(out, "/// Options which can be used in a client-config-dir file").unwrap();
+    writeln!(out, "pub(crate) const CCD_OPTIONS: &[&str] = &[").unwrap();
+    for directive in directives.iter().filter(|d| d.ccd) {
+        writeln!(out, "    \"{}\",", directive.command).unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+
+    writeln!(out, "/// Options which add to earlier uses rather than replacing them").unwrap();
+    writeln!(out, "pub(crate) const REPEATABLE_OPTIONS: &[&str] = &[").unwrap();
+    for directive in directives.iter().filter(|d| d.repeatable) {
+        writeln!(out, "    \"{}\",", directive.command).unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+
+    writeln!(out, "/// The values arguments with a fixed vocabulary can take, as the option,").unwrap();
+    writeln!(out, "/// whether the argument is optional, its index among the required or").unwrap();
+    writeln!(out, "/// optional arguments, its name and the values").unwrap();
+    writeln!(out, "pub(crate) const ARGUMENT_CHOICES: &[(&str, bool, usize, &str, &[&str])] = &[").unwrap();
+    for directive in directives.iter() {
+        for optional in [false, true].iter() {
+            let args = directive.args.iter().filter(|a| a.optional == *optional);
+            for (index, arg) in args.enumerate().filter(|&(_, a)| !a.choices.is_empty()) {
+                let choices: Vec<String> = arg.choices.iter().map(|c| format!("\"{}\"", c)).collect();
+                writeln!(
+                    out,
+                    "    (\"{}\", {}, {}, \"{}\", &[{}]),",
+                    directive.command, optional, index, arg.name, choices.join(", "),
+                ).unwrap();
+            }
+        }
+    }
+    writeln!(out, "];\n").unwrap();
+
+    writeln!(out, "/// The versions of openvpn which added arguments to options, as the").unwrap();
+    writeln!(out, "/// option, whether the argument is optional, its index among the required").unwrap();
+    writeln!(out, "/// or optional arguments and the version").unwrap();
+    writeln!(out, "pub(crate) const ARGUMENT_VERSIONS: &[(&str, bool, usize, Version)] = &[").unwrap();
+    for directive in directives.iter() {
+        for optional in [false, true].iter() {
+            let args = directive.args.iter().filter(|a| a.optional == *optional);
+            for (index, arg) in args.enumerate() {
+                if let Some(ref since) = arg.since {
+                    writeln!(
+                        out,
+                        "    (\"{}\", {}, {}, {}),",
+                        directive.command, optional, index, version_value(since),
+                    ).unwrap();
+                }
+            }
+        }
+    }
+    writeln!(out, "];\n").unwrap();
+
+    writeln!(out, "/// The spec of every option, in the order of the option table").unwrap();
+    writeln!(out, "pub(crate) static DIRECTIVE_SPECS: &[DirectiveSpec] = &[").unwrap();
+    for directive in directives.iter() {
+        let arguments: Vec<String> = directive.args.iter().map(argument_spec).collect();
+        writeln!(
+            out,
+            "    DirectiveSpec{{command: \"{}\", variant: \"{}\", arguments: &[{}], inline: {}, pushable: {}, ccd: {}, repeatable: {}}},",
+            directive.command,
+            rust_name(&directive.command),
+            arguments.join(", "),
+            directive.inline,
+            directive.pushable,
+            directive.ccd,
+            directive.repeatable,
+        ).unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+
+    writeln!(out, "/// The versions of openvpn in which an option was introduced, deprecated").unwrap();
+    writeln!(out, "/// and removed. `None` if the option is not known.").unwrap();
+    writeln!(out, "pub fn option_versions(option_name: &str) -> Option<OptionVersions> {{").unwrap();
+    writeln!(out, "    match option_name {{").unwrap();
+    for directive in directives.iter() {
+        writeln!(
+            out,
+            "        \"{}\" => Some(OptionVersions{{introduced: {}, deprecated: {}, removed: {}, replaced_by: {}}}),",
+            directive.command,
+            version_expr(&directive.since),
+            version_expr(&directive.deprecated),
+            version_expr(&directive.removed),
+            directive.replaced_by.as_ref().map_or("None".to_string(), |r| format!("Some(\"{}\")", r)),
+        ).unwrap();
+    }
+    writeln!(out, "        _ => None,").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+}
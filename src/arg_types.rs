@@ -0,0 +1,344 @@
+//! Typed representations of option arguments which take one of a fixed set
+//! of values.
+use std::fmt;
+use std::str::FromStr;
+
+// Defines an enum where each variant corresponds to a keyword in the config
+// file, along with the `FromStr` and `Display` implementations used to parse
+// and serialize it.
+macro_rules! keyword_arg {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $keyword:expr),* $(,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+        pub enum $name {
+            $($variant,)*
+        }
+
+        impl FromStr for $name {
+            type Err = ();
+            fn from_str(s: &str) -> Result<$name, ()> {
+                match s {
+                    $($keyword => Ok($name::$variant),)*
+                    _ => Err(()),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let keyword = match *self {
+                    $($name::$variant => $keyword,)*
+                };
+                f.write_str(keyword)
+            }
+        }
+    };
+}
+
+keyword_arg! {
+    /// Argument of the --mode option
+    pub enum Mode {
+        P2p => "p2p",
+        Server => "server",
+    }
+}
+
+/// The third argument of the --http-proxy option
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum HttpProxyAuth {
+    /// Read the username and password from the given file
+    AuthFile(String),
+    /// Query the management interface or prompt for credentials
+    Auto,
+    /// As `Auto` but don't allow weak authentication methods
+    AutoNct,
+}
+
+impl FromStr for HttpProxyAuth {
+    type Err = ();
+    fn from_str(s: &str) -> Result<HttpProxyAuth, ()> {
+        match s {
+            "auto" => Ok(HttpProxyAuth::Auto),
+            "auto-nct" => Ok(HttpProxyAuth::AutoNct),
+            authfile => Ok(HttpProxyAuth::AuthFile(authfile.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for HttpProxyAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HttpProxyAuth::AuthFile(ref authfile) => f.write_str(authfile),
+            HttpProxyAuth::Auto => f.write_str("auto"),
+            HttpProxyAuth::AutoNct => f.write_str("auto-nct"),
+        }
+    }
+}
+
+keyword_arg! {
+    /// The authentication method argument of the --http-proxy option
+    pub enum HttpProxyAuthMethod {
+        None => "none",
+        Basic => "basic",
+        Digest => "digest",
+        Ntlm => "ntlm",
+    }
+}
+
+/// A field name given to the --x509-username-field option
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum X509Field {
+    /// A field of the certificate subject, e.g `CN` or `emailAddress`
+    Subject(String),
+    /// A certificate extension given with the `ext:` prefix, e.g
+    /// `ext:subjectAltName`
+    Extension(String),
+}
+
+impl FromStr for X509Field {
+    type Err = ();
+    fn from_str(s: &str) -> Result<X509Field, ()> {
+        if let Some(extension) = s.strip_prefix("ext:") {
+            Ok(X509Field::Extension(extension.to_string()))
+        } else {
+            Ok(X509Field::Subject(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for X509Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            X509Field::Subject(ref field) => f.write_str(field),
+            X509Field::Extension(ref extension) => write!(f, "ext:{}", extension),
+        }
+    }
+}
+
+/// The parameters of an --echo option, interpreted according to the
+/// conventions used by GUI clients.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum EchoCommand {
+    /// `echo msg <text>`, a line of a message to show the user
+    Msg(String),
+    /// `echo msg-n <text>`, as `Msg` but without a trailing newline
+    MsgN(String),
+    /// `echo msg-window <title>`, show the message in a window
+    MsgWindow(String),
+    /// `echo msg-notify <title>`, show the message as a notification
+    MsgNotify(String),
+    /// Any other message
+    Other(String),
+}
+
+impl EchoCommand {
+    /// Interpret the parameters of an `echo` option
+    pub fn from_parms(parms: &[String]) -> EchoCommand {
+        let rest = || parms[1..].join(" ");
+        match parms.first().map(|p| p.as_ref()) {
+            Some("msg") => EchoCommand::Msg(rest()),
+            Some("msg-n") => EchoCommand::MsgN(rest()),
+            Some("msg-window") => EchoCommand::MsgWindow(rest()),
+            Some("msg-notify") => EchoCommand::MsgNotify(rest()),
+            _ => EchoCommand::Other(parms.join(" ")),
+        }
+    }
+}
+
+keyword_arg! {
+    /// The optional flag of the --tls-crypt-v2 option controlling how the
+    /// server handles clients which don't support HMAC cookies
+    pub enum TlsCryptV2CookieMode {
+        ForceCookie => "force-cookie",
+        AllowNoncookie => "allow-noncookie",
+    }
+}
+
+keyword_arg! {
+    /// Whether DNSSEC should be used, the argument of `dns server n dnssec`
+    pub enum DnsSecMode {
+        Yes => "yes",
+        Optional => "optional",
+        No => "no",
+    }
+}
+
+keyword_arg! {
+    /// The transport used to reach a DNS server, the argument of
+    /// `dns server n transport`
+    pub enum DnsTransport {
+        Plain => "plain",
+        DnsOverHttps => "DoH",
+        DnsOverTls => "DoT",
+    }
+}
+
+keyword_arg! {
+    /// Argument of the --tls-cert-profile option
+    pub enum TlsCertProfile {
+        Insecure => "insecure",
+        Legacy => "legacy",
+        Preferred => "preferred",
+        Suiteb => "suiteb",
+    }
+}
+
+keyword_arg! {
+    /// Argument of the --vlan-accept option
+    pub enum VlanAccept {
+        All => "all",
+        Tagged => "tagged",
+        Untagged => "untagged",
+    }
+}
+
+/// A VLAN identifier, the argument of the --vlan-pvid option. Valid
+/// identifiers are between 1 and 4094.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct VlanId(u16);
+
+impl VlanId {
+    /// Create a VLAN identifier, `None` if `id` is out of range
+    pub fn new(id: u16) -> Option<VlanId> {
+        if (1..=4094).contains(&id) {
+            Some(VlanId(id))
+        } else {
+            None
+        }
+    }
+
+    /// The numeric identifier
+    pub fn id(self) -> u16 {
+        self.0
+    }
+}
+
+impl FromStr for VlanId {
+    type Err = ();
+    fn from_str(s: &str) -> Result<VlanId, ()> {
+        s.parse().ok().and_then(VlanId::new).ok_or(())
+    }
+}
+
+impl fmt::Display for VlanId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+keyword_arg! {
+    /// Argument of the --windows-driver option
+    pub enum WindowsDriver {
+        Wintun => "wintun",
+        TapWindows6 => "tap-windows6",
+        OvpnDco => "ovpn-dco",
+    }
+}
+
+keyword_arg! {
+    /// The type of key to generate, the first argument of the --genkey option
+    pub enum GenkeyType {
+        Secret => "secret",
+        TlsAuth => "tls-auth",
+        TlsCrypt => "tls-crypt",
+        TlsCryptV2Server => "tls-crypt-v2-server",
+        TlsCryptV2Client => "tls-crypt-v2-client",
+        AuthToken => "auth-token",
+    }
+}
+
+keyword_arg! {
+    /// The algorithm of a certificate hash given to the --verify-hash option
+    pub enum HashAlgorithm {
+        Sha1 => "SHA1",
+        Sha256 => "SHA256",
+    }
+}
+
+/// A certificate hash, written in config files as hex optionally separated
+/// by colons, e.g `AB:CD:EF`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CertHash(Vec<u8>);
+
+impl CertHash {
+    /// Create a hash from it's bytes
+    pub fn new(bytes: Vec<u8>) -> CertHash {
+        CertHash(bytes)
+    }
+
+    /// The bytes of the hash
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for CertHash {
+    type Err = ();
+    fn from_str(s: &str) -> Result<CertHash, ()> {
+        let hex: Vec<char> = s.chars().filter(|&c| c != ':').collect();
+        if hex.is_empty() || !hex.len().is_multiple_of(2) {
+            return Err(());
+        }
+        hex.chunks(2).map(|pair| {
+            let high = pair[0].to_digit(16).ok_or(())?;
+            let low = pair[1].to_digit(16).ok_or(())?;
+            Ok((high * 16 + low) as u8)
+        }).collect::<Result<Vec<u8>, ()>>().map(CertHash)
+    }
+}
+
+impl fmt::Display for CertHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex: Vec<String> = self.0.iter().map(|b| format!("{:02X}", b)).collect();
+        f.write_str(&hex.join(":"))
+    }
+}
+
+keyword_arg! {
+    /// The optional flag of the --mssfix option
+    pub enum MssfixFlag {
+        Mtu => "mtu",
+        Fixed => "fixed",
+    }
+}
+
+keyword_arg! {
+    /// The optional flag of the --auth-gen-token option
+    pub enum AuthGenTokenFlag {
+        ExternalAuth => "external-auth",
+    }
+}
+
+keyword_arg! {
+    /// The optional flag of the --fragment option
+    pub enum FragmentFlag {
+        Mtu => "mtu",
+    }
+}
+
+keyword_arg! {
+    /// The optional flag of the --crl-verify option, `dir` means the file
+    /// argument is a directory containing one file per revoked serial number.
+    /// This can only be used with a path, not inline contents, the
+    /// `conflicting-options` lint rule reports it used with inline contents.
+    pub enum CrlVerifyFlag {
+        Dir => "dir",
+    }
+}
+
+keyword_arg! {
+    /// The method argument of the --ip-win32 option
+    pub enum IpWin32Method {
+        Manual => "manual",
+        Netsh => "netsh",
+        Ipapi => "ipapi",
+        Exec => "exec",
+        Dynamic => "dynamic",
+        Adaptive => "adaptive",
+    }
+}
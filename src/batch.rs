@@ -0,0 +1,34 @@
+//! Parsing many configs at once in parallel
+use std::io::Read;
+use rayon::prelude::*;
+use errors;
+use {parse, ParsedConfigFile};
+
+/// Parse each of `inputs` in parallel. The result at each index is that of
+/// the input at the same index, so a failure to read one input doesn't
+/// affect the others.
+pub fn parse_many<I, R>(inputs: I) -> Vec<errors::Result<ParsedConfigFile>>
+    where I: IntoIterator<Item = R>, R: Read + Send
+{
+    let inputs: Vec<R> = inputs.into_iter().collect();
+    inputs.into_par_iter().map(parse).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ConfigDirective;
+
+    #[test]
+    fn test_parse_many() {
+        let inputs: Vec<String> = (0..100).map(|i| format!("client\nverb {}\n", i)).collect();
+        let mut results = parse_many(inputs.iter().map(|input| input.as_bytes()));
+        results.push(parse_many(vec![&b"client\n\xff\n"[..]]).remove(0));
+        assert_eq!(results.len(), 101);
+        for (i, result) in results[..100].iter().enumerate() {
+            let config = result.as_ref().unwrap();
+            assert_eq!(config.directives()[1], ConfigDirective::Verb{n: i.to_string()});
+        }
+        assert!(results[100].is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! A command line tool for checking and rewriting openvpn config files,
+//! built with the `cli` feature
+//!
+//! ```text
+//! ovpnfile lint [--security] [FILE]   report problems with a config
+//! ovpnfile fmt [--normalize] [FILE]   print a config with consistent formatting
+//! ovpnfile inline [FILE]              print a config with its files inlined
+//! ovpnfile json [FILE]                print a config's options as JSON
+//! ```
+//!
+//! Configs are read from standard input if no file is given. Line numbers
+//! in the output count from 1. `lint` exits with 1 if there are errors.
+//! `fmt` keeps comments, blank lines and lines it can't parse, and exits
+//! with 1 if there were any of the latter. `fmt --normalize` refuses a
+//! config with comments, and it and `inline` exit with 1 if any lines were
+//! left out.
+extern crate ovpnfile;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process;
+use ovpnfile::lint::{Linter, Severity};
+use ovpnfile::{ConfigDocument, ParseWarning, ParsedConfigFile};
+
+const USAGE: &str = "usage: ovpnfile <lint [--security] | fmt [--normalize] | inline | json> [FILE]";
+
+/// The contents of `path`, or of standard input if there isn't one
+fn read_input(path: Option<&str>) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    match path {
+        Some(path) => contents = fs::read(path)?,
+        None => { io::stdin().read_to_end(&mut contents)?; },
+    }
+    Ok(contents)
+}
+
+fn warning_description(warning: &ParseWarning) -> &'static str {
+    match *warning {
+        ParseWarning::NotEnoughArguments => "not enough arguments",
+        ParseWarning::NoMatchingCommand => "unknown option",
+        ParseWarning::InvalidArgument => "invalid argument",
+        ParseWarning::LineTooLong => "line too long",
+        ParseWarning::UnclosedInlineFile => "unclosed inline file",
+    }
+}
+
+/// Write the options of a config, one per line, returning whether any lines
+/// were left out. Lines which couldn't be parsed, and comments, aren't
+/// written, the lines left out are reported on standard error. Lines which
+/// are too long are still written, but are reported too.
+fn write_config<W: Write>(config: &ParsedConfigFile, out: &mut W) -> io::Result<bool> {
+    for line in config.success_lines.iter() {
+        writeln!(out, "{}", line.result.as_ovpn_config())?;
+    }
+    let mut left_out = false;
+    for line in config.warning_lines.iter() {
+        if line.result == ParseWarning::LineTooLong {
+            eprintln!("line {}: {}", line.number + 1, warning_description(&line.result));
+        } else {
+            eprintln!("line {}: {}, left out", line.number + 1, warning_description(&line.result));
+            left_out = true;
+        }
+    }
+    Ok(left_out)
+}
+
+/// Write a document formatted, returning whether any lines couldn't be
+/// parsed. Those lines are written as they are, and reported on standard
+/// error with the lines which are too long.
+fn write_document<W: Write>(document: &ConfigDocument, out: &mut W) -> io::Result<bool> {
+    write!(out, "{}", document.formatted())?;
+    let mut unparsed = false;
+    for line in document.to_parsed().warning_lines.iter() {
+        if line.result == ParseWarning::LineTooLong {
+            eprintln!("line {}: {}", line.number + 1, warning_description(&line.result));
+        } else {
+            eprintln!("line {}: {}, kept as written", line.number + 1, warning_description(&line.result));
+            unparsed = true;
+        }
+    }
+    Ok(unparsed)
+}
+
+/// `value` as a JSON string
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// A config as a JSON object with an `options` array, each option with its
+/// line, name, arguments and inline file contents, and a `warnings` array
+fn write_json<W: Write>(config: &ParsedConfigFile, out: &mut W) -> io::Result<()> {
+    let mut options = Vec::new();
+    for line in config.success_lines.iter() {
+        // The arguments as they'd be written in a config file
+        let raw = ovpnfile::tokenize(line.result.as_ovpn_config().as_bytes())
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let raw = match raw.lines.into_iter().next() {
+            Some(raw) => raw,
+            None => continue,
+        };
+        let args: Vec<String> = raw.args.iter().map(|a| json_string(a)).collect();
+        let inline = raw.inline_contents.as_ref().map_or("null".to_string(), |c| json_string(c));
+        options.push(format!(
+            "{{\"line\": {}, \"option\": {}, \"args\": [{}], \"inline\": {}}}",
+            line.number + 1, json_string(line.result.openvpn_option_name()), args.join(", "), inline,
+        ));
+    }
+    let warnings: Vec<String> = config.warning_lines.iter().map(|line| format!(
+        "{{\"line\": {}, \"warning\": {}}}",
+        line.number + 1, json_string(warning_description(&line.result)),
+    )).collect();
+    writeln!(out, "{{\"options\": [{}], \"warnings\": [{}]}}", options.join(", "), warnings.join(", "))
+}
+
+fn run(args: &[String]) -> Result<i32, String> {
+    let command = args.first().ok_or_else(|| USAGE.to_string())?;
+    let flags: Vec<&str> = args[1..].iter().filter(|a| a.starts_with("--")).map(|a| &a[..]).collect();
+    let paths: Vec<&str> = args[1..].iter().filter(|a| !a.starts_with("--")).map(|a| &a[..]).collect();
+    let allowed_flags: &[&str] = match &command[..] {
+        "lint" => &["--security"],
+        "fmt" => &["--normalize"],
+        "inline" | "json" => &[],
+        _ => return Err(USAGE.to_string()),
+    };
+    if paths.len() > 1 || flags.iter().any(|f| !allowed_flags.contains(f)) {
+        return Err(USAGE.to_string());
+    }
+    let path = paths.first().cloned();
+    let input = read_input(path).map_err(|e| format!("{}: {}", path.unwrap_or("stdin"), e))?;
+    let config = ovpnfile::parse_bytes(&input).map_err(|e| e.to_string())?;
+    let document = ConfigDocument::parse(&String::from_utf8_lossy(&input));
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let written = match &command[..] {
+        "lint" => {
+            let linter = if flags.contains(&"--security") { Linter::security() } else { Linter::new() };
+            let diagnostics = linter.lint(&config);
+            for diagnostic in diagnostics.iter() {
+                writeln!(out, "{}", diagnostic).map_err(|e| e.to_string())?;
+            }
+            // Fail if openvpn wouldn't accept the config
+            let failed = diagnostics.iter().any(|d| d.severity == Severity::Error);
+            return Ok(if failed { 1 } else { 0 });
+        },
+        "fmt" if flags.contains(&"--normalize") => {
+            // Normalizing moves options around, so there's nowhere to keep
+            // the comments
+            if document.entries().iter().any(|e| e.comment().is_some()) {
+                return Err(format!("{}: --normalize would remove the comments", path.unwrap_or("stdin")));
+            }
+            write_config(&config.normalize(), &mut out)
+        },
+        "fmt" => write_document(&document, &mut out),
+        "inline" => {
+            let base_dir = path.and_then(|p| Path::new(p).parent()).unwrap_or_else(|| Path::new("."));
+            let inlined = config.inlined(base_dir).map_err(|e| e.to_string())?;
+            write_config(&inlined, &mut out)
+        },
+        _ => write_json(&config, &mut out).map(|_| false),
+    };
+    // Fail if the output isn't the whole config
+    written.map(|left_out| if left_out { 1 } else { 0 }).map_err(|e| e.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => process::exit(code),
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        },
+    }
+}
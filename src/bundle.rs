@@ -0,0 +1,354 @@
+//! Converting between unified profiles, which have their certificates and
+//! keys inline, and profiles which refer to separate files
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use errors::{self, ResultExt};
+use base64;
+use {ConfigDirective, ConfigLine, File, FileReference, FileUse, ParsedConfigFile};
+
+/// The extension of the file an inline option's contents are written to
+fn file_extension(option: &str) -> &'static str {
+    match option {
+        "ca" | "cert" | "extra-certs" => "crt",
+        "dh" | "crl-verify" => "pem",
+        "pkcs12" => "p12",
+        "http-proxy-user-pass" => "txt",
+        _ => "key",
+    }
+}
+
+/// The contents of a file as they are put inline for `option`
+fn inline_contents(option: &str, contents: &[u8]) -> String {
+    if option == "pkcs12" {
+        base64::encode(contents)
+    } else {
+        String::from_utf8_lossy(contents).lines().collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Write `contents` to a new file at `path`, which on unix only the owner
+/// can read if `private`. Fails if there's already a file at `path`, rather
+/// than overwriting it or leaving it with its old permissions.
+fn write_new(path: &Path, contents: &[u8], private: bool) -> ::std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    if private {
+        #[cfg(unix)]
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+/// Give the direction of each inline `tls-auth` and `secret` with a
+/// `key-direction` after it, as an inline file can't have a direction
+/// argument. This is the reverse of what `split_profile` does.
+fn inline_key_directions(config: &mut ParsedConfigFile) {
+    let mut success_lines = Vec::with_capacity(config.success_lines.len());
+    for mut line in config.success_lines.drain(..) {
+        let key_direction = match line.result {
+            ConfigDirective::TlsAuth{file: File::InlineFileContents(_), ref mut direction} |
+            ConfigDirective::Secret{file: File::InlineFileContents(_), ref mut direction} => direction.take(),
+            _ => None,
+        };
+        let number = line.number;
+        success_lines.push(line);
+        if let Some(direction) = key_direction {
+            success_lines.push(ConfigLine{number, result: ConfigDirective::KeyDirection{direction}});
+        }
+    }
+    config.success_lines = success_lines;
+}
+
+impl ParsedConfigFile {
+    /// This config with the file of every option which can have inline file
+    /// contents, such as `ca`, `key` or `tls-crypt`, read and put inline,
+    /// making a single self contained profile. Relative paths are read from
+    /// `base_dir`, which would normally be the directory of the config file.
+    /// A `pkcs12` bundle is base64 encoded, as openvpn expects inline. The
+    /// direction of an inlined `tls-auth` or `secret` is given by a
+    /// `key-direction` after it, with the option's line number.
+    pub fn inlined<P: AsRef<Path>>(&self, base_dir: P) -> errors::Result<ParsedConfigFile> {
+        let mut config = ParsedConfigFile{
+            success_lines: self.success_lines.clone(),
+            warning_lines: self.warning_lines.clone(),
+        };
+        for line in config.success_lines.iter_mut() {
+            let option = line.result.openvpn_option_name();
+            if let Some(file) = line.result.file_mut() {
+                let contents = match *file {
+                    File::FilePath(ref path) => {
+                        fs::read(base_dir.as_ref().join(path)).chain_err(|| format!("Error reading {}", path))?
+                    },
+                    _ => continue,
+                };
+                *file = File::InlineFileContents(inline_contents(option, &contents));
+            }
+        }
+        inline_key_directions(&mut config);
+        Ok(config)
+    }
+
+    /// The inverse of `inlined`, this config with the contents of every
+    /// inline file written to a file in `dir` and the option changed to
+    /// refer to it. Files are named after their option, e.g `ca.crt` or
+    /// `tls-crypt.key`, with the line number added if an option is used
+    /// more than once, and on unix can only be read by their owner. A
+    /// `pkcs12` bundle is base64 decoded. Existing files are never
+    /// overwritten, it's an error if one of the files is already in `dir`.
+    pub fn externalized<P: AsRef<Path>>(&self, dir: P) -> errors::Result<ParsedConfigFile> {
+        write_inline_files(self, dir.as_ref(), |option| option, true)
+    }
+}
+
+/// Write the contents of every inline file in `config` to a file in `dir`
+/// named `stem(option)` with the option's extension, and change the option
+/// to refer to it, by its full path if `full_paths` or else by its name
+fn write_inline_files(config: &ParsedConfigFile, dir: &Path, stem: fn(&'static str) -> &'static str, full_paths: bool)
+    -> errors::Result<ParsedConfigFile>
+{
+    let mut config = ParsedConfigFile{
+        success_lines: config.success_lines.clone(),
+        warning_lines: config.warning_lines.clone(),
+    };
+    let mut names = HashSet::new();
+    for line in config.success_lines.iter_mut() {
+        let option = line.result.openvpn_option_name();
+        if let Some(file) = line.result.file_mut() {
+            let contents = match *file {
+                File::InlineFileContents(ref contents) if option == "pkcs12" => {
+                    base64::decode(contents.split_whitespace().collect::<String>())
+                        .chain_err(|| format!("Invalid base64 in inline {}", option))?
+                },
+                File::InlineFileContents(ref contents) => format!("{}\n", contents).into_bytes(),
+                _ => continue,
+            };
+            let mut name = format!("{}.{}", stem(option), file_extension(option));
+            if !names.insert(name.clone()) {
+                name = format!("{}-{}.{}", stem(option), line.number, file_extension(option));
+            }
+            let path = dir.join(&name);
+            write_new(&path, &contents, true).chain_err(|| format!("Error writing {}", path.display()))?;
+            *file = File::FilePath(if full_paths { path.to_string_lossy().into_owned() } else { name });
+        }
+    }
+    Ok(config)
+}
+
+/// The name a file is given by `split_profile`, following the usual layout
+/// of `/etc/openvpn`
+fn profile_file_stem(option: &'static str) -> &'static str {
+    match option {
+        "cert" | "key" | "pkcs12" => "client",
+        "tls-auth" => "ta",
+        "secret" => "static",
+        "crl-verify" => "crl",
+        "http-proxy-user-pass" => "proxy-auth",
+        option => option,
+    }
+}
+
+/// Split a unified profile into a config file named `name.conf` and the
+/// files it refers to, `ca.crt`, `client.crt`, `client.key`, `ta.key` and
+/// so on, all written to `dir`, the layout `/etc/openvpn` uses. The config
+/// refers to the files by name, so openvpn should be run from `dir`. A
+/// line number is added to the name of a file if its option is used more
+/// than once. Any `key-direction` is also given as the direction argument
+/// of `tls-auth` and `secret`, so it still applies if the `key-direction`
+/// is removed. As with `externalized` existing files are never overwritten.
+/// Returns the path of the config file.
+pub fn split_profile<P: AsRef<Path>>(profile: &ParsedConfigFile, dir: P, name: &str) -> errors::Result<PathBuf> {
+    let dir = dir.as_ref();
+    let mut config = write_inline_files(profile, dir, profile_file_stem, false)?;
+    let key_direction = config.success_lines.iter().rev().find_map(|line| match line.result {
+        ConfigDirective::KeyDirection{ref direction} => Some(direction.clone()),
+        _ => None,
+    });
+    if let Some(key_direction) = key_direction {
+        for line in config.success_lines.iter_mut() {
+            match line.result {
+                ConfigDirective::TlsAuth{ref mut direction, ..} |
+                ConfigDirective::Secret{ref mut direction, ..} if direction.is_none() => {
+                    *direction = Some(key_direction.clone());
+                },
+                _ => {},
+            }
+        }
+    }
+    let contents: String = config.iter()
+        .map(|directive| format!("{}\n", directive.as_ovpn_config()))
+        .collect();
+    let path = dir.join(format!("{}.conf", name));
+    write_new(&path, contents.as_bytes(), false).chain_err(|| format!("Error writing {}", path.display()))?;
+    Ok(path)
+}
+
+/// The file `assemble_profile` puts inline for a directive, that of an
+/// option which can have inline file contents or the credentials file of
+/// `auth-user-pass`, unless openvpn is to prompt for them
+fn assembled_file(directive: &mut ConfigDirective) -> Option<&mut File> {
+    match *directive {
+        ConfigDirective::AuthUserPass{up: Some(File::FilePath(ref path))} if path == "stdin" => None,
+        ConfigDirective::AuthUserPass{up: Some(ref mut file)} => Some(file),
+        ref mut directive => directive.file_mut(),
+    }
+}
+
+/// Assemble a unified profile from `config` and the contents of the files
+/// it refers to, keyed by the path the config uses, e.g `ca.crt`. Every
+/// option which can have inline file contents, and the credentials file of
+/// `auth-user-pass`, has the contents of its file put inline, with the
+/// direction of `tls-auth` and `secret` given by a `key-direction` as
+/// `ParsedConfigFile::inlined` does. If any of those files are missing from
+/// `files` the options which refer to them are returned instead, so that
+/// the caller can ask for all of them at once.
+pub fn assemble_profile(config: &ParsedConfigFile, files: &HashMap<String, Vec<u8>>)
+    -> Result<ParsedConfigFile, Vec<FileReference>>
+{
+    let mut profile = ParsedConfigFile{
+        success_lines: config.success_lines.clone(),
+        warning_lines: config.warning_lines.clone(),
+    };
+    let mut missing = Vec::new();
+    for line in profile.success_lines.iter_mut() {
+        let option = line.result.openvpn_option_name();
+        if let Some(file) = assembled_file(&mut line.result) {
+            let contents = match *file {
+                File::FilePath(ref path) => match files.get(path) {
+                    Some(contents) => contents,
+                    None => {
+                        missing.push(FileReference{option, line: line.number, path: path.clone(), usage: FileUse::Read});
+                        continue;
+                    },
+                },
+                _ => continue,
+            };
+            *file = File::InlineFileContents(inline_contents(option, contents));
+        }
+    }
+    if missing.is_empty() {
+        inline_key_directions(&mut profile);
+        Ok(profile)
+    } else {
+        Err(missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+    use {parse, parse_str};
+
+    #[test]
+    fn test_externalized() {
+        let dir = env::temp_dir().join(format!("ovpnfile-externalized-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = parse_str(
+            "client\n<ca>\nca contents\n</ca>\n<extra-certs>\none\n</extra-certs>\n<extra-certs>\ntwo\n</extra-certs>\n",
+        );
+        let externalized = config.externalized(&dir).unwrap();
+        let paths: Vec<String> = externalized.directives().iter().skip(1).map(|d| match d.file() {
+            Some(File::FilePath(path)) => path.clone(),
+            _ => panic!("not a file path"),
+        }).collect();
+        assert_eq!(paths, vec![
+            dir.join("ca.crt").to_string_lossy().into_owned(),
+            dir.join("extra-certs.crt").to_string_lossy().into_owned(),
+            dir.join("extra-certs-7.crt").to_string_lossy().into_owned(),
+        ]);
+        assert_eq!(fs::read_to_string(dir.join("extra-certs-7.crt")).unwrap(), "two\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(dir.join("ca.crt")).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert_eq!(externalized.inlined(&dir).unwrap().directives(), config.directives());
+        assert!(config.externalized(&dir).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_profile() {
+        let dir = env::temp_dir().join(format!("ovpnfile-split-profile-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let profile = parse_str(
+            "client\nremote vpn.example.com\n<ca>\nca contents\n</ca>\n<cert>\ncert contents\n</cert>\n\
+             <key>\nkey contents\n</key>\nkey-direction 1\n<tls-auth>\nstatic key\n</tls-auth>\n",
+        );
+        let path = split_profile(&profile, &dir, "work").unwrap();
+        assert_eq!(path, dir.join("work.conf"));
+        let config = parse(fs::File::open(&path).unwrap()).unwrap();
+        let options: Vec<String> = config.directives().iter().map(|d| d.as_ovpn_config()).collect();
+        assert_eq!(options, vec![
+            "client",
+            "remote vpn.example.com",
+            "ca ca.crt",
+            "cert client.crt",
+            "key client.key",
+            "key-direction 1",
+            "tls-auth ta.key 1",
+        ]);
+        assert_eq!(fs::read_to_string(dir.join("client.key")).unwrap(), "key contents\n");
+        let inlined: Vec<String> = config.inlined(&dir).unwrap().directives().iter().map(|d| d.as_ovpn_config()).collect();
+        assert_eq!(&inlined[5..], ["key-direction 1", "<tls-auth>\nstatic key\n</tls-auth>", "key-direction 1"]);
+        fs::remove_file(dir.join("work.conf")).unwrap();
+        assert!(split_profile(&profile, &dir, "work").is_err());
+        assert!(!dir.join("work.conf").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_assemble_profile() {
+        let config = parse_str("client\nca ca.crt\ncert client.crt\nkey client.key\ntls-auth ta.key 1\n\
+                                auth-user-pass creds.txt\n");
+        let mut files = HashMap::new();
+        files.insert("ca.crt".to_string(), b"ca contents\n".to_vec());
+        files.insert("client.crt".to_string(), b"cert contents\n".to_vec());
+        assert_eq!(assemble_profile(&config, &files).err(), Some(vec![
+            FileReference{option: "key", line: 3, path: "client.key".to_string(), usage: FileUse::Read},
+            FileReference{option: "tls-auth", line: 4, path: "ta.key".to_string(), usage: FileUse::Read},
+            FileReference{option: "auth-user-pass", line: 5, path: "creds.txt".to_string(), usage: FileUse::Read},
+        ]));
+
+        files.insert("client.key".to_string(), b"key contents\n".to_vec());
+        files.insert("ta.key".to_string(), b"static key\n".to_vec());
+        files.insert("creds.txt".to_string(), b"user\npass\n".to_vec());
+        let profile = assemble_profile(&config, &files).unwrap();
+        let text: String = profile.directives().iter().map(|d| format!("{}\n", d.as_ovpn_config())).collect();
+        assert_eq!(text, "client\n<ca>\nca contents\n</ca>\n<cert>\ncert contents\n</cert>\n<key>\nkey contents\n</key>\n\
+                          <tls-auth>\nstatic key\n</tls-auth>\nkey-direction 1\n<auth-user-pass>\nuser\npass\n</auth-user-pass>\n");
+
+        let config = parse_str("client\nauth-user-pass\nauth-user-pass stdin\n");
+        assert_eq!(assemble_profile(&config, &HashMap::new()).unwrap().directives(), config.directives());
+    }
+
+    #[test]
+    fn test_inlined() {
+        let base_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/certs");
+        let config = parse_str("client\nca ca.crt\n<key>\nkey contents\n</key>\ndh none\n");
+        let inlined = config.inlined(base_dir).unwrap();
+        let ca = include_str!("../tests/certs/ca.crt").trim_end();
+        assert_eq!(inlined.directives()[1].as_ovpn_config(), format!("<ca>\n{}\n</ca>", ca));
+        assert_eq!(inlined.directives()[2], config.directives()[2]);
+        assert_eq!(inlined.directives()[3], config.directives()[3]);
+        assert_eq!(inlined.success_lines[1].number, 1);
+
+        let config = parse_str("client\ntls-auth ca.key 1\nsecret other.key 0\n");
+        let inlined = config.inlined(base_dir).unwrap();
+        let text: String = inlined.directives().iter().map(|d| format!("{}\n", d.as_ovpn_config())).collect();
+        let ca_key = include_str!("../tests/certs/ca.key").trim_end();
+        let other_key = include_str!("../tests/certs/other.key").trim_end();
+        assert_eq!(text, format!(
+            "client\n<tls-auth>\n{}\n</tls-auth>\nkey-direction 1\n<secret>\n{}\n</secret>\nkey-direction 0\n",
+            ca_key, other_key,
+        ));
+        assert_eq!(inlined.success_lines[2].number, 1);
+
+        let config = parse_str("cert missing.crt\n");
+        assert!(config.inlined(base_dir).is_err());
+    }
+}
@@ -1,7 +1,21 @@
 
+use std::time::Duration;
+use std::net::Ipv4Addr;
+use arg_types::{
+    Mode, HttpProxyAuth, HttpProxyAuthMethod, X509Field, EchoCommand, TlsCryptV2CookieMode,
+    DnsSecMode, DnsTransport, TlsCertProfile, VlanAccept, VlanId,
+    WindowsDriver, GenkeyType, HashAlgorithm, CertHash,
+    MssfixFlag, AuthGenTokenFlag, FragmentFlag, CrlVerifyFlag,
+    IpWin32Method,
+};
+use version::{Version, OptionVersions};
+use spec::{ArgumentSpec, DirectiveSpec};
+use {ParseWarning, split_args};
+
 pub enum LineParseResult {
     NoMatchingCommand,
     NotEnoughArguments,
+    InvalidArgument,
     Success(ConfigDirective),
 }
 
@@ -9,7 +23,43 @@ pub enum LineParseResult {
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum ServerBridgeArg {
     NoGateway,
-    GatewayConfig{gateway: String, netmask: String, pool_start_ip: String, pool_end_ip: String},
+    GatewayConfig{gateway: Ipv4Addr, netmask: Ipv4Addr, pool_start_ip: Ipv4Addr, pool_end_ip: Ipv4Addr},
+    /// The gateway and netmask without an address pool, clients obtain an
+    /// address using DHCP
+    DhcpProxy{gateway: Ipv4Addr, netmask: Ipv4Addr},
+}
+
+/// Whether `netmask` is made up of contiguous one bits followed by zero bits
+pub(crate) fn is_valid_netmask(netmask: Ipv4Addr) -> bool {
+    let bits = u32::from(netmask);
+    bits.leading_ones() + bits.trailing_zeros() == 32
+}
+
+fn parse_server_bridge(args: &[&str]) -> LineParseResult {
+    if args.len() == 1 && args[0] == "nogw" {
+        return LineParseResult::Success(ConfigDirective::ServerBridge(ServerBridgeArg::NoGateway));
+    }
+    if args.len() != 2 && args.len() != 4 {
+        return LineParseResult::NotEnoughArguments;
+    }
+    let addresses: Vec<Ipv4Addr> = match args.iter().map(|a| parse_arg(a)).collect() {
+        Some(addresses) => addresses,
+        None => return LineParseResult::InvalidArgument,
+    };
+    let (gateway, netmask) = (addresses[0], addresses[1]);
+    if !is_valid_netmask(netmask) {
+        return LineParseResult::InvalidArgument;
+    }
+    let arg = if addresses.len() == 4 {
+        let (pool_start_ip, pool_end_ip) = (addresses[2], addresses[3]);
+        if pool_start_ip > pool_end_ip {
+            return LineParseResult::InvalidArgument;
+        }
+        ServerBridgeArg::GatewayConfig{gateway, netmask, pool_start_ip, pool_end_ip}
+    } else {
+        ServerBridgeArg::DhcpProxy{gateway, netmask}
+    };
+    LineParseResult::Success(ConfigDirective::ServerBridge(arg))
 }
 
 /// Arguments for any option which can have inline file contents
@@ -17,6 +67,202 @@ pub enum ServerBridgeArg {
 pub enum File {
     FilePath(String),
     InlineFileContents(String),
+    /// `dh none`, which disables static DH parameters. No other option
+    /// accepts `none` in place of a file.
+    None,
+}
+
+/// The `File` for the first argument of an inline file option
+fn file_arg(command: &str, arg: &str) -> File {
+    match (command, arg) {
+        ("dh", "none") => File::None,
+        (_, path) => File::FilePath(path.to_string()),
+    }
+}
+
+/// Conversion between an argument in the config file and it's typed
+/// representation.
+pub(crate) trait ArgValue: Sized {
+    /// Parse an argument, `None` if the argument is not valid for this type
+    fn from_arg(arg: &str) -> Option<Self>;
+    /// The argument as it would appear in a config file
+    fn to_arg(&self) -> String;
+}
+
+// Implements `ArgValue` in terms of `FromStr` and `Display`
+macro_rules! arg_value_from_str {
+    ($($argtype:ty),* $(,)*) => {
+        $(
+            impl ArgValue for $argtype {
+                fn from_arg(arg: &str) -> Option<$argtype> {
+                    arg.parse().ok()
+                }
+                fn to_arg(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+arg_value_from_str!(
+    String, i8, i16, u8, u16, u64, Ipv4Addr,
+    Mode, HttpProxyAuth, HttpProxyAuthMethod, X509Field, TlsCryptV2CookieMode,
+    DnsSecMode, DnsTransport, TlsCertProfile, VlanAccept, VlanId,
+    WindowsDriver, GenkeyType, HashAlgorithm, CertHash,
+    MssfixFlag, AuthGenTokenFlag, FragmentFlag, CrlVerifyFlag,
+    IpWin32Method,
+);
+
+/// Flags are written as `0` or `1` in config files
+impl ArgValue for bool {
+    fn from_arg(arg: &str) -> Option<bool> {
+        match arg {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        }
+    }
+    fn to_arg(&self) -> String {
+        if *self { "1" } else { "0" }.to_string()
+    }
+}
+
+/// Durations are written as a number of seconds in config files
+impl ArgValue for Duration {
+    fn from_arg(arg: &str) -> Option<Duration> {
+        arg.parse().ok().map(Duration::from_secs)
+    }
+    fn to_arg(&self) -> String {
+        self.as_secs().to_string()
+    }
+}
+
+/// A file given as an argument, inline contents are written as a block
+/// rather than an argument so have no argument of their own
+impl ArgValue for File {
+    fn from_arg(arg: &str) -> Option<File> {
+        Some(File::FilePath(arg.to_string()))
+    }
+    fn to_arg(&self) -> String {
+        match *self {
+            File::FilePath(ref path) => path.clone(),
+            File::InlineFileContents(_) => String::new(),
+            File::None => "none".to_string(),
+        }
+    }
+}
+
+/// Parse a single argument into it's typed representation, `None` if the
+/// argument is not valid for that type.
+fn parse_arg<T: ArgValue>(arg: &str) -> Option<T> {
+    T::from_arg(arg)
+}
+
+/// Quote an argument if necessary so that it is read back as a single
+/// argument.
+pub(crate) fn quote_arg(arg: String) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\' || c == '\'') {
+        return arg
+    }
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Arguments of the --dns option
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum DnsDirective {
+    /// `dns search-domains domain [domain ...]`
+    SearchDomains(Vec<String>),
+    /// `dns server n <option>`, `priority` is the `n` which orders the
+    /// servers, lower values are preferred
+    Server{priority: i8, option: DnsServerOption},
+}
+
+/// The settings of a `dns server n` option
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum DnsServerOption {
+    /// `address addr[:port] [addr[:port] ...]`
+    Address(Vec<String>),
+    /// `resolve-domains domain [domain ...]`
+    ResolveDomains(Vec<String>),
+    /// `dnssec yes|optional|no`
+    Dnssec(DnsSecMode),
+    /// `transport plain|DoH|DoT`
+    Transport(DnsTransport),
+    /// `sni server-name`
+    Sni(String),
+}
+
+impl DnsDirective {
+    fn arg_values(&self) -> Vec<String> {
+        match *self {
+            DnsDirective::SearchDomains(ref domains) => {
+                let mut result = vec!["search-domains".to_string()];
+                result.extend(domains.iter().cloned());
+                result
+            },
+            DnsDirective::Server{priority, ref option} => {
+                let mut result = vec!["server".to_string(), priority.to_string()];
+                match *option {
+                    DnsServerOption::Address(ref addresses) => {
+                        result.push("address".to_string());
+                        result.extend(addresses.iter().cloned());
+                    },
+                    DnsServerOption::ResolveDomains(ref domains) => {
+                        result.push("resolve-domains".to_string());
+                        result.extend(domains.iter().cloned());
+                    },
+                    DnsServerOption::Dnssec(mode) => {
+                        result.push("dnssec".to_string());
+                        result.push(mode.to_arg());
+                    },
+                    DnsServerOption::Transport(transport) => {
+                        result.push("transport".to_string());
+                        result.push(transport.to_arg());
+                    },
+                    DnsServerOption::Sni(ref name) => {
+                        result.push("sni".to_string());
+                        result.push(name.clone());
+                    },
+                }
+                result
+            },
+        }
+    }
+}
+
+fn parse_dns(args: &[&str]) -> LineParseResult {
+    let to_strings = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<String>>();
+    let directive = match args {
+        ["search-domains", domains @ ..] if !domains.is_empty() => {
+            DnsDirective::SearchDomains(to_strings(domains))
+        },
+        ["server", priority, option, values @ ..] if !values.is_empty() => {
+            let priority = match parse_arg(priority) {
+                Some(priority) => priority,
+                None => return LineParseResult::InvalidArgument,
+            };
+            let option = match (*option, values) {
+                ("address", addresses) => DnsServerOption::Address(to_strings(addresses)),
+                ("resolve-domains", domains) => DnsServerOption::ResolveDomains(to_strings(domains)),
+                ("dnssec", [mode]) => match parse_arg(mode) {
+                    Some(mode) => DnsServerOption::Dnssec(mode),
+                    None => return LineParseResult::InvalidArgument,
+                },
+                ("transport", [transport]) => match parse_arg(transport) {
+                    Some(transport) => DnsServerOption::Transport(transport),
+                    None => return LineParseResult::InvalidArgument,
+                },
+                ("sni", [name]) => DnsServerOption::Sni(name.to_string()),
+                _ => return LineParseResult::InvalidArgument,
+            };
+            DnsDirective::Server{priority, option}
+        },
+        ["search-domains"] | ["server", ..] => return LineParseResult::NotEnoughArguments,
+        [] => return LineParseResult::NotEnoughArguments,
+        _ => return LineParseResult::InvalidArgument,
+    };
+    LineParseResult::Success(ConfigDirective::Dns(directive))
 }
 
 // This macro courtesy of https://stackoverflow.com/questions/44160750/how-to-generate-complex-enum-variants-with-a-macro-in-rust
@@ -25,6 +271,10 @@ macro_rules! define_config_directives {
     (@single $($_tt:tt)*) => {()};
     (@count $($tts:tt),*) => {<[()]>::len(&[$(define_config_directives!(@single $tts)),*])};
 
+    // Argument type rules, arguments without an explicit type are `String`s
+    (@argtype) => {String};
+    (@argtype $argtype:ty) => {$argtype};
+
     // Start rule.
     // Note: `$(,)*` is a trick to eat any number of trailing commas.
     ( $( {$($cmd:tt)*} ),* $(,)*) => {
@@ -56,38 +306,50 @@ macro_rules! define_config_directives {
         pub enum ConfigDirective {
             $($eout)*
                 ServerBridge(ServerBridgeArg),
+                Dns(DnsDirective),
+                /// A `<connection>` block, with the options in it
+                Connection(Vec<ConfigDirective>),
         }
 
         impl ConfigDirective {
-            #[cfg_attr(feature="cargo-clippy", allow(let_and_return))]
+            #[allow(clippy::let_and_return)]
             fn required_arg_values(&self) -> Vec<String> {
                 match *self {
                     $($argsout)*
                         ConfigDirective::ServerBridge(ServerBridgeArg::NoGateway) => vec!["nogw".to_string()],
                         ConfigDirective::ServerBridge(ServerBridgeArg::GatewayConfig{
-                            ref gateway, ref netmask, ref pool_start_ip, ref pool_end_ip
-                        }) => vec![gateway.clone(), netmask.clone(), pool_start_ip.clone(), pool_end_ip.clone()]
+                            gateway, netmask, pool_start_ip, pool_end_ip
+                        }) => vec![gateway.to_arg(), netmask.to_arg(), pool_start_ip.to_arg(), pool_end_ip.to_arg()],
+                        ConfigDirective::ServerBridge(ServerBridgeArg::DhcpProxy{gateway, netmask}) => {
+                            vec![gateway.to_arg(), netmask.to_arg()]
+                        },
+                        ConfigDirective::Dns(ref dns) => dns.arg_values(),
+                        ConfigDirective::Connection(_) => Vec::new(),
                 }
             }
-            #[cfg_attr(feature="cargo-clippy", allow(let_and_return))]
+            #[allow(clippy::let_and_return)]
             fn optional_arg_values(&self) -> Vec<String> {
                 match *self {
                     $($oargsout)*
                         ConfigDirective::ServerBridge(_) => Vec::new(),
+                        ConfigDirective::Dns(_) => Vec::new(),
+                        ConfigDirective::Connection(_) => Vec::new(),
                 }
             }
             /// The option name this directive was constructed from
-            #[cfg_attr(feature="cargo-clippy", allow(let_and_return))]
-            pub fn openvpn_option_name(&self) -> &str {
+            #[allow(clippy::let_and_return)]
+            pub fn openvpn_option_name(&self) -> &'static str {
                 match *self {
                     $($commandname_out)*
                         ConfigDirective::ServerBridge(_) => "server-bridge",
+                        ConfigDirective::Dns(_) => "dns",
+                        ConfigDirective::Connection(_) => "connection",
                 }
             }
             /// The line this directive would appear as in a config file. For
             /// directives with inline file contents this will appear as multiple
             /// lines exactly as in the config file.
-            #[cfg_attr(feature="cargo-clippy", allow(let_and_return))]
+            #[allow(clippy::let_and_return)]
             pub fn as_ovpn_config(&self) -> String {
                 match *self {
                     ConfigDirective::Ca{file: File::InlineFileContents(ref contents), ..} => {
@@ -120,15 +382,28 @@ macro_rules! define_config_directives {
                     ConfigDirective::TlsCrypt{file: File::InlineFileContents(ref contents), ..} => {
                         inline_file_contents(self.openvpn_option_name(), contents)
                     },
+                    ConfigDirective::TlsCryptV2{file: File::InlineFileContents(ref contents), ..} => {
+                        inline_file_contents(self.openvpn_option_name(), contents)
+                    },
+                    ConfigDirective::AuthUserPass{up: Some(File::InlineFileContents(ref contents))} => {
+                        inline_file_contents(self.openvpn_option_name(), contents)
+                    },
+                    ConfigDirective::Connection(ref directives) => {
+                        let options: Vec<String> = directives.iter().map(|d| d.as_ovpn_config()).collect();
+                        inline_file_contents(self.openvpn_option_name(), &options.join("\n"))
+                    },
+                    ConfigDirective::PeerFingerprint{ref fingerprints} if fingerprints.len() > 1 => {
+                        inline_file_contents(self.openvpn_option_name(), &fingerprints.join("\n"))
+                    },
                     ConfigDirective::Secret{file: File::InlineFileContents(ref contents), ..} => {
                         inline_file_contents(self.openvpn_option_name(), contents)
                     },
-                    _ => format!(
-                        "{} {} {}",
-                        self.openvpn_option_name(),
-                        self.required_arg_values().join(" "),
-                        self.optional_arg_values().join(" ")
-                        )
+                    _ => {
+                        let mut parts = vec![self.openvpn_option_name().to_string()];
+                        parts.extend(self.required_arg_values().into_iter().map(quote_arg));
+                        parts.extend(self.optional_arg_values().into_iter().map(quote_arg));
+                        parts.join(" ")
+                    },
                 }
             }
         }
@@ -140,18 +415,8 @@ macro_rules! define_config_directives {
         pub fn parse_line(command: &str, $args: &[&str]) -> LineParseResult {
             match command {
                 $($pout)*
-                    "server-bridge" => {
-                        match $args.len() {
-                            1 if $args[0] == "nogw" => LineParseResult::Success(ConfigDirective::ServerBridge(ServerBridgeArg::NoGateway)),
-                            4 => LineParseResult::Success(ConfigDirective::ServerBridge(ServerBridgeArg::GatewayConfig{
-                                gateway: $args[0].to_string(),
-                                netmask: $args[1].to_string(),
-                                pool_start_ip: $args[2].to_string(),
-                                pool_end_ip: $args[3].to_string(),
-                            })),
-                            _ => LineParseResult::NotEnoughArguments
-                        }
-                    },
+                    "server-bridge" => parse_server_bridge($args),
+                    "dns" => parse_dns($args),
                 _ => LineParseResult::NoMatchingCommand
             }
         }
@@ -201,8 +466,8 @@ macro_rules! define_config_directives {
         {
             command: $sname:expr,
             rust_name: $rname:ident,
-            args: [$($args:ident),* $(,)*],
-            optional_args: [$($oargs:ident),* $(,)*] $(,)*
+            args: [$($args:ident $(: $argtype:ty)?),* $(,)*],
+            optional_args: [$($oargs:ident $(: $oargtype:ty)?),* $(,)*] $(,)*
         },
         $($tail:tt)*
     ) => {
@@ -210,7 +475,10 @@ macro_rules! define_config_directives {
             @parse
             {
                 $($eout)*
-                    $rname { $( $args: String, )* $( $oargs: Option<String>, )* },
+                    $rname {
+                        $( $args: define_config_directives!(@argtype $($argtype)?), )*
+                        $( $oargs: Option<define_config_directives!(@argtype $($oargtype)?)>, )*
+                    },
             },
             ($pargs){
                 $($pout)*
@@ -223,13 +491,28 @@ macro_rules! define_config_directives {
                             // regular integers.  We'll just use a mutable index
                             // instead.
                             let mut i = 0;
-                            $(let $args = $pargs[i].into(); i += 1;)*
-                                $(let $oargs = $pargs.get(i).map(|&s| s.into()); i += 1;)*
-                                let _ = i; // avoid unused assignment warnings.
+                            $(
+                                let $args: define_config_directives!(@argtype $($argtype)?) = match parse_arg($pargs[i]) {
+                                    Some(value) => value,
+                                    None => return LineParseResult::InvalidArgument,
+                                };
+                                i += 1;
+                            )*
+                            $(
+                                let $oargs: Option<define_config_directives!(@argtype $($oargtype)?)> = match $pargs.get(i) {
+                                    Some(arg) => match parse_arg(arg) {
+                                        Some(value) => Some(value),
+                                        None => return LineParseResult::InvalidArgument,
+                                    },
+                                    None => None,
+                                };
+                                i += 1;
+                            )*
+                            let _ = i; // avoid unused assignment warnings.
 
                             LineParseResult::Success(ConfigDirective::$rname {
-                                $($args: $args,)*
-                                    $($oargs: $oargs,)*
+                                $($args,)*
+                                $($oargs,)*
                             })
                         }
                     },
@@ -241,16 +524,14 @@ macro_rules! define_config_directives {
             {
                 $($argsout)*
                     ConfigDirective::$rname{$(ref $args,)*..} => {
-                        let mut _result = Vec::new();
-                        $(_result.push($args.clone());)*
-                            _result
+                        vec![$($args.to_arg()),*]
                     },
             },
             {
                 $($oargsout)*
                     ConfigDirective::$rname{$(ref $oargs,)* ..} => {
                         let mut _result = Vec::new();
-                        $(if let Some(ref thing) = *$oargs { _result.push(thing.clone())})*
+                        $(if let Some(ref thing) = *$oargs { _result.push(thing.to_arg())})*
                             _result
                     },
             };
@@ -380,7 +661,7 @@ macro_rules! define_config_directives {
                             LineParseResult::NotEnoughArguments
                         } else {
                             LineParseResult::Success(ConfigDirective::$rname {
-                                file: File::FilePath($pargs[0].to_string()),
+                                file: file_arg($sname, $pargs[0]),
                             })
                         }
                     },
@@ -397,6 +678,9 @@ macro_rules! define_config_directives {
                     ConfigDirective::$rname{file: File::InlineFileContents(_)} => {
                         Vec::new()
                     },
+                    ConfigDirective::$rname{file: File::None} => {
+                        vec!["none".to_string()]
+                    },
             },
             {
                 $($oargsout)*
@@ -414,7 +698,7 @@ macro_rules! define_config_directives {
             command: $sname:expr,
             rust_name: $rname:ident,
             inline_file: true,
-            optional_args: [$($oargs:ident),* $(,)*] $(,)*
+            optional_args: [$($oargs:ident $(: $oargtype:ty)?),* $(,)*] $(,)*
         },
         $($tail:tt)*
     ) => {
@@ -422,7 +706,7 @@ macro_rules! define_config_directives {
             @parse
             {
                 $($eout)*
-                    $rname { file: File, $($oargs: Option<String>, )*},
+                    $rname { file: File, $($oargs: Option<define_config_directives!(@argtype $($oargtype)?)>, )*},
             },
             ($pargs){
                 $($pout)*
@@ -430,14 +714,23 @@ macro_rules! define_config_directives {
                         if $pargs.len() < 1 {
                             LineParseResult::NotEnoughArguments
                         } else {
-                            let filename = File::FilePath($pargs[0].to_string());
+                            let filename = file_arg($sname, $pargs[0]);
                             let mut i = 1;
-                            $(let $oargs = $pargs.get(i).map(|&s| s.into()); i += 1;)*
-                                let _ = i; // avoid unused assignment warnings.
+                            $(
+                                let $oargs: Option<define_config_directives!(@argtype $($oargtype)?)> = match $pargs.get(i) {
+                                    Some(arg) => match parse_arg(arg) {
+                                        Some(value) => Some(value),
+                                        None => return LineParseResult::InvalidArgument,
+                                    },
+                                    None => None,
+                                };
+                                i += 1;
+                            )*
+                            let _ = i; // avoid unused assignment warnings.
 
                             LineParseResult::Success(ConfigDirective::$rname {
                                 file: filename,
-                                $($oargs: $oargs,)*
+                                $($oargs,)*
                             })
                         }
                     },
@@ -454,13 +747,16 @@ macro_rules! define_config_directives {
                     ConfigDirective::$rname{file: File::InlineFileContents(..), ..} => {
                         Vec::new()
                     },
+                    ConfigDirective::$rname{file: File::None, ..} => {
+                        vec!["none".to_string()]
+                    },
             },
             {
                 $($oargsout)*
                     ConfigDirective::$rname{$(ref $oargs,)* ..} => {
                         let mut _result = Vec::new();
-                        $(if let Some(ref thing) = *$oargs { _result.push(thing.clone()) })*
-                            Vec::new()
+                        $(if let Some(ref thing) = *$oargs { _result.push(thing.to_arg()) })*
+                            _result
                     },
             };
             $($tail)*
@@ -468,272 +764,227 @@ macro_rules! define_config_directives {
     };
 }
 
-define_config_directives!{
-    {command: "help", rust_name: Help, args: [], optional_args: []},
-    {command: "config", rust_name: Config, args: [file], optional_args: []},
-    {command: "mode", rust_name: Mode, args: [m], optional_args: []},
-    {command: "local", rust_name: Local, args: [host], optional_args: []},
-    {command: "remote", rust_name: Remote, args: [host], optional_args: [port, proto]},
-    {command: "remote-random-hostname", rust_name: RemoteRandomHostname, args: [], optional_args: []},
-    {command: "proto-force", rust_name: ProtoForce, args: [p], optional_args: []},
-    {command: "remote-random", rust_name: RemoteRandom, args: [], optional_args: []},
-    {command: "proto", rust_name: Proto, args: [p], optional_args: []},
-    {command: "connect-retry", rust_name: ConnectRetry, args: [n], optional_args: [max]},
-    {command: "connect-retry-max", rust_name: ConnectRetryMax, args: [n], optional_args: []},
-    {command: "show-proxy-settings", rust_name: ShowProxySettings, args: [], optional_args: []},
-    {command: "http-proxy", rust_name: HttpProxy, args: [server, port], optional_args: [authfile_or_auto_or_auto_nct, auth_method]},
-    {command: "http-proxy-option", rust_name: HttpProxyOption, args: [http_proxy_option_type], optional_args: [parm]},
-    {command: "http-proxy-user-type", rust_name: HttpProxyUserPass, inline_file: true},
-    {command: "socks-proxy", rust_name: SocksProxy, args: [server], optional_args: [port, authfile]},
-    {command: "resolv-retry", rust_name: ResolvRetry, args: [n], optional_args: []},
-    {command: "float", rust_name: Float, args: [], optional_args: []},
-    {command: "ipchange", rust_name: Ipchange, args: [cmd], optional_args: []},
-    {command: "port", rust_name: Port, args: [port], optional_args: []},
-    {command: "lport", rust_name: Lport, args: [port], optional_args: []},
-    {command: "rport", rust_name: Rport, args: [port], optional_args: []},
-    {command: "bind", rust_name: Bind, args: [], optional_args: [ipv6only]},
-    {command: "nobind", rust_name: Nobind, args: [], optional_args: []},
-    {command: "dev", rust_name: Dev, args: [devarg], optional_args: []},
-    {command: "dev-type", rust_name: DevType, args: [device_type], optional_args: []},
-    {command: "topology", rust_name: Topology, args: [mode], optional_args: []},
-    {command: "dev-node", rust_name: DevNode, args: [node], optional_args: []},
-    {command: "lladdr", rust_name: Lladdr, args: [address], optional_args: []},
-    {command: "iproute", rust_name: Iproute, args: [cmd], optional_args: []},
-    {command: "ifconfig", rust_name: Ifconfig, args: [l, rn], optional_args: []},
-    {command: "ifconfig-noexec", rust_name: IfconfigNoexec, args: [], optional_args: []},
-    {command: "ifconfig-nowarn", rust_name: IfconfigNowarn, args: [], optional_args: []},
-    {command: "route", rust_name: Route, args: [network_or_ip], optional_args: [netmask, gateway, metric]},
-    {command: "route-gateway", rust_name: RouteGateway, args: [gw_or_dhcp], optional_args: []},
-    {command: "route-metric", rust_name: RouteMetric, args: [m], optional_args: []},
-    {command: "route-delay", rust_name: RouteDelay, args: [], optional_args: [n, w]},
-    {command: "route-up", rust_name: RouteUp, args: [cmd], optional_args: []},
-    {command: "route-pre-down", rust_name: RoutePreDown, args: [cmd], optional_args: []},
-    {command: "route-noexec", rust_name: RouteNoexec, args: [], optional_args: []},
-    {command: "route-nopull", rust_name: RouteNopull, args: [], optional_args: []},
-    {command: "allow-pull-fqdn", rust_name: AllowPullFqdn, args: [], optional_args: []},
-    {command: "client-nat", rust_name: ClientNat, args: [snat_or_dnat, network, netmask, alias], optional_args: []},
-    {command: "redirect-gateway", rust_name: RedirectGateway, varargs: flags},
-    {command: "link-mtu", rust_name: LinkMtu, args: [n], optional_args: []},
-    {command: "redirect-private", rust_name: RedirectPrivate, optional_varargs: flags},
-    {command: "tun-mtu", rust_name: TunMtu, args: [n], optional_args: []},
-    {command: "tun-mtu-extra", rust_name: TunMtuExtra, args: [n], optional_args: []},
-    {command: "mtu-disc", rust_name: MtuDisc, args: [mtu_disc_type], optional_args: []},
-    {command: "mtu-test", rust_name: MtuTest, args: [], optional_args: []},
-    {command: "fragment", rust_name: Fragment, args: [max], optional_args: []},
-    {command: "mssfix", rust_name: Mssfix, args: [max], optional_args: []},
-    {command: "sndbuf", rust_name: Sndbuf, args: [size], optional_args: []},
-    {command: "rcvbuf", rust_name: Rcvbuf, args: [size], optional_args: []},
-    {command: "mark", rust_name: Mark, args: [value], optional_args: []},
-    {command: "socket-flags", rust_name: SocketFlags, varargs: flags},
-    {command: "txqueuelen", rust_name: Txqueuelen, args: [n], optional_args: []},
-    {command: "shaper", rust_name: Shaper, args: [n], optional_args: []},
-    {command: "inactive", rust_name: Inactive, args: [n], optional_args: [bytes]},
-    {command: "ping", rust_name: Ping, args: [n], optional_args: []},
-    {command: "ping-exit", rust_name: PingExit, args: [n], optional_args: []},
-    {command: "ping-restart", rust_name: PingRestart, args: [n], optional_args: []},
-    {command: "keepalive", rust_name: Keepalive, args: [interval, timeout], optional_args: []},
-    {command: "ping-timer-rem", rust_name: PingTimerRem, args: [], optional_args: []},
-    {command: "persist-tun", rust_name: PersistTun, args: [], optional_args: []},
-    {command: "persist-key", rust_name: PersistKey, args: [], optional_args: []},
-    {command: "persist-local-ip", rust_name: PersistLocalIp, args: [], optional_args: []},
-    {command: "persist-remote-ip", rust_name: PersistRemoteIp, args: [], optional_args: []},
-    {command: "mlock", rust_name: Mlock, args: [], optional_args: []},
-    {command: "up", rust_name: Up, args: [cmd], optional_args: []},
-    {command: "up-delay", rust_name: UpDelay, args: [], optional_args: []},
-    {command: "down", rust_name: Down, args: [cmd], optional_args: []},
-    {command: "down-pre", rust_name: DownPre, args: [], optional_args: []},
-    {command: "up-restart", rust_name: UpRestart, args: [], optional_args: []},
-    {command: "setenv", rust_name: Setenv, args: [name, value], optional_args: []},
-    {command: "setenv-safe", rust_name: SetenvSafe, args: [name, value], optional_args: []},
-    {command: "ignore-unknown-option", rust_name: IgnoreUnknownOption, varargs: opts},
-    {command: "script-security", rust_name: ScriptSecurity, args: [level], optional_args: []},
-    {command: "disable-occ", rust_name: DisableOcc, args: [], optional_args: []},
-    {command: "user", rust_name: User, args: [user], optional_args: []},
-    {command: "group", rust_name: Group, args: [group], optional_args: []},
-    {command: "cd", rust_name: Cd, args: [dir], optional_args: []},
-    {command: "chroot", rust_name: Chroot, args: [dir], optional_args: []},
-    {command: "setcon", rust_name: Setcon, args: [context], optional_args: []},
-    {command: "daemon", rust_name: Daemon, args: [], optional_args: [progname]},
-    {command: "syslog", rust_name: Syslog, args: [], optional_args: [progname]},
-    {command: "errors-to-stderr", rust_name: ErrorsToStderr, args: [], optional_args: []},
-    {command: "passtos", rust_name: Passtos, args: [], optional_args: []},
-    {command: "inetd", rust_name: Inetd, args: [], optional_args: [wait_or_nowait, progname]},
-    {command: "log", rust_name: Log, args: [file], optional_args: []},
-    {command: "log-append", rust_name: LogAppend, args: [file], optional_args: []},
-    {command: "suppress-timestamps", rust_name: SuppressTimestamps, args: [], optional_args: []},
-    {command: "machine-readable-output", rust_name: MachineReadableOutput, args: [], optional_args: []},
-    {command: "writepid", rust_name: Writepid, args: [file], optional_args: []},
-    {command: "nice", rust_name: Nice, args: [n], optional_args: []},
-    {command: "fast-io", rust_name: FastIo, args: [], optional_args: []},
-    {command: "multihome", rust_name: Multihome, args: [], optional_args: []},
-    {command: "echo", rust_name: Echo, optional_varargs: parms},
-    {command: "remap-usr1", rust_name: RemapUsr1, args: [signal], optional_args: []},
-    {command: "verb", rust_name: Verb, args: [n], optional_args: []},
-    {command: "status", rust_name: Status, args: [file], optional_args: [n]},
-    {command: "status-version", rust_name: StatusVersion, args: [], optional_args: [n]},
-    {command: "mute", rust_name: Mute, args: [n], optional_args: []},
-    {command: "compress", rust_name: Compress, args: [], optional_args: [algorithm]},
-    {command: "comp-lzo", rust_name: CompLzo, args: [], optional_args: [mode]},
-    {command: "comp-noadapt", rust_name: CompNoadapt, args: [], optional_args: []},
-    {command: "management", rust_name: Management, args: [ip, port], optional_args: [pw_file]},
-    {command: "management-client", rust_name: ManagementClient, args: [], optional_args: []},
-    {command: "management-query-passwords", rust_name: ManagementQueryPasswords, args: [], optional_args: []},
-    {command: "management-query-proxy", rust_name: ManagementQueryProxy, args: [], optional_args: []},
-    {command: "management-query-remote", rust_name: ManagementQueryRemote, args: [], optional_args: []},
-    {command: "management-external-key", rust_name: ManagementExternalKey, args: [], optional_args: []},
-    {command: "management-external-cert", rust_name: ManagementExternalCert, args: [certificate_hint], optional_args: []},
-    {command: "management-forget-disconnect", rust_name: ManagementForgetDisconnect, args: [], optional_args: []},
-    {command: "management-hold", rust_name: ManagementHold, args: [], optional_args: []},
-    {command: "management-signal", rust_name: ManagementSignal, args: [], optional_args: []},
-    {command: "management-log-cache", rust_name: ManagementLogCache, args: [n], optional_args: []},
-    {command: "management-up-down", rust_name: ManagementUpDown, args: [], optional_args: []},
-    {command: "management-client-auth", rust_name: ManagementClientAuth, args: [], optional_args: []},
-    {command: "management-client-pf", rust_name: ManagementClientPf, args: [], optional_args: []},
-    {command: "management-client-user", rust_name: ManagementClientUser, args: [u], optional_args: []},
-    {command: "management-client-group", rust_name: ManagementClientGroup, args: [g], optional_args: []},
-    {command: "plugin", rust_name: Plugin, args: [module_pathname], optional_args: [init_string]},
-    {command: "keying-material-exporter", rust_name: KeyingMaterialExporter, args: [label, len], optional_args: []},
-    {command: "server", rust_name: Server, args: [network, netmask], optional_args: [nopool]},
-    {command: "push", rust_name: Push, args: [option], optional_args: []},
-    {command: "push-reset", rust_name: PushReset, args: [], optional_args: []},
-    {command: "push-remove", rust_name: PushRemove, args: [opt], optional_args: []},
-    {command: "push-peer-info", rust_name: PushPeerInfo, args: [], optional_args: []},
-    {command: "disable", rust_name: Disable, args: [], optional_args: []},
-    {command: "ifconfig-pool", rust_name: IfconfigPool, args: [start_ip, end_ip], optional_args: [netmask]},
-    {command: "ifconfig-pool-persist", rust_name: IfconfigPoolPersist, args: [file], optional_args: [seconds]},
-    {command: "ifconfig-pool-linear", rust_name: IfconfigPoolLinear, args: [], optional_args: []},
-    {command: "ifconfig-push", rust_name: IfconfigPush, args: [local, remote_netmask], optional_args: [alias]},
-    {command: "iroute", rust_name: Iroute, args: [network], optional_args: [netmask]},
-    {command: "client-to-client", rust_name: ClientToClient, args: [], optional_args: []},
-    {command: "duplicate-cn", rust_name: DuplicateCn, args: [], optional_args: []},
-    {command: "client-connect", rust_name: ClientConnect, args: [cmd], optional_args: []},
-    {command: "client-disconnect", rust_name: ClientDisconnect, args: [cmd], optional_args: []},
-    {command: "client-config-dir", rust_name: ClientConfigDir, args: [dir], optional_args: []},
-    {command: "ccd-exclusive", rust_name: CcdExclusive, args: [], optional_args: []},
-    {command: "tmp-dir", rust_name: TmpDir, args: [dir], optional_args: []},
-    {command: "hash-size", rust_name: HashSize, args: [r, v], optional_args: []},
-    {command: "bcast-buffers", rust_name: BcastBuffers, args: [n], optional_args: []},
-    {command: "tcp-queue-limit", rust_name: TcpQueueLimit, args: [n], optional_args: []},
-    {command: "tcp-nodelay", rust_name: TcpNodelay, args: [], optional_args: []},
-    {command: "max-clients", rust_name: MaxClients, args: [n], optional_args: []},
-    {command: "max-routes-per-client", rust_name: MaxRoutesPerClient, args: [n], optional_args: []},
-    {command: "stale-routes-check", rust_name: StaleRoutesCheck, args: [n], optional_args: [t]},
-    {command: "connect-freq", rust_name: ConnectFreq, args: [n, sec], optional_args: []},
-    {command: "learn-address", rust_name: LearnAddress, args: [cmd], optional_args: []},
-    {command: "auth-user-pass-verify", rust_name: AuthUserPassVerify, args: [cmd, method], optional_args: []},
-    {command: "auth-gen-token", rust_name: AuthGenToken, args: [], optional_args: [lifetime]},
-    {command: "opt-verify", rust_name: OptVerify, args: [], optional_args: []},
-    {command: "auth-user-pass-optional", rust_name: AuthUserPassOptional, args: [], optional_args: []},
-    {command: "client-cert-not-required", rust_name: ClientCertNotRequired, args: [], optional_args: []},
-    {command: "verify-client-cert", rust_name: VerifyClientCert, args: [none_optional_require], optional_args: []},
-    {command: "username-as-common-name", rust_name: UsernameAsCommonName, args: [], optional_args: []},
-    {command: "compat-names", rust_name: CompatNames, args: [], optional_args: [no_remapping]},
-    {command: "no-name-remapping", rust_name: NoNameRemapping, args: [], optional_args: []},
-    {command: "port-share", rust_name: PortShare, args: [host, port], optional_args: [dir]},
-    {command: "client", rust_name: Client, args: [], optional_args: []},
-    {command: "pull", rust_name: Pull, args: [], optional_args: []},
-    {command: "pull-filter", rust_name: PullFilter, args: [accept_or_ignore_or_reject, text], optional_args: []},
-    {command: "auth-user-pass", rust_name: AuthUserPass, args: [], optional_args: [up]},
-    {command: "auth-retry", rust_name: AuthRetry, args: [auth_retry_type], optional_args: []},
-    {command: "static-challenge", rust_name: StaticChallenge, args: [t, e], optional_args: []},
-    {command: "server-poll-timeout", rust_name: ServerPollTimeout, args: [n], optional_args: []},
-    {command: "connect-timeout", rust_name: ConnectTimeout, args: [n], optional_args: []},
-    {command: "explicit-exit-notify", rust_name: ExplicitExitNotify, args: [], optional_args: [n]},
-    {command: "allow-recursive-routing", rust_name: AllowRecursiveRouting, args: [], optional_args: []},
-    {command: "secret", rust_name: Secret, inline_file: true, optional_args: [direction]},
-    {command: "key-direction", rust_name: KeyDirection, args: [direction], optional_args: []},
-    {command: "auth", rust_name: Auth, args: [alg], optional_args: []},
-    {command: "cipher", rust_name: Cipher, args: [alg], optional_args: []},
-    {command: "ncp-ciphers", rust_name: NcpCiphers, args: [cipher_list], optional_args: []},
-    {command: "ncp-disable", rust_name: NcpDisable, args: [], optional_args: []},
-    {command: "keysize", rust_name: Keysize, args: [n], optional_args: []},
-    {command: "prng", rust_name: Prng, args: [alg], optional_args: [nsl]},
-    {command: "engine", rust_name: Engine, args: [], optional_args: [engine_name]},
-    {command: "no-replay", rust_name: NoReplay, args: [], optional_args: []},
-    {command: "replay-window", rust_name: ReplayWindow, args: [n], optional_args: [t]},
-    {command: "mute-replay-warnings", rust_name: MuteReplayWarnings, args: [], optional_args: []},
-    {command: "replay-persist", rust_name: ReplayPersist, args: [file], optional_args: []},
-    {command: "no-iv", rust_name: NoIv, args: [], optional_args: []},
-    {command: "use-prediction-resistance", rust_name: UsePredictionResistance, args: [], optional_args: []},
-    {command: "test-crypto", rust_name: TestCrypto, args: [], optional_args: []},
-    {command: "tls-auth", rust_name: TlsAuth, inline_file: true, optional_args: [direction]},
-    {command: "tls-server", rust_name: TlsServer, args: [], optional_args: []},
-    {command: "tls-client", rust_name: TlsClient, args: [], optional_args: []},
-    {command: "ca", rust_name: Ca, inline_file: true},
-    {command: "capath", rust_name: Capath, args: [dir], optional_args: []},
-    {command: "dh", rust_name: Dh, inline_file: true},
-    {command: "ecdh-curve", rust_name: EcdhCurve, args: [name], optional_args: []},
-    {command: "cert", rust_name: Cert, inline_file: true},
-    {command: "extra-certs", rust_name: ExtraCerts, inline_file: true},
-    {command: "key", rust_name: Key, inline_file: true},
-    {command: "tls-version-min", rust_name: TlsVersionMin, args: [version], optional_args: [or_highest]},
-    {command: "tls-version-max", rust_name: TlsVersionMax, args: [version], optional_args: []},
-    {command: "pkcs12", rust_name: Pkcs12, inline_file: true},
-    {command: "verify-hash", rust_name: VerifyHash, args: [hash], optional_args: []},
-    {command: "pkcs11-cert-private", rust_name: Pkcs11CertPrivate, varargs: providers},
-    {command: "pkcs11-id", rust_name: Pkcs11Id, args: [name], optional_args: []},
-    {command: "pkcs11-id-management", rust_name: Pkcs11IdManagement, args: [], optional_args: []},
-    {command: "pkcs11-pin-cache", rust_name: Pkcs11PinCache, args: [seconds], optional_args: []},
-    {command: "pkcs11-protected-authentication", rust_name: Pkcs11ProtectedAuthentication, varargs: providers},
-    {command: "pkcs11-providers", rust_name: Pkcs11Providers, varargs: providers},
-    {command: "pkcs11-private-mode", rust_name: Pkcs11PrivateMode, varargs: modes},
-    {command: "cryptoapicert", rust_name: Cryptoapicert, args: [select_string], optional_args: []},
-    {command: "key-method", rust_name: KeyMethod, args: [m], optional_args: []},
-    {command: "tls-cipher", rust_name: TlsCipher, args: [l], optional_args: []},
-    {command: "tls-timeout", rust_name: TlsTimeout, args: [n], optional_args: []},
-    {command: "reneg-bytes", rust_name: RenegBytes, args: [n], optional_args: []},
-    {command: "reneg-pkts", rust_name: RenegPkts, args: [n], optional_args: []},
-    {command: "reneg-sec", rust_name: RenegSec, args: [n], optional_args: []},
-    {command: "hand-window", rust_name: HandWindow, args: [n], optional_args: []},
-    {command: "tran-window", rust_name: TranWindow, args: [n], optional_args: []},
-    {command: "single-session", rust_name: SingleSession, args: [], optional_args: []},
-    {command: "tls-exit", rust_name: TlsExit, args: [], optional_args: []},
-    {command: "tls-crypt", rust_name: TlsCrypt, inline_file: true},
-    {command: "askpass", rust_name: Askpass, args: [], optional_args: [file]},
-    {command: "auth-nocache", rust_name: AuthNocache, args: [], optional_args: []},
-    {command: "auth-token", rust_name: AuthToken, args: [token], optional_args: []},
-    {command: "tls-verify", rust_name: TlsVerify, args: [cmd], optional_args: []},
-    {command: "tls-export-cert", rust_name: TlsExportCert, args: [directory], optional_args: []},
-    {command: "x509-username-field", rust_name: X509UsernameField, args: [fieldname], optional_args: []},
-    {command: "verify-x509-name", rust_name: VerifyX509Name, args: [name, verify_x509_name_type], optional_args: []},
-    {command: "x509-track", rust_name: X509Track, args: [attribute], optional_args: []},
-    {command: "ns-cert-type", rust_name: NsCertType, args: [client_or_server], optional_args: []},
-    {command: "remote-cert-ku", rust_name: RemoteCertKu, varargs: values},
-    {command: "remote-cert-eku", rust_name: RemoteCertEku, args: [oid], optional_args: []},
-    {command: "remote-cert-tls", rust_name: RemoteCertTls, args: [client_or_server], optional_args: []},
-    {command: "crl-verify", rust_name: CrlVerify, inline_file: true, optional_args: [direction]},
-    {command: "show-ciphers", rust_name: ShowCiphers, args: [], optional_args: []},
-    {command: "show-digests", rust_name: ShowDigests, args: [], optional_args: []},
-    {command: "show-tls", rust_name: ShowTls, args: [], optional_args: []},
-    {command: "show-engines", rust_name: ShowEngines, args: [], optional_args: []},
-    {command: "show-curves", rust_name: ShowCurves, args: [], optional_args: []},
-    {command: "genkey", rust_name: Genkey, args: [], optional_args: []},
-    {command: "mktun", rust_name: Mktun, args: [], optional_args: []},
-    {command: "rmtun", rust_name: Rmtun, args: [], optional_args: []},
-    {command: "win-sys", rust_name: WinSys, args: [path], optional_args: []},
-    {command: "ip-win32", rust_name: IpWin32, args: [method], optional_args: []},
-    {command: "route-method", rust_name: RouteMethod, args: [m], optional_args: []},
-    {command: "dhcp-option", rust_name: DhcpOption, args: [dhcp_option_type], optional_args: [parm]},
-    {command: "tap-sleep", rust_name: TapSleep, args: [n], optional_args: []},
-    {command: "show-net-up", rust_name: ShowNetUp, args: [], optional_args: []},
-    {command: "block-outside-dns", rust_name: BlockOutsideDns, args: [], optional_args: []},
-    {command: "dhcp-renew", rust_name: DhcpRenew, args: [], optional_args: []},
-    {command: "dhcp-release", rust_name: DhcpRelease, args: [], optional_args: []},
-    {command: "register-dns", rust_name: RegisterDns, args: [], optional_args: []},
-    {command: "pause-exit", rust_name: PauseExit, args: [], optional_args: []},
-    {command: "service", rust_name: Service, args: [exit_event], optional_args: [initial_state_of_event]},
-    {command: "show-adapters", rust_name: ShowAdapters, args: [], optional_args: []},
-    {command: "allow-nonadmin", rust_name: AllowNonadmin, args: [], optional_args: [tap_adapter]},
-    {command: "show-valid-subnets", rust_name: ShowValidSubnets, args: [], optional_args: []},
-    {command: "show-net", rust_name: ShowNet, args: [], optional_args: []},
-    {command: "show-pkcs11-ids", rust_name: ShowPkcs11Ids, args: [], optional_args: [provider, cert_private]},
-    {command: "show-gateway", rust_name: ShowGateway, args: [], optional_args: [v6target]},
-    {command: "ifconfig-ipv6", rust_name: IfconfigIpv6, args: [ipv6addr, ipv6remote], optional_args: []},
-    {command: "route-ipv6", rust_name: RouteIpv6, args: [ipv6addr], optional_args: [gateway, metric]},
-    {command: "server-ipv6", rust_name: ServerIpv6, args: [ipv6addr], optional_args: []},
-    {command: "ifconfig-ipv6-pool", rust_name: IfconfigIpv6Pool, args: [ipv6addr], optional_args: []},
-    {command: "ifconfig-ipv6-push", rust_name: IfconfigIpv6Push, args: [ipv6addr, ipv6remote], optional_args: []},
-    {command: "iroute-ipv6", rust_name: IrouteIpv6, args: [ipv6addr], optional_args: []},
+/// Parse a single option, as it would appear on a line of a config file
+pub(crate) fn parse_option(option: &str) -> Result<ConfigDirective, ParseWarning> {
+    let command_and_args = split_args(option);
+    let command = match command_and_args.first() {
+        Some(command) => command,
+        None => return Err(ParseWarning::NotEnoughArguments),
+    };
+    let args: Vec<&str> = command_and_args[1..].iter().map(|a| a.as_ref()).collect();
+    parse_command(command, &args)
+}
+
+/// Parse a command and its arguments
+pub(crate) fn parse_command(command: &str, args: &[&str]) -> Result<ConfigDirective, ParseWarning> {
+    match parse_line(command, args) {
+        LineParseResult::Success(directive) => Ok(directive),
+        LineParseResult::NoMatchingCommand => Err(ParseWarning::NoMatchingCommand),
+        LineParseResult::NotEnoughArguments => Err(ParseWarning::NotEnoughArguments),
+        LineParseResult::InvalidArgument => Err(ParseWarning::InvalidArgument),
+    }
+}
+
+// The directive table is generated by build.rs from `ovpn_commands`
+include!(concat!(env!("OUT_DIR"), "/directives.rs"));
+
+/// The values an argument with a fixed vocabulary can take, e.g
+/// `net30`, `p2p` and `subnet` for the `mode` argument of `topology`.
+/// `None` if the option or argument is not known or the argument can take
+/// any value.
+pub fn argument_choices(option_name: &str, argument: &str) -> Option<&'static [&'static str]> {
+    ARGUMENT_CHOICES.iter()
+        .find(|&&(option, _, _, name, _)| option == option_name && name == argument)
+        .map(|&(_, _, _, _, choices)| choices)
+}
+
+impl ConfigDirective {
+    /// The versions of openvpn in which this directive was introduced,
+    /// deprecated and removed
+    pub fn versions(&self) -> OptionVersions {
+        option_versions(self.openvpn_option_name()).expect("every directive is in the option table")
+    }
+
+    /// The first version of openvpn which supports this directive with these
+    /// arguments, `None` if the option and arguments predate the versions
+    /// the option table tracks. Arguments count as given if they'd be
+    /// written to a config file, so an optional argument after one which is
+    /// omitted is ignored as it is by `as_ovpn_config`.
+    pub fn required_version(&self) -> Option<Version> {
+        let given = (self.required_arg_values().len(), self.optional_arg_values().len());
+        let argument_version = ARGUMENT_VERSIONS.iter()
+            .filter(|&&(option, ..)| option == self.openvpn_option_name())
+            .filter(|&&(_, optional, index, _)| index < if optional { given.1 } else { given.0 })
+            .map(|&(.., version)| version)
+            .max();
+        // Values which were added to an argument later than the argument
+        let value_version = match *self {
+            ConfigDirective::WindowsDriver{driver: WindowsDriver::OvpnDco} => Some(Version::new(2, 6)),
+            ConfigDirective::Dh{file: File::None} => Some(Version::new(2, 4)),
+            ConfigDirective::AuthUserPass{up: Some(File::InlineFileContents(_))} => Some(Version::new(2, 6)),
+            _ => None,
+        };
+        self.versions().introduced.max(argument_version).max(value_version)
+    }
+
+    /// The arguments of this directive which aren't one of the values
+    /// `argument_choices` allows, as the argument name and its value
+    pub fn unexpected_argument_values(&self) -> Vec<(&'static str, String)> {
+        let required = self.required_arg_values();
+        let optional = self.optional_arg_values();
+        ARGUMENT_CHOICES.iter()
+            .filter(|&&(option, ..)| option == self.openvpn_option_name())
+            .filter_map(|&(_, is_optional, index, name, choices)| {
+                let value = if is_optional { optional.get(index) } else { required.get(index) }?;
+                if choices.contains(&&value[..]) {
+                    None
+                } else {
+                    Some((name, value.clone()))
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a server can push this directive to clients
+    pub fn is_pushable(&self) -> bool {
+        PUSHABLE_OPTIONS.contains(&self.openvpn_option_name())
+    }
+
+    /// Whether using this option again adds to the earlier uses, like
+    /// `remote` or `route`, rather than replacing them
+    pub fn is_repeatable(&self) -> bool {
+        REPEATABLE_OPTIONS.contains(&self.openvpn_option_name())
+    }
+
+    /// Whether this directive can be used in a `client-config-dir` file
+    pub fn is_ccd_option(&self) -> bool {
+        CCD_OPTIONS.contains(&self.openvpn_option_name())
+    }
+
+    /// The directive a `push` directive pushes, parsed as though it were a
+    /// line of a config file. Returns `None` for any other directive.
+    pub fn pushed_directive(&self) -> Option<Result<ConfigDirective, ParseWarning>> {
+        match *self {
+            ConfigDirective::Push{ref option} => Some(parse_option(option)),
+            _ => None,
+        }
+    }
+
+    /// The file of an option which can have inline file contents, `None`
+    /// for any other directive
+    pub fn file(&self) -> Option<&File> {
+        match *self {
+            ConfigDirective::Ca{ref file} |
+            ConfigDirective::Cert{ref file} |
+            ConfigDirective::ExtraCerts{ref file} |
+            ConfigDirective::Dh{ref file} |
+            ConfigDirective::Key{ref file} |
+            ConfigDirective::Pkcs12{ref file} |
+            ConfigDirective::CrlVerify{ref file, ..} |
+            ConfigDirective::HttpProxyUserPass{ref file} |
+            ConfigDirective::TlsAuth{ref file, ..} |
+            ConfigDirective::TlsCrypt{ref file} |
+            ConfigDirective::TlsCryptV2{ref file, ..} |
+            ConfigDirective::Secret{ref file, ..} => Some(file),
+            _ => None,
+        }
+    }
+
+    /// A mutable reference to the file of an option which can have inline
+    /// file contents, `None` for any other directive
+    pub fn file_mut(&mut self) -> Option<&mut File> {
+        match *self {
+            ConfigDirective::Ca{ref mut file} |
+            ConfigDirective::Cert{ref mut file} |
+            ConfigDirective::ExtraCerts{ref mut file} |
+            ConfigDirective::Dh{ref mut file} |
+            ConfigDirective::Key{ref mut file} |
+            ConfigDirective::Pkcs12{ref mut file} |
+            ConfigDirective::CrlVerify{ref mut file, ..} |
+            ConfigDirective::HttpProxyUserPass{ref mut file} |
+            ConfigDirective::TlsAuth{ref mut file, ..} |
+            ConfigDirective::TlsCrypt{ref mut file} |
+            ConfigDirective::TlsCryptV2{ref mut file, ..} |
+            ConfigDirective::Secret{ref mut file, ..} => Some(file),
+            _ => None,
+        }
+    }
+
+    /// The number of exit notifications an `explicit-exit-notify` directive
+    /// will send, taking into account openvpn's default of 1 when the count
+    /// is omitted. Returns `None` for any other directive.
+    pub fn explicit_exit_notify_count(&self) -> Option<u8> {
+        match *self {
+            ConfigDirective::ExplicitExitNotify{n} => Some(n.unwrap_or(1)),
+            _ => None,
+        }
+    }
+
+    /// The port a `socks-proxy` directive will connect to, taking into
+    /// account openvpn's default of 1080 when the port is omitted. Returns
+    /// `None` for any other directive.
+    pub fn socks_proxy_port(&self) -> Option<u16> {
+        match *self {
+            ConfigDirective::SocksProxy{port, ..} => Some(port.unwrap_or(1080)),
+            _ => None,
+        }
+    }
+
+    /// How often a `status` directive will write the status file, taking
+    /// into account openvpn's default of 60 seconds when the interval is
+    /// omitted. Returns `None` for any other directive.
+    pub fn status_interval(&self) -> Option<Duration> {
+        match *self {
+            ConfigDirective::Status{n, ..} => Some(Duration::from_secs(n.unwrap_or(60))),
+            _ => None,
+        }
+    }
+
+    /// The fields of an `x509-username-field` directive, distinguishing
+    /// certificate extensions given with the `ext:` prefix from subject
+    /// fields. Returns `None` for any other directive.
+    pub fn x509_username_fields(&self) -> Option<Vec<X509Field>> {
+        match *self {
+            ConfigDirective::X509UsernameField{ref fields} => {
+                Some(fields.iter().filter_map(|f| parse_arg(f)).collect())
+            },
+            _ => None,
+        }
+    }
+
+    /// The parameters of an `echo` directive joined by spaces, which is how
+    /// clients display them. Returns `None` for any other directive.
+    pub fn message(&self) -> Option<String> {
+        match *self {
+            ConfigDirective::Echo{parms: Some(ref parms)} => Some(parms.join(" ")),
+            ConfigDirective::Echo{parms: None} => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    /// The username of an `auth-token-user` directive, decoded from base64.
+    /// Returns `None` for any other directive or if the username is not
+    /// valid base64 encoded UTF-8.
+    pub fn auth_token_username(&self) -> Option<String> {
+        match *self {
+            ConfigDirective::AuthTokenUser{ref base64_username} => {
+                base64::decode(base64_username).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+            },
+            _ => None,
+        }
+    }
+
+    /// The parameters of an `echo` directive interpreted according to the
+    /// `msg`, `msg-n`, `msg-window` and `msg-notify` conventions used by GUI
+    /// clients. Returns `None` for any other directive.
+    pub fn echo_command(&self) -> Option<EchoCommand> {
+        match *self {
+            ConfigDirective::Echo{ref parms} => {
+                Some(EchoCommand::from_parms(parms.as_ref().map(|p| p.as_slice()).unwrap_or(&[])))
+            },
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -748,4 +999,95 @@ mod tests {
         let directive2 = ConfigDirective::Ca{file: File::InlineFileContents("line1\nline2".to_string())};
         assert_eq!(directive2.as_ovpn_config(), "<ca>\nline1\nline2\n</ca>".to_string())
     }
+
+    #[test]
+    fn test_explicit_exit_notify_count() {
+        match parse_line("explicit-exit-notify", &[]) {
+            LineParseResult::Success(directive) => assert_eq!(directive.explicit_exit_notify_count(), Some(1)),
+            _ => panic!("expected explicit-exit-notify to parse"),
+        }
+        match parse_line("explicit-exit-notify", &["3"]) {
+            LineParseResult::Success(directive) => assert_eq!(directive.explicit_exit_notify_count(), Some(3)),
+            _ => panic!("expected explicit-exit-notify to parse"),
+        }
+        match parse_line("explicit-exit-notify", &["many"]) {
+            LineParseResult::InvalidArgument => (),
+            _ => panic!("expected an invalid argument"),
+        }
+    }
+
+    #[test]
+    fn test_quotes_arguments() {
+        let directive = ConfigDirective::StaticChallenge{t: "Enter your \"PIN\"".to_string(), e: true};
+        assert_eq!(directive.as_ovpn_config(), "static-challenge \"Enter your \\\"PIN\\\"\" 1");
+    }
+
+    #[test]
+    fn test_auth_token_username() {
+        let directive = ConfigDirective::AuthTokenUser{base64_username: "c29tZXVzZXI=".to_string()};
+        assert_eq!(directive.auth_token_username(), Some("someuser".to_string()));
+        let directive = ConfigDirective::AuthTokenUser{base64_username: "not base64".to_string()};
+        assert_eq!(directive.auth_token_username(), None);
+    }
+
+    #[test]
+    fn test_status_interval() {
+        let directive = ConfigDirective::Status{file: "somefile".to_string(), n: None};
+        assert_eq!(directive.status_interval(), Some(Duration::from_secs(60)));
+        let directive = ConfigDirective::Status{file: "somefile".to_string(), n: Some(10)};
+        assert_eq!(directive.status_interval(), Some(Duration::from_secs(10)));
+        assert_eq!(ConfigDirective::Help.status_interval(), None);
+    }
+
+    #[test]
+    fn test_versions() {
+        let directive = ConfigDirective::TlsCrypt{file: File::FilePath("somefile".to_string())};
+        assert_eq!(directive.versions().introduced, Some(Version::new(2, 4)));
+        let directive = ConfigDirective::KeyMethod{m: "2".to_string()};
+        assert!(directive.versions().supported_in(Version::new(2, 4)));
+        assert!(directive.versions().deprecated_in(Version::new(2, 4)));
+        assert!(!directive.versions().supported_in(Version::new(2, 5)));
+        assert_eq!(ConfigDirective::ServerBridge(ServerBridgeArg::NoGateway).versions().introduced, None);
+        assert_eq!(option_versions("not-an-option"), None);
+    }
+
+    #[test]
+    fn test_pushed_directive() {
+        let push = |option: &str| ConfigDirective::Push{option: option.to_string()};
+        assert_eq!(
+            push("route 10.0.0.0 255.255.255.0").pushed_directive(),
+            Some(Ok(ConfigDirective::Route{
+                network_or_ip: "10.0.0.0".to_string(),
+                netmask: Some("255.255.255.0".to_string()),
+                gateway: None,
+                metric: None,
+            })),
+        );
+        assert_eq!(push("not-an-option").pushed_directive(), Some(Err(ParseWarning::NoMatchingCommand)));
+        assert_eq!(push("").pushed_directive(), Some(Err(ParseWarning::NotEnoughArguments)));
+        assert_eq!(ConfigDirective::Help.pushed_directive(), None);
+        assert!(ConfigDirective::PersistTun.is_pushable());
+        assert!(!ConfigDirective::TlsCrypt{file: File::FilePath("somefile".to_string())}.is_pushable());
+        assert!(ConfigDirective::Mute{n: "10".to_string()}.is_pushable());
+        assert!(ConfigDirective::Shaper{n: "1000".to_string()}.is_pushable());
+        assert!(ConfigDirective::PeerId{id: "1".to_string()}.is_pushable());
+        assert!(!ConfigDirective::Setenv{name: "FOO".to_string(), value: "bar".to_string()}.is_pushable());
+        assert!(ConfigDirective::Iroute{network: "10.0.0.0".to_string(), netmask: None}.is_ccd_option());
+        assert!(!ConfigDirective::PersistTun.is_ccd_option());
+        assert!(ConfigDirective::Push{option: "route 10.0.0.0".to_string()}.is_repeatable());
+        assert!(!ConfigDirective::PersistTun.is_repeatable());
+    }
+
+    #[test]
+    fn test_argument_choices() {
+        assert_eq!(argument_choices("topology", "mode"), Some(&["net30", "p2p", "subnet"][..]));
+        assert_eq!(argument_choices("topology", "other"), None);
+        assert_eq!(argument_choices("remote", "host"), None);
+        let directive = ConfigDirective::Topology{mode: "subnet".to_string()};
+        assert_eq!(directive.unexpected_argument_values(), vec![]);
+        let directive = ConfigDirective::Topology{mode: "subnets".to_string()};
+        assert_eq!(directive.unexpected_argument_values(), vec![("mode", "subnets".to_string())]);
+        let directive = ConfigDirective::TlsAuth{file: File::InlineFileContents("key".to_string()), direction: Some("2".to_string())};
+        assert_eq!(directive.unexpected_argument_values(), vec![("direction", "2".to_string())]);
+    }
 }
@@ -0,0 +1,203 @@
+//! Checks on the certificates and keys in a config file and decoding of
+//! PKCS#12 bundles, enabled by the `crypto` feature.
+use std::error::Error;
+use std::fmt;
+use base64;
+use openssl::dh::Dh;
+use openssl::error::ErrorStack;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::PKey;
+use openssl::stack::Stack;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::verify::X509VerifyFlags;
+use openssl::x509::{X509, X509StoreContext};
+use {ConfigDirective, File};
+
+/// Whether the public key of the first certificate in `cert_pem` matches the
+/// private key in `key_pem`
+pub fn cert_matches_key(cert_pem: &str, key_pem: &str) -> Result<bool, ErrorStack> {
+    let cert = X509::from_pem(cert_pem.as_bytes())?;
+    let key = PKey::private_key_from_pem(key_pem.as_bytes())?;
+    Ok(cert.public_key()?.public_eq(&key))
+}
+
+/// Whether the first certificate in `cert_pem` chains to one of the
+/// certificates in `ca_pem`, using the certificates in `intermediates_pem`
+/// (the contents of `extra-certs`) as intermediates. Certificate validity
+/// periods are not checked.
+pub fn cert_chains_to_ca(cert_pem: &str, ca_pem: &str, intermediates_pem: Option<&str>) -> Result<bool, ErrorStack> {
+    let cert = X509::from_pem(cert_pem.as_bytes())?;
+    let mut store = X509StoreBuilder::new()?;
+    for ca in X509::stack_from_pem(ca_pem.as_bytes())? {
+        store.add_cert(ca)?;
+    }
+    store.set_flags(X509VerifyFlags::NO_CHECK_TIME)?;
+    let store = store.build();
+    let mut intermediates = Stack::new()?;
+    if let Some(intermediates_pem) = intermediates_pem {
+        for intermediate in X509::stack_from_pem(intermediates_pem.as_bytes())? {
+            intermediates.push(intermediate)?;
+        }
+    }
+    let mut context = X509StoreContext::new()?;
+    context.init(&store, &cert, &intermediates, |c| c.verify_cert())
+}
+
+/// The size in bits of the prime of the PEM encoded DH parameters in
+/// `dh_pem`
+pub fn dh_bits(dh_pem: &str) -> Result<u32, ErrorStack> {
+    let dh = Dh::params_from_pem(dh_pem.as_bytes())?;
+    Ok(dh.prime_p().num_bits() as u32)
+}
+
+/// The contents of a PKCS#12 bundle, PEM encoded
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Pkcs12Contents {
+    /// The CA certificates, concatenated
+    pub ca: Option<String>,
+    /// The client certificate
+    pub cert: Option<String>,
+    /// The private key, unencrypted
+    pub key: Option<String>,
+}
+
+/// An error decoding a PKCS#12 bundle
+#[derive(Debug)]
+pub enum Pkcs12Error {
+    /// An inline bundle isn't valid base64
+    InvalidBase64,
+    /// openssl couldn't parse the bundle, or the passphrase is wrong
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for Pkcs12Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Pkcs12Error::InvalidBase64 => f.write_str("the inline PKCS#12 bundle isn't valid base64"),
+            Pkcs12Error::OpenSsl(ref e) => write!(f, "could not decode the PKCS#12 bundle: {}", e),
+        }
+    }
+}
+
+impl Error for Pkcs12Error {}
+
+impl From<ErrorStack> for Pkcs12Error {
+    fn from(e: ErrorStack) -> Pkcs12Error {
+        Pkcs12Error::OpenSsl(e)
+    }
+}
+
+fn pem_string(pem: Vec<u8>) -> String {
+    String::from_utf8_lossy(&pem).trim_end().to_string()
+}
+
+/// Decode the DER encoded PKCS#12 bundle in `der`, which is encrypted with
+/// `passphrase` if it is given
+pub fn decode_pkcs12(der: &[u8], passphrase: Option<&str>) -> Result<Pkcs12Contents, Pkcs12Error> {
+    let parsed = Pkcs12::from_der(der)?.parse2(passphrase.unwrap_or(""))?;
+    let ca = match parsed.ca {
+        Some(ref ca) if !ca.is_empty() => {
+            let pems: Result<Vec<String>, ErrorStack> = ca.iter().map(|c| Ok(pem_string(c.to_pem()?))).collect();
+            Some(pems?.join("\n"))
+        },
+        _ => None,
+    };
+    Ok(Pkcs12Contents{
+        ca,
+        cert: parsed.cert.map(|c| c.to_pem()).transpose()?.map(pem_string),
+        key: parsed.pkey.map(|k| k.private_key_to_pem_pkcs8()).transpose()?.map(pem_string),
+    })
+}
+
+impl ConfigDirective {
+    /// The contents of an inline `pkcs12` directive, whose bundle is base64
+    /// encoded. Returns `None` for any other directive, or if the bundle is
+    /// in a separate file.
+    pub fn pkcs12_contents(&self, passphrase: Option<&str>) -> Option<Result<Pkcs12Contents, Pkcs12Error>> {
+        match *self {
+            ConfigDirective::Pkcs12{file: File::InlineFileContents(ref contents)} => {
+                let der = match base64::decode(contents.split_whitespace().collect::<String>()) {
+                    Ok(der) => der,
+                    Err(_) => return Some(Err(Pkcs12Error::InvalidBase64)),
+                };
+                Some(decode_pkcs12(&der, passphrase))
+            },
+            _ => None,
+        }
+    }
+
+    /// The inline `ca`, `cert` and `key` directives equivalent to an inline
+    /// `pkcs12` directive, for clients which can't use PKCS#12. Returns
+    /// `None` for any other directive, or if the bundle is in a separate
+    /// file.
+    pub fn pkcs12_to_pem(&self, passphrase: Option<&str>) -> Option<Result<Vec<ConfigDirective>, Pkcs12Error>> {
+        self.pkcs12_contents(passphrase).map(|contents| contents.map(|contents| {
+            let mut result = Vec::new();
+            if let Some(ca) = contents.ca {
+                result.push(ConfigDirective::Ca{file: File::InlineFileContents(ca)});
+            }
+            if let Some(cert) = contents.cert {
+                result.push(ConfigDirective::Cert{file: File::InlineFileContents(cert)});
+            }
+            if let Some(key) = contents.key {
+                result.push(ConfigDirective::Key{file: File::InlineFileContents(key)});
+            }
+            result
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &str = include_str!("../tests/certs/ca.crt");
+    const OTHER_CA: &str = include_str!("../tests/certs/other-ca.crt");
+    const CERT: &str = include_str!("../tests/certs/client.crt");
+    const KEY: &str = include_str!("../tests/certs/client.key");
+    const OTHER_KEY: &str = include_str!("../tests/certs/other.key");
+    const DH_1024: &str = include_str!("../tests/certs/dh1024.pem");
+    const DH_2048: &str = include_str!("../tests/certs/dh2048.pem");
+    const PKCS12: &[u8] = include_bytes!("../tests/certs/client.p12");
+
+    #[test]
+    fn test_cert_matches_key() {
+        assert!(cert_matches_key(CERT, KEY).unwrap());
+        assert!(!cert_matches_key(CERT, OTHER_KEY).unwrap());
+        assert!(cert_matches_key("not a certificate", KEY).is_err());
+    }
+
+    #[test]
+    fn test_cert_chains_to_ca() {
+        assert!(cert_chains_to_ca(CERT, CA, None).unwrap());
+        assert!(cert_chains_to_ca(CERT, &format!("{}{}", OTHER_CA, CA), None).unwrap());
+        assert!(!cert_chains_to_ca(CERT, OTHER_CA, None).unwrap());
+        assert!(!cert_chains_to_ca(CERT, OTHER_CA, Some(CA)).unwrap());
+    }
+
+    #[test]
+    fn test_dh_bits() {
+        assert_eq!(dh_bits(DH_1024).unwrap(), 1024);
+        assert_eq!(dh_bits(DH_2048).unwrap(), 2048);
+        assert!(dh_bits(CERT).is_err());
+    }
+
+    #[test]
+    fn test_pkcs12() {
+        let directive = ConfigDirective::Pkcs12{file: File::InlineFileContents(base64::encode(PKCS12))};
+        let directives = directive.pkcs12_to_pem(Some("secret")).unwrap().unwrap();
+        let contents: Vec<&str> = directives.iter().map(|d| match d.file() {
+            Some(File::InlineFileContents(contents)) => &contents[..],
+            _ => panic!("not inline"),
+        }).collect();
+        assert_eq!(directives.iter().map(|d| d.openvpn_option_name()).collect::<Vec<_>>(), vec!["ca", "cert", "key"]);
+        assert_eq!(contents[0], CA.trim_end());
+        assert_eq!(contents[1], CERT.trim_end());
+        assert!(cert_matches_key(contents[1], contents[2]).unwrap());
+
+        assert!(directive.pkcs12_contents(Some("wrong")).unwrap().is_err());
+        let invalid = ConfigDirective::Pkcs12{file: File::InlineFileContents("not base64!".to_string())};
+        assert!(matches!(invalid.pkcs12_contents(None), Some(Err(Pkcs12Error::InvalidBase64))));
+        assert!(ConfigDirective::Pkcs12{file: File::FilePath("client.p12".to_string())}.pkcs12_contents(None).is_none());
+    }
+}
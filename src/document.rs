@@ -0,0 +1,407 @@
+//! A config file as a document of entries which keep their source text, so
+//! that editors can change some options and write the file back without
+//! disturbing the rest of it
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+use raw::{LineKind, Scanner};
+use {inline_directive, parse_config_line, split_args_and_comment};
+use {ConfigDirective, ConfigLine, ParseWarning, ParsedConfigFile, MAX_LINE_LEN};
+
+/// What an entry of a document is
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum EntryKind {
+    Blank,
+    Comment,
+    /// An option, or an inline file, with the result of parsing it. An
+    /// inline file which is never closed is a `ParseWarning::UnclosedInlineFile`
+    /// covering the rest of the document.
+    Option(Result<ConfigDirective, ParseWarning>),
+}
+
+/// A line of a document, or the lines of an inline file
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DocumentEntry {
+    /// The lines of the document the entry covers
+    pub span: Range<i32>,
+    pub kind: EntryKind,
+    text: String,
+    modified: bool,
+}
+
+impl DocumentEntry {
+    fn new(kind: EntryKind, text: String, modified: bool) -> DocumentEntry {
+        DocumentEntry{span: 0..0, kind, text, modified}
+    }
+
+    /// The entry's text, including its line endings
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether the entry was changed or added since the document was parsed
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// The directive of a successfully parsed option
+    pub fn directive(&self) -> Option<&ConfigDirective> {
+        match self.kind {
+            EntryKind::Option(Ok(ref directive)) => Some(directive),
+            _ => None,
+        }
+    }
+
+    /// The comment of a comment entry, or the one at the end of an option's
+    /// line
+    pub fn comment(&self) -> Option<&str> {
+        let content = line_content(&self.text);
+        match self.kind {
+            EntryKind::Comment => Some(content.trim()),
+            // Only an option on one line can have a comment after it
+            EntryKind::Option(_) if self.span.len() == 1 => split_args_and_comment(content).1,
+            _ => None,
+        }
+    }
+
+    /// The line ending the entry's text uses
+    fn line_ending(&self) -> Option<&'static str> {
+        if self.text.ends_with("\r\n") {
+            Some("\r\n")
+        } else if self.text.ends_with('\n') {
+            Some("\n")
+        } else {
+            None
+        }
+    }
+}
+
+/// `line` without its line ending
+fn line_content(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// The entries of `text`, numbered from 0
+fn parse_entries(text: &str) -> Vec<DocumentEntry> {
+    let mut entries = Vec::new();
+    let mut scanner = Scanner::new(false);
+    // The text and contents of the inline file being read
+    let mut block = String::new();
+    let mut contents = Vec::new();
+    for line in text.split_inclusive('\n') {
+        let content = line_content(line);
+        let kind = match scanner.scan(content).kind {
+            LineKind::InlineOpen(_) => {
+                block.push_str(line);
+                continue;
+            },
+            LineKind::InlineContents => {
+                block.push_str(line);
+                contents.push(content);
+                continue;
+            },
+            LineKind::InlineClose(tag, _) => {
+                block.push_str(line);
+                let directive = inline_directive(&tag, contents.join("\n"));
+                contents.clear();
+                entries.push(DocumentEntry::new(EntryKind::Option(directive), mem::take(&mut block), false));
+                continue;
+            },
+            LineKind::Blank => EntryKind::Blank,
+            LineKind::Comment => EntryKind::Comment,
+            LineKind::Option => parse_config_line(content).map_or(EntryKind::Blank, EntryKind::Option),
+        };
+        entries.push(DocumentEntry::new(kind, line.to_string(), false));
+    }
+    if !block.is_empty() {
+        entries.push(DocumentEntry::new(EntryKind::Option(Err(ParseWarning::UnclosedInlineFile)), block, false));
+    }
+    entries
+}
+
+/// A config file which keeps its comments, blank lines and formatting.
+/// Entries can be edited, added and removed, and the document written back
+/// with only the changed entries reformatted.
+///
+/// ```
+/// use ovpnfile::{ConfigDirective, ConfigDocument};
+///
+/// let mut document = ConfigDocument::parse("# My VPN\nremote old.example.com\n\nverb 3\n");
+/// let index = document.find("remote").unwrap();
+/// document.set_directive(index, ConfigDirective::Remote{host: "new.example.com".to_string(), port: None, proto: None});
+/// assert_eq!(document.to_string(), "# My VPN\nremote new.example.com\n\nverb 3\n");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ConfigDocument {
+    entries: Vec<DocumentEntry>,
+}
+
+impl ConfigDocument {
+    /// Parse a document. Lines which are too long are parsed as usual, they
+    /// are reported by `to_parsed`.
+    pub fn parse(text: &str) -> ConfigDocument {
+        let mut document = ConfigDocument{entries: parse_entries(text)};
+        document.renumber();
+        document
+    }
+
+    pub fn entries(&self) -> &[DocumentEntry] {
+        &self.entries
+    }
+
+    /// The index of the first entry for the given option
+    pub fn find(&self, option_name: &str) -> Option<usize> {
+        self.entries.iter().position(|e| e.directive().is_some_and(|d| d.openvpn_option_name() == option_name))
+    }
+
+    /// The index of the entry which covers a line
+    pub fn entry_at_line(&self, line: i32) -> Option<usize> {
+        self.entries.iter().position(|e| e.span.contains(&line))
+    }
+
+    /// The line ending new entries use, the document's first one
+    fn line_ending(&self) -> &'static str {
+        self.entries.iter().find_map(|e| e.line_ending()).unwrap_or("\n")
+    }
+
+    fn directive_entry(&self, directive: ConfigDirective, line_ending: &str) -> DocumentEntry {
+        let text = directive.as_ovpn_config().replace('\n', line_ending) + line_ending;
+        DocumentEntry::new(EntryKind::Option(Ok(directive)), text, true)
+    }
+
+    /// Replace the entry at `index` with a directive
+    pub fn set_directive(&mut self, index: usize, directive: ConfigDirective) {
+        let line_ending = self.entries[index].line_ending().unwrap_or_else(|| self.line_ending());
+        self.entries[index] = self.directive_entry(directive, line_ending);
+        self.renumber();
+    }
+
+    /// Insert a directive before the entry at `index`, or at the end if
+    /// `index` is the number of entries
+    pub fn insert_directive(&mut self, index: usize, directive: ConfigDirective) {
+        let line_ending = self.line_ending();
+        // The last line may not have had a line ending
+        if index == self.entries.len() {
+            if let Some(last) = self.entries.last_mut() {
+                if last.line_ending().is_none() {
+                    last.text.push_str(line_ending);
+                    last.modified = true;
+                }
+            }
+        }
+        let entry = self.directive_entry(directive, line_ending);
+        self.entries.insert(index, entry);
+        self.renumber();
+    }
+
+    /// Remove the entry at `index`
+    pub fn remove(&mut self, index: usize) -> DocumentEntry {
+        let entry = self.entries.remove(index);
+        self.renumber();
+        entry
+    }
+
+    /// Replace the lines in `lines` with `text`, which should end with a line
+    /// ending unless it's at the end of the document, and reparse only the
+    /// entries the edit touches. An edit which opens an inline file reparses
+    /// the following entries until it's closed. Returns the indices of the
+    /// reparsed entries, which are marked as modified.
+    ///
+    /// Panics if `lines` starts after the end of the document.
+    pub fn apply_edit(&mut self, lines: Range<i32>, text: &str) -> Range<usize> {
+        let line_count = self.entries.last().map_or(0, |e| e.span.end);
+        assert!(lines.start <= line_count, "edit starts after the end of the document");
+        let end = lines.end.clamp(lines.start, line_count);
+        let mut first = self.entries.iter().position(|e| e.span.end > lines.start).unwrap_or(self.entries.len());
+        // Text added after a last line without a line ending joins it
+        if first == self.entries.len() && self.entries.last().is_some_and(|e| e.line_ending().is_none()) {
+            first -= 1;
+        }
+        let mut last = self.entries.iter().position(|e| e.span.start >= end).unwrap_or(self.entries.len()).max(first);
+        let chunk_start = self.entries.get(first).map_or(line_count, |e| e.span.start);
+
+        // The lines of the touched entries before and after the edit are
+        // kept
+        let old: String = self.entries[first..last].iter().map(|e| e.text()).collect();
+        let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+        let mut chunk: String = old_lines[..(lines.start - chunk_start) as usize].concat();
+        chunk.push_str(text);
+        chunk.push_str(&old_lines[(end - chunk_start) as usize..].concat());
+
+        let mut entries = parse_entries(&chunk);
+        while last < self.entries.len() && entries.last().is_some_and(|e| e.kind == EntryKind::Option(Err(ParseWarning::UnclosedInlineFile))) {
+            chunk.push_str(&self.entries[last].text);
+            last += 1;
+            entries = parse_entries(&chunk);
+        }
+        for entry in entries.iter_mut() {
+            entry.modified = true;
+        }
+        let reparsed = first..first + entries.len();
+        self.entries.splice(first..last, entries);
+        self.renumber();
+        reparsed
+    }
+
+    /// Set the spans of the entries from their text
+    fn renumber(&mut self) {
+        let mut line = 0;
+        for entry in self.entries.iter_mut() {
+            let lines = entry.text.split_inclusive('\n').count() as i32;
+            entry.span = line..line + lines;
+            line += lines;
+        }
+    }
+
+    /// The options of the document as `parse` would give them, numbered by
+    /// the line they start on, and the lines which are too long
+    pub fn to_parsed(&self) -> ParsedConfigFile {
+        let mut success_lines = Vec::new();
+        let mut warning_lines = Vec::new();
+        for entry in self.entries.iter() {
+            for (index, line) in entry.text.split_inclusive('\n').enumerate() {
+                if line_content(line).len() > MAX_LINE_LEN {
+                    warning_lines.push(ConfigLine{number: entry.span.start + index as i32, result: ParseWarning::LineTooLong});
+                }
+            }
+            match entry.kind {
+                EntryKind::Option(Ok(ref directive)) => {
+                    success_lines.push(ConfigLine{number: entry.span.start, result: directive.clone()})
+                },
+                EntryKind::Option(Err(ref warning)) => {
+                    warning_lines.push(ConfigLine{number: entry.span.start, result: warning.clone()})
+                },
+                _ => {},
+            }
+        }
+        ParsedConfigFile{success_lines, warning_lines}
+    }
+
+    /// The document with each option written as `as_ovpn_config` gives it,
+    /// keeping a comment at the end of its line, comments without their
+    /// indentation and blank lines empty. Lines which couldn't be parsed are
+    /// kept as they are. The entries which changed are marked as modified.
+    pub fn formatted(&self) -> ConfigDocument {
+        let entries = self.entries.iter().map(|entry| {
+            let line_ending = entry.line_ending().unwrap_or("");
+            let text = match entry.kind {
+                EntryKind::Blank => line_ending.to_string(),
+                EntryKind::Comment => entry.comment().unwrap_or("").to_string() + line_ending,
+                EntryKind::Option(Ok(ref directive)) => {
+                    let mut text = directive.as_ovpn_config().replace('\n', line_ending);
+                    if let Some(comment) = entry.comment() {
+                        text.push(' ');
+                        text.push_str(comment);
+                    }
+                    text + line_ending
+                },
+                EntryKind::Option(Err(_)) => entry.text.clone(),
+            };
+            let modified = entry.modified || text != entry.text;
+            DocumentEntry{span: entry.span.clone(), kind: entry.kind.clone(), text, modified}
+        }).collect();
+        let mut document = ConfigDocument{entries};
+        document.renumber();
+        document
+    }
+}
+
+impl fmt::Display for ConfigDocument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for entry in self.entries.iter() {
+            f.write_str(&entry.text)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {parse_str, File};
+
+    const CONFIG: &str = "# Example\r\nclient\r\n\r\nremote   vpn.example.com 1194 # primary\r\n<ca>\r\nca line\r\n</ca>\r\nunknown-option\r\nverb 3";
+
+    #[test]
+    fn test_round_trip() {
+        let document = ConfigDocument::parse(CONFIG);
+        assert_eq!(document.to_string(), CONFIG);
+        let kinds: Vec<(Range<i32>, bool)> = document.entries().iter()
+            .map(|e| (e.span.clone(), e.directive().is_some()))
+            .collect();
+        assert_eq!(kinds, vec![(0..1, false), (1..2, true), (2..3, false), (3..4, true), (4..7, true), (7..8, false), (8..9, true)]);
+        assert_eq!(document.entries()[0].kind, EntryKind::Comment);
+        assert_eq!(document.entries()[2].kind, EntryKind::Blank);
+        assert_eq!(document.entries()[3].comment(), Some("# primary"));
+        assert_eq!(document.entries()[4].comment(), None);
+
+        let parsed = parse_str(CONFIG);
+        assert_eq!(document.to_parsed().success_lines, parsed.success_lines);
+        assert_eq!(document.to_parsed().warning_lines, parsed.warning_lines);
+
+        let unclosed = ConfigDocument::parse("client\n<ca>\nca line\n");
+        assert_eq!(unclosed.entries()[1].kind, EntryKind::Option(Err(ParseWarning::UnclosedInlineFile)));
+        assert_eq!(unclosed.to_string(), "client\n<ca>\nca line\n");
+    }
+
+    #[test]
+    fn test_formatted() {
+        let document = ConfigDocument::parse(CONFIG).formatted();
+        assert_eq!(
+            document.to_string(),
+            "# Example\r\nclient\r\n\r\nremote vpn.example.com 1194 # primary\r\n<ca>\r\nca line\r\n</ca>\r\nunknown-option\r\nverb 3",
+        );
+        let modified: Vec<bool> = document.entries().iter().map(|e| e.is_modified()).collect();
+        assert_eq!(modified, vec![false, false, false, true, false, false, false]);
+        assert_eq!(document.to_parsed().success_lines, ConfigDocument::parse(CONFIG).to_parsed().success_lines);
+
+        let document = ConfigDocument::parse("  ; indented  \n   \nverb   3;quiet\nsetenv   A b ;note\n").formatted();
+        assert_eq!(document.to_string(), "; indented\n\nverb 3;quiet\nsetenv A b ;note\n");
+    }
+
+    #[test]
+    fn test_edits() {
+        let mut document = ConfigDocument::parse(CONFIG);
+        let ca = document.find("ca").unwrap();
+        document.set_directive(ca, ConfigDirective::Ca{file: File::FilePath("ca.crt".to_string())});
+        document.insert_directive(document.entries().len(), ConfigDirective::Nobind);
+        let unknown = document.entry_at_line(5).unwrap();
+        document.remove(unknown);
+        assert_eq!(
+            document.to_string(),
+            "# Example\r\nclient\r\n\r\nremote   vpn.example.com 1194 # primary\r\nca ca.crt\r\nverb 3\r\nnobind\r\n",
+        );
+        let modified: Vec<bool> = document.entries().iter().map(|e| e.is_modified()).collect();
+        assert_eq!(modified, vec![false, false, false, false, true, true, true]);
+        assert_eq!(document.entries()[6].span, 6..7);
+    }
+
+    #[test]
+    fn test_apply_edit() {
+        let mut document = ConfigDocument::parse(CONFIG);
+        let reparsed = document.apply_edit(3..4, "remote backup.example.com\r\nremote-random\r\n");
+        assert_eq!(reparsed, 3..5);
+        assert_eq!(document.entries()[4].directive(), Some(&ConfigDirective::RemoteRandom));
+        assert_eq!(document.entries()[5].span, 5..8);
+        assert!(!document.entries()[5].is_modified());
+
+        // Every edit gives the same document as parsing the edited text
+        let line_count = CONFIG.split_inclusive('\n').count() as i32;
+        for text in ["", "<ca>\r\n", "</ca>\r\n", "remote a.example.com\r\n", "# comment\r\n<tls-auth>\r\n"].iter() {
+            for start in 0..=line_count {
+                for end in start..=line_count {
+                    let mut document = ConfigDocument::parse(CONFIG);
+                    document.apply_edit(start..end, text);
+                    let lines: Vec<&str> = CONFIG.split_inclusive('\n').collect();
+                    let edited = format!("{}{}{}", lines[..start as usize].concat(), text, lines[end as usize..].concat());
+                    let expected = ConfigDocument::parse(&edited);
+                    let entries = |d: &ConfigDocument| -> Vec<(Range<i32>, EntryKind, String)> {
+                        d.entries().iter().map(|e| (e.span.clone(), e.kind.clone(), e.text.clone())).collect()
+                    };
+                    assert_eq!(entries(&document), entries(&expected), "{:?} at {}..{}", text, start, end);
+                }
+            }
+        }
+    }
+}
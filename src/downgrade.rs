@@ -0,0 +1,131 @@
+//! Rewriting a config for clients running an older version of openvpn
+use {ConfigDirective, ConfigLine, ParsedConfigFile, Version};
+
+/// Options which change how peers authenticate each other, so can't be
+/// dropped without breaking the connection
+const AUTHENTICATION_OPTIONS: &[&str] = &["tls-crypt", "tls-crypt-v2", "peer-fingerprint"];
+
+/// How a line was changed by `downgraded`. Every change loses something:
+/// a list of ciphers becomes one cipher, a compression algorithm becomes
+/// `comp-lzo` or an option is dropped.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum DowngradeChange {
+    /// The line was removed as the target version doesn't support it
+    Dropped(ConfigLine<ConfigDirective>),
+    /// The line was replaced by the closest option the target version
+    /// supports
+    Rewritten{original: ConfigLine<ConfigDirective>, replacement: ConfigDirective},
+    /// The target version doesn't support the line and it has no
+    /// equivalent, but it was kept as removing it would break the
+    /// connection, e.g `tls-crypt`. The config needs changing by hand on
+    /// both ends.
+    Unsupported(ConfigLine<ConfigDirective>),
+}
+
+/// The result of `downgraded`
+pub struct Downgraded {
+    /// The config rewritten for the target version. Rewritten lines keep
+    /// their line numbers.
+    pub config: ParsedConfigFile,
+    /// The lines which were dropped, rewritten or couldn't be changed, in
+    /// the order they appear
+    pub changes: Vec<DowngradeChange>,
+}
+
+/// The `comp-lzo` mode closest to a `compress` algorithm. Only `lzo`
+/// compresses, `comp-lzo no` keeps the framing of the other algorithms
+/// without compressing.
+fn comp_lzo_mode(algorithm: Option<&str>) -> &'static str {
+    if algorithm == Some("lzo") { "yes" } else { "no" }
+}
+
+impl ParsedConfigFile {
+    /// This config rewritten for clients running `target`, such as routers
+    /// stuck on old firmware, with a report of what was changed. Options
+    /// newer than `target` are rewritten to an older equivalent where there
+    /// is one: `data-ciphers` becomes `ncp-ciphers` on 2.4 or the first of
+    /// its ciphers as `cipher` before that, `data-ciphers-fallback` becomes
+    /// `cipher` and `compress` becomes `comp-lzo`. Options which affect
+    /// authentication, such as `tls-crypt`, are kept and reported, the rest
+    /// are dropped.
+    pub fn downgraded(&self, target: Version) -> Downgraded {
+        let has_cipher = self.success_lines.iter().any(|l| l.result.openvpn_option_name() == "cipher");
+        let mut success_lines = Vec::new();
+        let mut changes = Vec::new();
+        for line in self.success_lines.iter() {
+            if line.result.required_version().is_none_or(|v| v <= target) {
+                success_lines.push(line.clone());
+                continue;
+            }
+            let replacement = match line.result {
+                ConfigDirective::DataCiphers{ref cipher_list} | ConfigDirective::NcpCiphers{ref cipher_list} => {
+                    if target >= Version::new(2, 4) {
+                        Some(ConfigDirective::NcpCiphers{cipher_list: cipher_list.clone()})
+                    } else if has_cipher {
+                        None
+                    } else {
+                        cipher_list.split(':').next().map(|alg| ConfigDirective::Cipher{alg: alg.to_string()})
+                    }
+                },
+                ConfigDirective::DataCiphersFallback{ref alg} if !has_cipher => Some(ConfigDirective::Cipher{alg: alg.clone()}),
+                ConfigDirective::Compress{ref algorithm} => Some(ConfigDirective::CompLzo{
+                    mode: Some(comp_lzo_mode(algorithm.as_ref().map(|a| &a[..])).to_string()),
+                }),
+                _ => None,
+            };
+            match replacement {
+                Some(replacement) => {
+                    success_lines.push(ConfigLine{number: line.number, result: replacement.clone()});
+                    changes.push(DowngradeChange::Rewritten{original: line.clone(), replacement});
+                },
+                None if AUTHENTICATION_OPTIONS.contains(&line.result.openvpn_option_name()) => {
+                    success_lines.push(line.clone());
+                    changes.push(DowngradeChange::Unsupported(line.clone()));
+                },
+                None => changes.push(DowngradeChange::Dropped(line.clone())),
+            }
+        }
+        Downgraded{
+            config: ParsedConfigFile{success_lines, warning_lines: self.warning_lines.clone()},
+            changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn downgraded(contents: &str, target: Version) -> (Vec<String>, Vec<DowngradeChange>) {
+        let result = parse_str(contents).downgraded(target);
+        let lines = result.config.success_lines.iter()
+            .map(|l| l.result.as_ovpn_config())
+            .collect();
+        (lines, result.changes)
+    }
+
+    #[test]
+    fn test_downgraded() {
+        let contents = "client\ndata-ciphers AES-256-GCM:AES-128-CBC\ncompress lz4-v2\nallow-compression no\n\
+                        tls-crypt tc.key\nverb 3\n";
+        let (lines, changes) = downgraded(contents, Version::new(2, 3));
+        assert_eq!(lines, vec!["client", "cipher AES-256-GCM", "comp-lzo no", "tls-crypt tc.key", "verb 3"]);
+        assert_eq!(changes[2], DowngradeChange::Dropped(ConfigLine{
+            number: 3,
+            result: ConfigDirective::AllowCompression{mode: "no".to_string()},
+        }));
+        assert_eq!(changes[3], DowngradeChange::Unsupported(ConfigLine{
+            number: 4,
+            result: ConfigDirective::TlsCrypt{file: ::File::FilePath("tc.key".to_string())},
+        }));
+
+        let (lines, changes) = downgraded(contents, Version::new(2, 4));
+        assert_eq!(lines, vec!["client", "ncp-ciphers AES-256-GCM:AES-128-CBC", "compress lz4-v2", "tls-crypt tc.key", "verb 3"]);
+        assert_eq!(changes.len(), 2);
+
+        let (lines, _) = downgraded("cipher AES-256-CBC\ndata-ciphers-fallback BF-CBC\ndata-ciphers AES-256-GCM\n", Version::new(2, 3));
+        assert_eq!(lines, vec!["cipher AES-256-CBC"]);
+        assert!(downgraded(contents, Version::new(2, 6)).1.is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+//! Rewriting helper options into the options they stand for, as documented
+//! in the openvpn manual
+use std::net::Ipv4Addr;
+use {ConfigDirective, ConfigLine, Ipv4Network, Mode, ParsedConfigFile, Role};
+
+/// The topology and route-gateway settings `server` depends on
+struct ServerSettings {
+    tun: bool,
+    topology: String,
+    client_to_client: bool,
+    route_gateway: bool,
+}
+
+impl ServerSettings {
+    fn new(config: &ParsedConfigFile) -> ServerSettings {
+        let mut settings = ServerSettings{
+            tun: !config.is_tap(),
+            topology: "net30".to_string(),
+            client_to_client: false,
+            route_gateway: false,
+        };
+        for line in config.success_lines.iter() {
+            match line.result {
+                ConfigDirective::Topology{ref mode} => settings.topology = mode.clone(),
+                ConfigDirective::ClientToClient => settings.client_to_client = true,
+                ConfigDirective::RouteGateway{..} => settings.route_gateway = true,
+                _ => {},
+            }
+        }
+        settings
+    }
+}
+
+fn offset(address: Ipv4Addr, by: i64) -> String {
+    Ipv4Addr::from((i64::from(u32::from(address)) + by) as u32).to_string()
+}
+
+fn push(option: String) -> ConfigDirective {
+    ConfigDirective::Push{option}
+}
+
+/// The options `server network netmask [nopool]` stands for, `None` if the
+/// network isn't valid
+fn expand_server(settings: &ServerSettings, network: &str, netmask: &str, nopool: bool) -> Option<Vec<ConfigDirective>> {
+    let network = Ipv4Network::with_netmask(network.parse().ok()?, netmask.parse().ok()?)?;
+    let (first, last) = network.range();
+    let mut result = vec![
+        ConfigDirective::Mode{m: Mode::Server},
+        ConfigDirective::TlsServer,
+        push(format!("topology {}", settings.topology)),
+    ];
+    if settings.tun && (settings.topology == "net30" || settings.topology == "p2p") {
+        result.push(ConfigDirective::Ifconfig{l: offset(first, 1), rn: offset(first, 2)});
+        if !nopool {
+            result.push(ConfigDirective::IfconfigPool{start_ip: offset(first, 4), end_ip: offset(last, -4), netmask: None});
+        }
+        result.push(ConfigDirective::Route{
+            network_or_ip: first.to_string(),
+            netmask: Some(netmask.to_string()),
+            gateway: None,
+            metric: None,
+        });
+        if settings.client_to_client {
+            result.push(push(format!("route {} {}", first, netmask)));
+        } else if settings.topology == "net30" {
+            result.push(push(format!("route {}", offset(first, 1))));
+        }
+    } else {
+        result.push(ConfigDirective::Ifconfig{l: offset(first, 1), rn: netmask.to_string()});
+        if !nopool {
+            result.push(ConfigDirective::IfconfigPool{
+                start_ip: offset(first, 2),
+                end_ip: offset(last, -2),
+                netmask: Some(netmask.to_string()),
+            });
+        }
+        result.push(push(format!("route-gateway {}", offset(first, 1))));
+        if !settings.route_gateway {
+            result.push(ConfigDirective::RouteGateway{gw_or_dhcp: offset(first, 2)});
+        }
+    }
+    Some(result)
+}
+
+impl ParsedConfigFile {
+    /// This config with the helper options `client`, `server` and
+    /// `keepalive` replaced by the options the openvpn manual says they
+    /// stand for. In server mode `keepalive` also pushes `ping` and
+    /// `ping-restart` to clients and doubles the server's own
+    /// `ping-restart`. The options a helper is replaced by have the helper's
+    /// line number. `server` is left as it is if its network isn't valid.
+    pub fn expand(&self) -> ParsedConfigFile {
+        let server_mode = self.role() == Role::Server;
+        let settings = ServerSettings::new(self);
+        let mut success_lines = Vec::new();
+        for line in self.success_lines.iter() {
+            let expanded = match line.result {
+                ConfigDirective::Client => vec![ConfigDirective::TlsClient, ConfigDirective::Pull],
+                ConfigDirective::Keepalive{ref interval, ref timeout} => {
+                    let ping_restart = if server_mode {
+                        timeout.parse::<u64>().ok().and_then(|t| t.checked_mul(2))
+                            .map_or_else(|| timeout.clone(), |t| t.to_string())
+                    } else {
+                        timeout.clone()
+                    };
+                    let mut result = vec![
+                        ConfigDirective::Ping{n: interval.clone()},
+                        ConfigDirective::PingRestart{n: ping_restart},
+                    ];
+                    if server_mode {
+                        result.push(push(format!("ping {}", interval)));
+                        result.push(push(format!("ping-restart {}", timeout)));
+                    }
+                    result
+                },
+                ConfigDirective::Server{ref network, ref netmask, ref nopool} => {
+                    expand_server(&settings, network, netmask, nopool.is_some())
+                        .unwrap_or_else(|| vec![line.result.clone()])
+                },
+                ref directive => vec![directive.clone()],
+            };
+            success_lines.extend(expanded.into_iter().map(|result| ConfigLine{number: line.number, result}));
+        }
+        ParsedConfigFile{success_lines, warning_lines: self.warning_lines.clone()}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+
+    fn expanded(contents: &str) -> Vec<String> {
+        let config = parse_str(contents);
+        config.expand().directives().iter().map(|d| d.as_ovpn_config()).collect()
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expanded("client\nkeepalive 10 60\n"), vec!["tls-client", "pull", "ping 10", "ping-restart 60"]);
+        assert_eq!(expanded("dev tun\nserver 10.8.0.0 255.255.255.0\nkeepalive 10 60\n"), vec![
+            "dev tun",
+            "mode server",
+            "tls-server",
+            "push \"topology net30\"",
+            "ifconfig 10.8.0.1 10.8.0.2",
+            "ifconfig-pool 10.8.0.4 10.8.0.251",
+            "route 10.8.0.0 255.255.255.0",
+            "push \"route 10.8.0.1\"",
+            "ping 10",
+            "ping-restart 120",
+            "push \"ping 10\"",
+            "push \"ping-restart 60\"",
+        ]);
+        let huge = "18446744073709551615";
+        let expanded_huge = expanded(&format!("mode server\nkeepalive 10 {}\n", huge));
+        assert!(expanded_huge.contains(&format!("ping-restart {}", huge)));
+        assert_eq!(expanded("dev tap\nserver 10.8.0.0 255.255.255.0 nopool\n"), vec![
+            "dev tap",
+            "mode server",
+            "tls-server",
+            "push \"topology net30\"",
+            "ifconfig 10.8.0.1 255.255.255.0",
+            "push \"route-gateway 10.8.0.1\"",
+            "route-gateway 10.8.0.2",
+        ]);
+    }
+}
@@ -0,0 +1,118 @@
+//! The files and directories outside a config file which it refers to, so
+//! that bundling tools know what has to go with it
+use std::borrow::Cow;
+use {split_args, ConfigDirective, CrlVerifyFlag, File, ParsedConfigFile};
+
+/// How openvpn uses a file a config refers to
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum FileUse {
+    /// The file is read, e.g a certificate or key
+    Read,
+    /// The file is written by openvpn, e.g a log or status file
+    Written,
+    /// A directory, e.g `client-config-dir`
+    Directory,
+    /// A script or plugin which openvpn runs
+    Executed,
+}
+
+/// A file or directory a config refers to
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct FileReference {
+    /// The option the path was given to
+    pub option: &'static str,
+    pub line: i32,
+    pub path: String,
+    pub usage: FileUse,
+}
+
+/// The path a script option runs, the first word of its command
+fn script_path(cmd: &str) -> Option<String> {
+    split_args(cmd).into_iter().next().map(Cow::into_owned)
+}
+
+/// A path which may be `stdin`, meaning openvpn will prompt instead
+fn prompt_path(path: &Option<String>) -> Option<String> {
+    path.clone().filter(|p| p != "stdin")
+}
+
+/// The path of a file which may be `stdin` or inline
+fn prompt_file(file: &Option<File>) -> Option<String> {
+    match *file {
+        Some(File::FilePath(ref path)) if path != "stdin" => Some(path.clone()),
+        _ => None,
+    }
+}
+
+/// Every file and directory the config refers to by path, in the order
+/// they appear. Inline files and credentials which openvpn prompts for are
+/// not included. For scripts the path is the first word of the command.
+pub fn referenced_files(config: &ParsedConfigFile) -> Vec<FileReference> {
+    config.success_lines.iter().filter_map(|line| {
+        let (path, usage) = match line.result {
+            ConfigDirective::CrlVerify{file: File::FilePath(ref path), flag: Some(CrlVerifyFlag::Dir)} => {
+                (Some(path.clone()), FileUse::Directory)
+            },
+            ref directive if directive.file().is_some() => match directive.file() {
+                Some(File::FilePath(path)) => (Some(path.clone()), FileUse::Read),
+                _ => (None, FileUse::Read),
+            },
+            ConfigDirective::Config{ref file} => (Some(file.clone()), FileUse::Read),
+            ConfigDirective::SocksProxy{ref authfile, ..} => (authfile.clone(), FileUse::Read),
+            ConfigDirective::Management{ref pw_file, ..} => (prompt_path(pw_file), FileUse::Read),
+            ConfigDirective::AuthUserPass{ref up} => (prompt_file(up), FileUse::Read),
+            ConfigDirective::Askpass{ref file} => (prompt_path(file), FileUse::Read),
+            ConfigDirective::Log{ref file} |
+            ConfigDirective::LogAppend{ref file} |
+            ConfigDirective::Writepid{ref file} |
+            ConfigDirective::Status{ref file, ..} |
+            ConfigDirective::IfconfigPoolPersist{ref file, ..} |
+            ConfigDirective::ReplayPersist{ref file} => (Some(file.clone()), FileUse::Written),
+            ConfigDirective::Cd{ref dir} |
+            ConfigDirective::Chroot{ref dir} |
+            ConfigDirective::Capath{ref dir} |
+            ConfigDirective::ClientConfigDir{ref dir} |
+            ConfigDirective::TmpDir{ref dir} => (Some(dir.clone()), FileUse::Directory),
+            ConfigDirective::PortShare{ref dir, ..} => (dir.clone(), FileUse::Directory),
+            ConfigDirective::TlsExportCert{ref directory} => (Some(directory.clone()), FileUse::Directory),
+            ConfigDirective::Plugin{ref module_pathname, ..} => (Some(module_pathname.clone()), FileUse::Executed),
+            ConfigDirective::Up{ref cmd} |
+            ConfigDirective::Down{ref cmd} |
+            ConfigDirective::RouteUp{ref cmd} |
+            ConfigDirective::RoutePreDown{ref cmd} |
+            ConfigDirective::Ipchange{ref cmd} |
+            ConfigDirective::Iproute{ref cmd} |
+            ConfigDirective::ClientConnect{ref cmd} |
+            ConfigDirective::ClientDisconnect{ref cmd} |
+            ConfigDirective::LearnAddress{ref cmd} |
+            ConfigDirective::AuthUserPassVerify{ref cmd, ..} |
+            ConfigDirective::TlsVerify{ref cmd} |
+            ConfigDirective::TlsCryptV2Verify{ref cmd} => (script_path(cmd), FileUse::Executed),
+            _ => return None,
+        };
+        path.map(|path| FileReference{option: line.result.openvpn_option_name(), line: line.number, path, usage})
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_referenced_files() {
+        let contents = "client\nca ca.crt\n<key>\nkey contents\n</key>\nauth-user-pass\nauth-user-pass creds.txt\n\
+                        up \"/etc/openvpn/up.sh --verbose\"\nstatus status.log 10\nclient-config-dir ccd\n\
+                        <auth-user-pass>\nuser\npass\n</auth-user-pass>\ncrl-verify crl.pem\ncrl-verify crls dir\n";
+        let config = parse_str(contents);
+        assert_eq!(referenced_files(&config), vec![
+            FileReference{option: "ca", line: 1, path: "ca.crt".to_string(), usage: FileUse::Read},
+            FileReference{option: "auth-user-pass", line: 6, path: "creds.txt".to_string(), usage: FileUse::Read},
+            FileReference{option: "up", line: 7, path: "/etc/openvpn/up.sh".to_string(), usage: FileUse::Executed},
+            FileReference{option: "status", line: 8, path: "status.log".to_string(), usage: FileUse::Written},
+            FileReference{option: "client-config-dir", line: 9, path: "ccd".to_string(), usage: FileUse::Directory},
+            FileReference{option: "crl-verify", line: 14, path: "crl.pem".to_string(), usage: FileUse::Read},
+            FileReference{option: "crl-verify", line: 15, path: "crls".to_string(), usage: FileUse::Directory},
+        ]);
+    }
+}
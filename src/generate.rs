@@ -0,0 +1,178 @@
+//! Generating the client profiles which go with a server config
+use std::error::Error;
+use std::fmt;
+use {ConfigDirective, ConfigLine, File, ParsedConfigFile, Role};
+
+/// Options which have to be the same on the client as on the server, and so
+/// are copied to client profiles
+const MIRRORED_OPTIONS: &[&str] = &[
+    "dev", "dev-type", "cipher", "data-ciphers", "data-ciphers-fallback", "auth", "tls-version-min",
+    "tls-cipher", "compress", "comp-lzo", "tun-mtu",
+];
+
+/// The certificates and keys of the client a profile is generated for, PEM
+/// encoded
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ClientCredentials {
+    pub ca: String,
+    pub cert: String,
+    pub key: String,
+    /// The `tls-auth` or `tls-crypt` key shared with the server, or the
+    /// client's own `tls-crypt-v2` key. Only needed if the server uses one
+    /// of these, and for `tls-auth` and `tls-crypt` only if the server's key
+    /// isn't inline.
+    pub tls_key: Option<String>,
+}
+
+/// Why a client profile couldn't be generated
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ProfileError {
+    /// The config isn't a server config
+    NotAServer,
+    /// The server uses `tls-auth`, `tls-crypt` or `tls-crypt-v2` but the
+    /// key wasn't given
+    MissingTlsKey,
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProfileError::NotAServer => f.write_str("the config is not a server config"),
+            ProfileError::MissingTlsKey => f.write_str("the server uses a TLS key but none was given"),
+        }
+    }
+}
+
+impl Error for ProfileError {}
+
+/// The `key-direction` the other end of a tunnel needs
+fn flip_direction(direction: &str) -> String {
+    if direction == "0" { "1" } else { "0" }.to_string()
+}
+
+/// The protocol a client uses to connect to a server using `proto`
+fn client_proto(proto: &str) -> String {
+    proto.replace("-server", "-client")
+}
+
+fn inline(contents: &str) -> File {
+    File::InlineFileContents(contents.trim_end().to_string())
+}
+
+/// Generate a client profile for connecting to `server` at `host`, with the
+/// credentials of the client inline. The port and protocol come from the
+/// server, the options which have to match the server's, such as `cipher`,
+/// `auth` and `compress`, are copied from it, and the `tls-auth` key
+/// direction is the opposite of the server's.
+pub fn client_profile(server: &ParsedConfigFile, host: &str, credentials: &ClientCredentials)
+    -> Result<ParsedConfigFile, ProfileError>
+{
+    if server.role() != Role::Server {
+        return Err(ProfileError::NotAServer);
+    }
+    let mut port = "1194".to_string();
+    let mut proto = "udp".to_string();
+    let mut mirrored = Vec::new();
+    let mut tls = None;
+    let mut key_direction = None;
+    let mut auth_user_pass = false;
+    for line in server.success_lines.iter() {
+        match line.result {
+            ConfigDirective::Port{port: ref p} | ConfigDirective::Lport{port: ref p} => port = p.clone(),
+            ConfigDirective::Proto{ref p} => proto = client_proto(p),
+            ConfigDirective::TlsAuth{ref direction, ..} => {
+                key_direction = direction.clone().or(key_direction);
+                tls = Some(&line.result);
+            },
+            ConfigDirective::TlsCrypt{..} | ConfigDirective::TlsCryptV2{..} => tls = Some(&line.result),
+            ConfigDirective::KeyDirection{ref direction} => key_direction = Some(direction.clone()),
+            ConfigDirective::AuthUserPassVerify{..} => auth_user_pass = true,
+            ref directive if MIRRORED_OPTIONS.contains(&directive.openvpn_option_name()) => {
+                mirrored.push(directive.clone());
+            },
+            _ => {},
+        }
+    }
+
+    let mut directives = vec![
+        ConfigDirective::Client,
+        ConfigDirective::Remote{host: host.to_string(), port: Some(port), proto: Some(proto)},
+        ConfigDirective::Nobind,
+        ConfigDirective::PersistKey,
+        ConfigDirective::PersistTun,
+        ConfigDirective::RemoteCertTls{client_or_server: "server".to_string()},
+    ];
+    directives.extend(mirrored);
+    if auth_user_pass {
+        directives.push(ConfigDirective::AuthUserPass{up: None});
+    }
+    directives.push(ConfigDirective::Ca{file: inline(&credentials.ca)});
+    directives.push(ConfigDirective::Cert{file: inline(&credentials.cert)});
+    directives.push(ConfigDirective::Key{file: inline(&credentials.key)});
+    if let Some(tls) = tls {
+        let server_key = match tls.file() {
+            Some(File::InlineFileContents(contents)) if tls.openvpn_option_name() != "tls-crypt-v2" => Some(contents),
+            _ => None,
+        };
+        let key = credentials.tls_key.as_ref().or(server_key).ok_or(ProfileError::MissingTlsKey)?;
+        match *tls {
+            ConfigDirective::TlsAuth{..} => {
+                directives.push(ConfigDirective::TlsAuth{file: inline(key), direction: None});
+                if let Some(direction) = key_direction {
+                    directives.push(ConfigDirective::KeyDirection{direction: flip_direction(&direction)});
+                }
+            },
+            ConfigDirective::TlsCrypt{..} => directives.push(ConfigDirective::TlsCrypt{file: inline(key)}),
+            _ => directives.push(ConfigDirective::TlsCryptV2{file: inline(key), cookie_mode: None}),
+        }
+    }
+    let success_lines = directives.into_iter().enumerate()
+        .map(|(number, result)| ConfigLine{number: number as i32, result})
+        .collect();
+    Ok(ParsedConfigFile{success_lines, warning_lines: Vec::new()})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn credentials(tls_key: Option<&str>) -> ClientCredentials {
+        ClientCredentials{
+            ca: "ca contents\n".to_string(),
+            cert: "cert contents\n".to_string(),
+            key: "key contents\n".to_string(),
+            tls_key: tls_key.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_client_profile() {
+        let server = parse_str(
+            "port 443\nproto tcp-server\ndev tun\nserver 10.8.0.0 255.255.255.0\nca ca.crt\ncert server.crt\nkey server.key\n\
+             tls-auth ta.key 0\ncipher AES-256-GCM\nauth SHA256\nkeepalive 10 60\n",
+        );
+        assert_eq!(client_profile(&server, "vpn.example.com", &credentials(None)).err(), Some(ProfileError::MissingTlsKey));
+        let profile = client_profile(&server, "vpn.example.com", &credentials(Some("static key"))).unwrap();
+        let options: Vec<String> = profile.directives().iter().map(|d| d.as_ovpn_config()).collect();
+        assert_eq!(options, vec![
+            "client",
+            "remote vpn.example.com 443 tcp-client",
+            "nobind",
+            "persist-key",
+            "persist-tun",
+            "remote-cert-tls server",
+            "dev tun",
+            "cipher AES-256-GCM",
+            "auth SHA256",
+            "<ca>\nca contents\n</ca>",
+            "<cert>\ncert contents\n</cert>",
+            "<key>\nkey contents\n</key>",
+            "<tls-auth>\nstatic key\n</tls-auth>",
+            "key-direction 1",
+        ]);
+
+        let client = parse_str("client\nremote vpn.example.com\n");
+        assert_eq!(client_profile(&client, "vpn.example.com", &credentials(None)).err(), Some(ProfileError::NotAServer));
+    }
+}
@@ -57,6 +57,36 @@
 //! there are missing required arguments for the command result in warning, as
 //! you can see from the above example.
 //!
+//! A config which is already in memory, such as a memory mapped file, can be
+//! parsed with `ovpnfile::parse_bytes` instead, which doesn't copy each line.
+//! `ovpnfile::Parser` gives more control over parsing, such as a callback
+//! for each warning as it's found. `ConfigDocument` keeps comments, blank
+//! lines and formatting, for editors which need to write a config back
+//! without disturbing the lines they didn't change.
+//!
+//! # Typed Arguments
+//! Some arguments have a well defined format, numeric counts for example.
+//! These are parsed into a suitable type rather than a `String`, if the value
+//! in the config file can't be parsed the line results in a
+//! `ParseWarning::InvalidArgument`.
+//!
+//! ```
+//! use std::io::{BufReader};
+//! use ovpnfile::{ConfigDirective, ConfigLine, ParseWarning};
+//! use ovpnfile;
+//!
+//! let contents = r"
+//! explicit-exit-notify 2
+//! explicit-exit-notify lots
+//! ".as_bytes();
+//!
+//! let result = ovpnfile::parse(BufReader::new(contents)).unwrap();
+//! assert!(result.success_lines == vec![
+//!     ConfigLine{number: 1, result: ConfigDirective::ExplicitExitNotify{n: Some(2)}},
+//! ]);
+//! assert!(result.warning_lines == vec![ConfigLine{number: 2, result: ParseWarning::InvalidArgument}]);
+//! ```
+//!
 //! # Inline File Contents
 //! As mentioned earlier some commands can include file contents inline in the
 //! config file. These commands are:
@@ -73,12 +103,16 @@
 //! --http-proxy-user-pass
 //! --tls-auth
 //! --tls-crypt
+//! --tls-crypt-v2
 //! --secret
+//! --peer-fingerprint
+//! --auth-user-pass
 //! ```
 //!
 //! The corresponding enum variants have a `file` record attribute which is an
 //! instance of `File`. `File` is either an `InlineFileContents(String)` or a
-//! `FilePath(String)`. So for example
+//! `FilePath(String)`, or `None` in the case of `dh none` which disables
+//! static DH parameters. So for example
 //!
 //! ```
 //! use std::io::{BufReader};
@@ -107,62 +141,181 @@
 //! ]);
 //! ```
 //!
+//! The exception is `--peer-fingerprint`, an inline `<peer-fingerprint>` block
+//! contains one fingerprint per line so the `PeerFingerprint` variant holds a
+//! list of fingerprints whether they were given inline or as an argument.
+//! The file of `--auth-user-pass` is optional, openvpn prompts for the
+//! username and password without one, so its `up` attribute is an
+//! `Option<File>`.
+//!
+//! A `<connection>` block isn't a file, the options in it are parsed into a
+//! `Connection` variant. A block with an option which can't be parsed is
+//! reported with that option's warning, on the line the block starts on.
+//!
 //! # Server Bridge
-//! The `--server-bridge` argument is special, it can take two forms
+//! The `--server-bridge` argument is special, it can take three forms
 //!
 //! ```text
 //! server-bridge gateway netmask pool-start-IP pool-end-IP
+//! server-bridge gateway netmask
 //! server-bridge nogw
 //! ```
 //!
 //! This is represented in this library as the `ServerBridgeArg` enum variant, it
-//! can either be a `NoGateway` or `GatewayConfig{gateway: String, netmask: String,
-//! pool_start_ip: String, pool_end_ip: String}`.
+//! can either be a `NoGateway`, a `GatewayConfig{gateway: Ipv4Addr, netmask:
+//! Ipv4Addr, pool_start_ip: Ipv4Addr, pool_end_ip: Ipv4Addr}` or a
+//! `DhcpProxy{gateway: Ipv4Addr, netmask: Ipv4Addr}`. Invalid addresses, a
+//! non contiguous netmask or a pool which starts after it ends result in a
+//! `ParseWarning::InvalidArgument`.
+//!
+//! # Versions
+//! `ConfigDirective::versions` (or `option_versions` given an option name)
+//! returns the openvpn versions in which an option was introduced, deprecated
+//! and removed, as far as they are known.
+//!
+//! ```
+//! use ovpnfile::{option_versions, Version};
+//!
+//! let versions = option_versions("tls-crypt").unwrap();
+//! assert_eq!(versions.introduced, Some(Version::new(2, 4)));
+//! assert!(versions.supported_in("2.6".parse().unwrap()));
+//! ```
+//!
+//! `required_openvpn_version` uses these to work out the oldest version of
+//! openvpn which can use a config file.
+//!
+//! `DirectiveSpec` describes each option at runtime: its arguments, their
+//! types and allowed values, and whether it can be inline, pushed or
+//! repeated, for tools such as editors and validators.
+//!
+//! The directives, their arguments and versions are generated at build time
+//! from the option synopses in the `ovpn_commands` file, so supporting a new
+//! option is usually a matter of adding a line there.
+//!
+//! # Linting
+//! The `lint` module contains checks which can be run on a parsed config
+//! file, see the module documentation for details.
+//!
+//! # Secrets
+//! `secrets` lists the private keys, static keys and credentials a config
+//! file contains inline or refers to by path, with their kind and line, so
+//! that they can be stored appropriately when importing a profile.
+//! `ParsedConfigFile::redacted` and the `Redacted` display wrapper hide the
+//! inline ones so that a config can be logged safely. `StaticKey` and
+//! `UserPass` read and write static keys and `auth-user-pass` files.
+//!
+//! # Features
+//! The `x509` feature enables the `x509` module, which gives the details of
+//! inline certificates, and the certificate expiry lint rule. The `crypto`
+//! feature, which implies `x509`, enables the `crypto` module and the lint
+//! rules which need to parse certificates, keys and DH parameters. Both
+//! depend on openssl.
+//!
+//! The `rayon` feature adds `parse_many`, which parses a batch of configs in
+//! parallel.
+//!
+//! The `cli` feature builds the `ovpnfile` command line tool, which lints,
+//! formats and inlines config files and converts them to JSON.
 //!
 //!
 //!
 #![recursion_limit="1024"]
 #[macro_use]
 extern crate error_chain;
-extern crate regex;
-#[macro_use]
-extern crate lazy_static;
+extern crate base64;
+#[cfg(feature = "x509")]
+extern crate openssl;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
 
-use std::io::{BufRead, BufReader, Read};
-use std::collections::HashSet;
-use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::str;
 
-pub use self::config_directive::{ConfigDirective, ServerBridgeArg, File};
+pub use self::config_directive::{ConfigDirective, ServerBridgeArg, DnsDirective, DnsServerOption, File, option_versions, argument_choices};
+pub use self::version::{Version, OptionVersions, required_openvpn_version};
+pub use self::static_key::{StaticKey, StaticKeyError, STATIC_KEY_LEN};
+pub use self::pkcs11::{Pkcs11Id, Pkcs11IdError};
+pub use self::user_pass::{UserPass, UserPassError, MAX_USER_PASS_LEN};
+pub use self::network::{Ipv4Network, Ipv6Network, NetworkParseError};
+pub use self::secrets::{secrets, Secret, SecretKind, SecretLocation};
+pub use self::credentials::CredentialPrompt;
+pub use self::files::{referenced_files, FileReference, FileUse};
+pub use self::redact::{Redacted, REDACTED};
+pub use self::bundle::{assemble_profile, split_profile};
+pub use self::generate::{client_profile, ClientCredentials, ProfileError};
+pub use self::template::{Template, TemplateArgument, TemplateError, TemplatedOption};
+pub use self::network_manager::{to_network_manager, from_network_manager, NetworkManagerSettings, NETWORK_MANAGER_SERVICE_TYPE};
+pub use self::wireguard::{to_wireguard, Untranslated, UntranslatedReason, WireGuardConfig};
+pub use self::openvpn3::{Openvpn3Change, Openvpn3Export};
+pub use self::modernize::{ModernizeChange, Modernized};
+pub use self::downgrade::{DowngradeChange, Downgraded};
+pub use self::privileges::{ManagementInterface, Privileges};
+pub use self::platform::{Platform, PortabilityIssue, PortabilityReason};
+pub use self::pushed::{PushedRoute, PushedSettings};
+#[cfg(feature = "rayon")]
+pub use self::batch::parse_many;
+pub use self::spec::{ArgumentSpec, DirectiveSpec};
+pub use self::raw::{tokenize, RawConfigFile, RawLine};
+use self::raw::{LineKind, Scanner};
+pub use self::document::{ConfigDocument, DocumentEntry, EntryKind};
+pub use self::remotes::{RemoteEndpoint, remote_endpoints, duplicate_remotes};
+pub use self::role::Role;
+pub use self::merge::{merge, MergedConfig, Replacement};
+pub use self::pull::{apply_pushed, parse_push_reply, push_reply_options, EffectiveConfig, PushChange, PushOutcome, IgnoreReason};
+pub use self::arg_types::{
+    Mode, HttpProxyAuth, HttpProxyAuthMethod, X509Field, EchoCommand, TlsCryptV2CookieMode,
+    DnsSecMode, DnsTransport, TlsCertProfile, VlanAccept, VlanId,
+    WindowsDriver, GenkeyType, HashAlgorithm, CertHash,
+    MssfixFlag, AuthGenTokenFlag, FragmentFlag, CrlVerifyFlag,
+    IpWin32Method,
+};
 mod config_directive;
+mod arg_types;
+mod version;
+mod spec;
+mod static_key;
+mod user_pass;
+mod pkcs11;
+mod network;
+mod secrets;
+mod remotes;
+mod role;
+mod merge;
+mod pull;
+mod raw;
+mod document;
+mod pushed;
+mod normalize;
+mod expand;
+mod files;
+mod credentials;
+mod bundle;
+mod redact;
+mod generate;
+mod template;
+mod network_manager;
+mod wireguard;
+mod openvpn3;
+mod modernize;
+mod downgrade;
+mod privileges;
+mod platform;
+pub mod lint;
+#[cfg(feature = "rayon")]
+mod batch;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+#[cfg(feature = "x509")]
+pub mod x509;
 
 mod errors {
     error_chain!{}
 }
 use errors::ResultExt;
 
-lazy_static! {
-    static ref COMMENT_REGEX: Regex = Regex::new(r"#.*$").unwrap();
-    static ref INLINE_START_REGEX: Regex = Regex::new(r"^<(\S+)>").unwrap();
-    static ref INLINE_END_REGEX: Regex = Regex::new(r"^</(\S+)>").unwrap();
-    static ref INLINE_FILE_OPTIONS: HashSet<&'static str> = {
-        let mut s = HashSet::new();
-        s.insert("ca");
-        s.insert("ca");
-        s.insert("cert");
-        s.insert("extra-certs");
-        s.insert("dh");
-        s.insert("key");
-        s.insert("pkcs12");
-        s.insert("crl-verify");
-        s.insert("http-proxy-user-pass");
-        s.insert("tls-auth");
-        s.insert("tls-crypt");
-        s.insert("secret");
-        s
-    };
-}
-
 /// Represents a line of the config file, the type `T` will be either
 /// a `ConfigDirective` or a `ParseWarning`.
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -171,11 +324,23 @@ pub struct ConfigLine<T> {
     pub result: T,
 }
 
+/// The longest line, in bytes, openvpn accepts in a config file, including
+/// the lines of inline files
+pub const MAX_LINE_LEN: usize = 256;
+
 /// Possible warnings
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum ParseWarning {
     NotEnoughArguments,
     NoMatchingCommand,
+    InvalidArgument,
+    /// The line is longer than `MAX_LINE_LEN`, openvpn will reject it. The
+    /// line is still parsed, and lines of inline files which are too long
+    /// are still included in the inline file's contents.
+    LineTooLong,
+    /// An inline file is never closed. Only reported when recovering from
+    /// this with `Parser::recover_unclosed_inline_files`.
+    UnclosedInlineFile,
 }
 
 /// The result of the `parse` function
@@ -187,7 +352,24 @@ pub struct ParsedConfigFile {
 impl ParsedConfigFile {
     /// Get the succesfully parsed ConfigDirectives.
     pub fn directives(&self) -> Vec<ConfigDirective> {
-        self.success_lines.iter().map(|l| l.result.clone()).collect()
+        self.iter().cloned().collect()
+    }
+
+    /// Iterate over the succesfully parsed ConfigDirectives without cloning
+    /// them
+    pub fn iter(&self) -> impl Iterator<Item = &ConfigDirective> {
+        self.success_lines.iter().map(|l| &l.result)
+    }
+
+    /// References to the succesfully parsed ConfigDirectives
+    pub fn directives_ref(&self) -> Vec<&ConfigDirective> {
+        self.iter().collect()
+    }
+
+    /// The succesfully parsed ConfigDirectives, moved out of the config
+    /// rather than cloned
+    pub fn into_directives(self) -> Vec<ConfigDirective> {
+        self.success_lines.into_iter().map(|l| l.result).collect()
     }
 }
 
@@ -195,102 +377,386 @@ struct InlineFileParseState {
     start_line_no: i32,
     identifier: String,
     lines: Vec<String>,
+    streamed_lines: usize,
 }
 
 impl InlineFileParseState {
-    fn new(line_no: usize, identifier: String) -> InlineFileParseState {
+    fn new(line_no: i32, identifier: String) -> InlineFileParseState {
         InlineFileParseState{
-            start_line_no: line_no as i32,
-            identifier: identifier,
+            start_line_no: line_no,
+            identifier,
             lines: Vec::new(),
+            streamed_lines: 0,
         }
     }
-    fn is_completed_by_line(&self, line: &str) -> bool {
-        if let Some(end_identifier_captures) = INLINE_END_REGEX.captures(line) {
-            return end_identifier_captures[1] == self.identifier
+    fn add_line(&mut self, line: &str) {
+        self.lines.push(line.to_string());
+    }
+    /// Write a line to `sink` rather than keeping it, separating it from the
+    /// previous line the same way `to_config_line` joins kept lines
+    fn stream_line(&mut self, line: &str, sink: &mut dyn Write) -> io::Result<()> {
+        if self.streamed_lines > 0 {
+            sink.write_all(b"\n")?;
         }
-        false
+        self.streamed_lines += 1;
+        sink.write_all(line.as_bytes())
     }
-    fn add_line(&mut self, line: String) {
-        self.lines.push(line);
+    fn to_config_line(&self) -> ConfigLine<Result<ConfigDirective, ParseWarning>> {
+        ConfigLine{result: inline_directive(&self.identifier, self.lines.join("\n")), number: self.start_line_no}
     }
-    fn to_config_line(&self) -> ConfigLine<ConfigDirective> {
-        let file = File::InlineFileContents(self.lines.join("\n"));
-        let directive = match self.identifier.as_ref() {
-            "ca" => ConfigDirective::Ca{file: file},
-            "cert" => ConfigDirective::Cert{file: file},
-            "extra-certs" => ConfigDirective::ExtraCerts{file: file},
-            "dh" => ConfigDirective::Dh{file: file},
-            "key" => ConfigDirective::Key{file: file},
-            "pkcs12" => ConfigDirective::Pkcs12{file: file},
-            "crl-verify" => ConfigDirective::CrlVerify{file: file, direction: None},
-            "http-proxy-user-pass" => ConfigDirective::HttpProxyUserPass{file: file},
-            "tls-auth" => ConfigDirective::TlsAuth{file: file, direction: None},
-            "tls-crypt" => ConfigDirective::TlsCrypt{file: file},
-            "secret" => ConfigDirective::Secret{file: file, direction: None},
-            _ => unreachable!()
+}
+
+/// The directive for an inline file with the given tag and contents. The
+/// options in a `<connection>` block are parsed, the block has the warning
+/// of the first one which doesn't parse.
+fn inline_directive(identifier: &str, contents: String) -> Result<ConfigDirective, ParseWarning> {
+    if identifier == "connection" {
+        return contents.lines().filter_map(parse_config_line).collect::<Result<_, _>>().map(ConfigDirective::Connection);
+    }
+    if identifier == "peer-fingerprint" {
+        let fingerprints = contents.lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| l.to_string())
+            .collect();
+        return Ok(ConfigDirective::PeerFingerprint{fingerprints});
+    }
+    let file = File::InlineFileContents(contents);
+    Ok(match identifier {
+        "ca" => ConfigDirective::Ca{file},
+        "cert" => ConfigDirective::Cert{file},
+        "extra-certs" => ConfigDirective::ExtraCerts{file},
+        "dh" => ConfigDirective::Dh{file},
+        "key" => ConfigDirective::Key{file},
+        "pkcs12" => ConfigDirective::Pkcs12{file},
+        "crl-verify" => ConfigDirective::CrlVerify{file, flag: None},
+        "http-proxy-user-pass" => ConfigDirective::HttpProxyUserPass{file},
+        "tls-auth" => ConfigDirective::TlsAuth{file, direction: None},
+        "tls-crypt" => ConfigDirective::TlsCrypt{file},
+        "tls-crypt-v2" => ConfigDirective::TlsCryptV2{file, cookie_mode: None},
+        "secret" => ConfigDirective::Secret{file, direction: None},
+        "auth-user-pass" => ConfigDirective::AuthUserPass{up: Some(file)},
+        _ => unreachable!()
+    })
+}
+
+/// The name of the tag at the start of `line`, which starts with `open`,
+/// e.g `ca` for `<ca>` when `open` is `<`. The name is everything up to the
+/// last `>` before any whitespace, anything after the tag is ignored.
+fn tag_name<'a>(line: &'a str, open: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(open)?;
+    let tag = rest.split(char::is_whitespace).next().unwrap_or("");
+    match tag.rfind('>') {
+        Some(end) if end > 0 => Some(&tag[..end]),
+        _ => None,
+    }
+}
+
+/// Whether a line is a comment, which starts with `#` or `;`
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with(['#', ';'])
+}
+
+/// Split a line into it's command and arguments. Arguments are separated by
+/// whitespace unless they are enclosed in double or single quotes, within
+/// double quotes or outside of quotes a backslash escapes the following
+/// character. As in openvpn a `#` or `;` at the start of an argument, outside
+/// of quotes, starts a comment which runs to the end of the line, elsewhere
+/// they're part of the argument. Arguments without quotes or escapes, which
+/// is most of them, borrow from `line` rather than being copied.
+fn split_args(line: &str) -> Vec<Cow<'_, str>> {
+    split_args_and_comment(line).0
+}
+
+/// The arguments of a line as `split_args` gives them, and the comment at
+/// the end of the line, from its `#` or `;`, if it has one
+fn split_args_and_comment<'a>(line: &'a str) -> (Vec<Cow<'a, str>>, Option<&'a str>) {
+    let mut args = Vec::new();
+    // Where the current argument starts, and whether it's still a plain
+    // slice of `line`. Once it has a quote or escape it's built up in
+    // `current` instead.
+    let mut start: Option<usize> = None;
+    let mut plain = true;
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = line.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if quote.is_none() && c.is_whitespace() {
+            if let Some(start) = start.take() {
+                args.push(if plain { Cow::Borrowed(&line[start..i]) } else { Cow::Owned(std::mem::take(&mut current)) });
+            }
+            continue;
+        }
+        if start.is_none() && (c == '#' || c == ';') {
+            return (args, Some(line[i..].trim_end()));
+        }
+        let arg_start = *start.get_or_insert_with(|| {
+            plain = true;
+            i
+        });
+        let special = match (quote, c) {
+            (Some(q), c) => q == c || (q == '"' && c == '\\'),
+            (None, c) => c == '"' || c == '\'' || c == '\\',
         };
-        ConfigLine{result: directive, number: self.start_line_no as i32}
+        if special && plain {
+            plain = false;
+            current.push_str(&line[arg_start..i]);
+        }
+        match (quote, c) {
+            (Some(q), c) if q == c => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some((_, escaped)) = chars.next() {
+                    current.push(escaped);
+                }
+            },
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (_, c) => {
+                if !plain {
+                    current.push(c);
+                }
+            },
+        }
     }
+    if let Some(start) = start {
+        args.push(if plain { Cow::Borrowed(&line[start..]) } else { Cow::Owned(current) });
+    }
+    (args, None)
 }
 
 /// The entry point for this library. Pass a `Read` containing the config file
 /// and get back a `ParsedConfigFile`.
 pub fn parse<R>(input: R) -> errors::Result<ParsedConfigFile> where R: Read {
-    let buf_reader = BufReader::new(input);
-    let mut success_lines = Vec::new();
-    let mut warning_lines = Vec::new();
-    let mut inline_file_parse_state: Option<InlineFileParseState> = None;
-    for (line_index, line_result) in buf_reader.lines().enumerate() {
-        let line_no = line_index;
-        let line = line_result.chain_err(|| "Error reading input")?;
+    parse_lines(BufReader::new(input).lines(), &mut HashMap::new(), None, false)
+}
 
-        let mut reset_inline_state = false;
-        if let Some(ref mut parse_state) = inline_file_parse_state {
-            if parse_state.is_completed_by_line(&line) {
-                success_lines.push(parse_state.to_config_line());
-                reset_inline_state = true
-            } else {
-                parse_state.add_line(line.clone());
-                continue;
-            }
+/// Parse a config held in a string, for the tests of each module
+#[cfg(test)]
+pub(crate) fn parse_str(contents: &str) -> ParsedConfigFile {
+    parse(contents.as_bytes()).unwrap()
+}
+
+/// Parse a config file, writing the contents of inline files to the sink in
+/// `sinks` for their option, e.g `pkcs12`, rather than keeping them in
+/// memory. This avoids buffering large inline files, which can be streamed
+/// to disk or a hasher instead. The contents are written exactly as they
+/// would be kept, and the directives of inline files which were written to
+/// a sink have empty contents.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::io::Write;
+/// use ovpnfile::{ConfigDirective, File};
+///
+/// let mut ca = Vec::new();
+/// let mut sinks: HashMap<&str, &mut dyn Write> = HashMap::new();
+/// sinks.insert("ca", &mut ca);
+/// let config = ovpnfile::parse_streaming("<ca>\nline 1\nline 2\n</ca>\n".as_bytes(), &mut sinks).unwrap();
+/// assert_eq!(config.directives(), vec![ConfigDirective::Ca{file: File::InlineFileContents(String::new())}]);
+/// drop(sinks);
+/// assert_eq!(ca, b"line 1\nline 2");
+/// ```
+pub fn parse_streaming<R>(input: R, sinks: &mut HashMap<&str, &mut dyn Write>) -> errors::Result<ParsedConfigFile> where R: Read {
+    parse_lines(BufReader::new(input).lines(), sinks, None, false)
+}
+
+/// Parse a config file which is already in memory, such as a memory mapped
+/// file or a network buffer, without reading it line by line into new
+/// `String`s as `parse` does. The result is the same as `parse`'s.
+pub fn parse_bytes(input: &[u8]) -> errors::Result<ParsedConfigFile> {
+    let text = str::from_utf8(input).chain_err(|| "Error reading input")?;
+    parse_lines(text.lines().map(Ok), &mut HashMap::new(), None, false)
+}
+
+/// A callback which is given each warning as it's found
+type WarningCallback<'a> = dyn FnMut(&ConfigLine<ParseWarning>) + 'a;
+
+/// A configurable parser, for when the `parse` functions' defaults aren't
+/// enough. The result of parsing is the same as `parse`'s.
+///
+/// ```
+/// let mut warnings = Vec::new();
+/// ovpnfile::Parser::new()
+///     .on_warning(|warning| warnings.push(warning.number))
+///     .parse("client\nunknown-option\n".as_bytes())
+///     .unwrap();
+/// assert_eq!(warnings, vec![1]);
+/// ```
+#[derive(Default)]
+pub struct Parser<'a> {
+    on_warning: Option<Box<WarningCallback<'a>>>,
+    recover_unclosed: bool,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new() -> Parser<'a> {
+        Parser::default()
+    }
+
+    /// Call `callback` with each warning as it's found, so that a long
+    /// running import can report them as it goes. The warnings are still
+    /// in the result's `warning_lines`.
+    pub fn on_warning<F>(&mut self, callback: F) -> &mut Parser<'a> where F: FnMut(&ConfigLine<ParseWarning>) + 'a {
+        self.on_warning = Some(Box::new(callback));
+        self
+    }
+
+    /// Recover from inline files which are never closed, rather than
+    /// treating the rest of the config as their contents. An inline file is
+    /// ended when another one opens or at the end of the config, after its
+    /// last `-----END` marker, or before the first line which is a valid
+    /// directive if there isn't one. The lines after that are parsed as
+    /// usual and the inline file gets a `ParseWarning::UnclosedInlineFile`.
+    ///
+    /// ```
+    /// use ovpnfile::{ConfigLine, ParseWarning};
+    ///
+    /// let contents = "<ca>\n-----BEGIN CERTIFICATE-----\n-----END CERTIFICATE-----\nremote vpn.example.com\n";
+    /// let config = ovpnfile::Parser::new().recover_unclosed_inline_files(true).parse(contents.as_bytes()).unwrap();
+    /// assert_eq!(config.success_lines[1].result.openvpn_option_name(), "remote");
+    /// assert_eq!(config.warning_lines, vec![ConfigLine{number: 0, result: ParseWarning::UnclosedInlineFile}]);
+    /// ```
+    pub fn recover_unclosed_inline_files(&mut self, recover: bool) -> &mut Parser<'a> {
+        self.recover_unclosed = recover;
+        self
+    }
+
+    /// Parse a config file as `parse` does
+    pub fn parse<R>(&mut self, input: R) -> errors::Result<ParsedConfigFile> where R: Read {
+        parse_lines(BufReader::new(input).lines(), &mut HashMap::new(), self.on_warning.as_deref_mut(), self.recover_unclosed)
+    }
+
+    /// Parse a config file which is already in memory as `parse_bytes` does
+    pub fn parse_bytes(&mut self, input: &[u8]) -> errors::Result<ParsedConfigFile> {
+        let text = str::from_utf8(input).chain_err(|| "Error reading input")?;
+        parse_lines(text.lines().map(Ok), &mut HashMap::new(), self.on_warning.as_deref_mut(), self.recover_unclosed)
+    }
+}
+
+/// The result of a line outside of an inline file, `None` for comments and
+/// blank lines. Lines which are too long are still parsed, the warning for
+/// them is reported separately.
+fn parse_config_line(line: &str) -> Option<Result<ConfigDirective, ParseWarning>> {
+    if is_comment(line) || line.trim().is_empty() {
+        return None;
+    }
+    let command_and_args = split_args(line);
+    let (command, args) = command_and_args.split_first()?;
+    let args: Vec<&str> = args.iter().map(|a| a.as_ref()).collect();
+    Some(config_directive::parse_command(command, &args))
+}
+
+/// How many of the lines of an inline file which was never closed belong to
+/// it: those up to the last `-----END` marker if there is one, otherwise
+/// those before the first line which is a valid directive
+fn unclosed_inline_file_len(lines: &[String]) -> usize {
+    if let Some(end) = lines.iter().rposition(|l| l.trim().starts_with("-----END")) {
+        return end + 1;
+    }
+    lines.iter().position(|l| matches!(parse_config_line(l), Some(Ok(_)))).unwrap_or(lines.len())
+}
+
+/// The lines and warnings parsed so far
+struct ParseResults<'c, 'w: 'c> {
+    success_lines: Vec<ConfigLine<ConfigDirective>>,
+    warning_lines: Vec<ConfigLine<ParseWarning>>,
+    on_warning: Option<&'c mut WarningCallback<'w>>,
+}
+
+impl<'c, 'w> ParseResults<'c, 'w> {
+    fn warn(&mut self, number: i32, result: ParseWarning) {
+        let warning = ConfigLine{number, result};
+        if let Some(ref mut on_warning) = self.on_warning {
+            on_warning(&warning);
         }
-        if reset_inline_state {
-            inline_file_parse_state = None;
-            continue;
+        self.warning_lines.push(warning);
+    }
+
+    fn add(&mut self, number: i32, result: Result<ConfigDirective, ParseWarning>) {
+        match result {
+            Ok(result) => self.success_lines.push(ConfigLine{number, result}),
+            Err(warning) => self.warn(number, warning),
         }
+    }
 
-        if let Some(captures) = INLINE_START_REGEX.captures(&line) {
-            let option = &captures[1];
-            if INLINE_FILE_OPTIONS.contains(option) {
-                inline_file_parse_state = Some(InlineFileParseState::new(line_no, option.to_string()));
-                continue;
+    /// End an inline file which was never closed where its contents seem to
+    /// end, and parse the lines after that as ordinary lines
+    fn recover_unclosed(&mut self, mut parse_state: InlineFileParseState) {
+        let len = unclosed_inline_file_len(&parse_state.lines);
+        let swallowed = parse_state.lines.split_off(len);
+        let line = parse_state.to_config_line();
+        self.add(line.number, line.result);
+        self.warn(parse_state.start_line_no, ParseWarning::UnclosedInlineFile);
+        let first_swallowed = parse_state.start_line_no + 1 + (parse_state.streamed_lines + len) as i32;
+        for (index, line) in swallowed.iter().enumerate() {
+            if let Some(result) = parse_config_line(line) {
+                self.add(first_swallowed + index as i32, result);
             }
         }
+    }
+}
 
-        if line.trim().starts_with('#') || line.trim().is_empty() {
-            continue
+fn parse_lines<I, S>(
+    lines: I,
+    sinks: &mut HashMap<&str, &mut dyn Write>,
+    on_warning: Option<&mut WarningCallback>,
+    recover_unclosed: bool,
+) -> errors::Result<ParsedConfigFile>
+    where I: Iterator<Item = io::Result<S>>, S: AsRef<str>
+{
+    let mut results = ParseResults{success_lines: Vec::new(), warning_lines: Vec::new(), on_warning};
+    let mut scanner = Scanner::new(recover_unclosed);
+    let mut inline_file_parse_state: Option<InlineFileParseState> = None;
+    for line_result in lines {
+        let line = line_result.chain_err(|| "Error reading input")?;
+        let line = line.as_ref();
+        let scanned = scanner.scan(line);
+        if scanned.too_long {
+            results.warn(scanned.number, ParseWarning::LineTooLong);
         }
-
-        let line_without_comments = COMMENT_REGEX.replace(&line, "");
-        let command_and_args: Vec<&str> = line_without_comments.split_whitespace().collect();
-        let command = command_and_args[0];
-        let args = &command_and_args[1..];
-        match config_directive::parse_line(command, args) {
-            config_directive::LineParseResult::NoMatchingCommand => {
-                warning_lines.push(ConfigLine{number: line_no as i32, result: ParseWarning::NoMatchingCommand})
+        match scanned.kind {
+            LineKind::InlineOpen(option) => {
+                // Another inline file opening before this one was closed
+                if let Some(parse_state) = inline_file_parse_state.take() {
+                    results.recover_unclosed(parse_state);
+                }
+                inline_file_parse_state = Some(InlineFileParseState::new(scanned.number, option.to_string()));
             },
-            config_directive::LineParseResult::NotEnoughArguments => {
-                warning_lines.push(ConfigLine{number: line_no as i32, result: ParseWarning::NotEnoughArguments})
+            LineKind::InlineContents => {
+                if let Some(ref mut parse_state) = inline_file_parse_state {
+                    match sinks.get_mut(&parse_state.identifier[..]) {
+                        Some(sink) => {
+                            parse_state.stream_line(line, *sink)
+                                .chain_err(|| format!("Error writing the contents of {}", parse_state.identifier))?;
+                        },
+                        None => parse_state.add_line(line),
+                    }
+                }
             },
-            config_directive::LineParseResult::Success(directive) => {
-                success_lines.push(ConfigLine{ number: line_no as i32, result: directive })
-            }
+            LineKind::InlineClose(..) => {
+                if let Some(parse_state) = inline_file_parse_state.take() {
+                    let line = parse_state.to_config_line();
+                    results.add(line.number, line.result);
+                }
+            },
+            LineKind::Option => {
+                if let Some(result) = parse_config_line(line) {
+                    results.add(scanned.number, result);
+                }
+            },
+            LineKind::Comment | LineKind::Blank => {},
+        }
+    }
+    if let Some(parse_state) = inline_file_parse_state {
+        if recover_unclosed {
+            results.recover_unclosed(parse_state);
         }
     }
+    // Recovered lines and `<connection>` blocks are parsed after the
+    // warnings for long lines among them were found
+    results.warning_lines.sort_by_key(|l| l.number);
     Ok(ParsedConfigFile{
-        success_lines: success_lines,
-        warning_lines: warning_lines,
+        success_lines: results.success_lines,
+        warning_lines: results.warning_lines,
     })
 }
 
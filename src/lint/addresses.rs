@@ -0,0 +1,167 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use config_directive::is_valid_netmask;
+use {ConfigDirective, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports address arguments which aren't valid addresses, netmasks which
+/// aren't contiguous, networks with host bits set outside their netmask and
+/// address pools which start after they end.
+pub struct Addresses;
+
+/// The checks for a single line, which collects the problems found
+struct LineCheck {
+    problems: Vec<String>,
+}
+
+impl LineCheck {
+    fn ipv4(&mut self, address: &str) -> Option<Ipv4Addr> {
+        let parsed = address.parse().ok();
+        if parsed.is_none() {
+            self.problems.push(format!("invalid IPv4 address {}", address));
+        }
+        parsed
+    }
+
+    fn netmask(&mut self, netmask: &str) -> Option<Ipv4Addr> {
+        match netmask.parse() {
+            Ok(parsed) if is_valid_netmask(parsed) => Some(parsed),
+            _ => {
+                self.problems.push(format!("invalid netmask {}", netmask));
+                None
+            },
+        }
+    }
+
+    /// Check a network and netmask, the network being a hostname is allowed
+    /// if `allow_hostname` is true
+    fn network(&mut self, network: &str, netmask: Option<&String>, allow_hostname: bool) {
+        let parsed_network = match network.parse::<Ipv4Addr>() {
+            Ok(parsed) => Some(parsed),
+            Err(_) if allow_hostname => None,
+            Err(_) => {
+                self.problems.push(format!("invalid IPv4 address {}", network));
+                None
+            },
+        };
+        let parsed_netmask = netmask.and_then(|n| self.netmask(n));
+        if let (Some(network), Some(netmask)) = (parsed_network, parsed_netmask) {
+            if u32::from(network) & !u32::from(netmask) != 0 {
+                self.problems.push(format!("network {} is inconsistent with netmask {}", network, netmask));
+            }
+        }
+    }
+
+    fn ipv6(&mut self, address: &str) {
+        if address.parse::<Ipv6Addr>().is_err() {
+            self.problems.push(format!("invalid IPv6 address {}", address));
+        }
+    }
+
+    /// Check an IPv6 address with an optional `/bits` prefix length
+    fn ipv6_prefix(&mut self, prefix: &str) {
+        let mut parts = prefix.splitn(2, '/');
+        let address = parts.next().unwrap_or("");
+        let valid_bits = parts.next().is_none_or(|b| b.parse::<u8>().ok().is_some_and(|b| b <= 128));
+        if address.parse::<Ipv6Addr>().is_err() || !valid_bits {
+            self.problems.push(format!("invalid IPv6 prefix {}", prefix));
+        }
+    }
+}
+
+impl Rule for Addresses {
+    fn name(&self) -> &'static str {
+        "addresses"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        for line in config.success_lines.iter() {
+            let mut check = LineCheck{problems: Vec::new()};
+            match line.result {
+                ConfigDirective::Server{ref network, ref netmask, ..} => {
+                    check.network(network, Some(netmask), false);
+                },
+                ConfigDirective::Ifconfig{ref l, ref rn} => {
+                    check.ipv4(l);
+                    check.ipv4(rn);
+                },
+                ConfigDirective::IfconfigPool{ref start_ip, ref end_ip, ref netmask} => {
+                    let start = check.ipv4(start_ip);
+                    let end = check.ipv4(end_ip);
+                    if let Some(ref netmask) = *netmask {
+                        check.netmask(netmask);
+                    }
+                    if let (Some(start), Some(end)) = (start, end) {
+                        if start > end {
+                            check.problems.push(format!("pool start {} is after the end {}", start, end));
+                        }
+                    }
+                },
+                ConfigDirective::IfconfigPush{ref local, ref remote_netmask, ..} => {
+                    check.ipv4(local);
+                    check.ipv4(remote_netmask);
+                },
+                ConfigDirective::Route{ref network_or_ip, ref netmask, ..} => {
+                    check.network(network_or_ip, netmask.as_ref(), true);
+                },
+                ConfigDirective::Iroute{ref network, ref netmask} => {
+                    check.network(network, netmask.as_ref(), false);
+                },
+                ConfigDirective::RouteGateway{ref gw_or_dhcp} if gw_or_dhcp != "dhcp" => {
+                    check.ipv4(gw_or_dhcp);
+                },
+                ConfigDirective::ClientNat{ref network, ref netmask, ref alias, ..} => {
+                    check.network(network, Some(netmask), false);
+                    check.ipv4(alias);
+                },
+                ConfigDirective::IfconfigIpv6{ref ipv6addr, ref ipv6remote} |
+                ConfigDirective::IfconfigIpv6Push{ref ipv6addr, ref ipv6remote} => {
+                    check.ipv6_prefix(ipv6addr);
+                    check.ipv6(ipv6remote);
+                },
+                ConfigDirective::RouteIpv6{ref ipv6addr, ref gateway, ..} => {
+                    check.ipv6_prefix(ipv6addr);
+                    if let Some(ref gateway) = *gateway {
+                        check.ipv6(gateway);
+                    }
+                },
+                ConfigDirective::ServerIpv6{ref ipv6addr} |
+                ConfigDirective::IfconfigIpv6Pool{ref ipv6addr} |
+                ConfigDirective::IrouteIpv6{ref ipv6addr} => {
+                    check.ipv6_prefix(ipv6addr);
+                },
+                _ => {},
+            }
+            result.extend(check.problems.into_iter().map(|p| Diagnostic::new(self.name(), Severity::Error, p, vec![line.number])));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<String> {
+        let config = parse_str(contents);
+        Addresses.check(&config).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_valid_addresses() {
+        let contents = "server 10.8.0.0 255.255.255.0\nroute vpn.example.com 255.255.255.255 net_gateway\n\
+                        ifconfig-pool 10.8.0.2 10.8.0.100\nroute-gateway dhcp\nserver-ipv6 fd00::/64\n\
+                        route-ipv6 2000::/3 fd00::1\n";
+        assert_eq!(check(contents), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        assert_eq!(check("server 10.8.0.1 255.255.255.0\n"), vec!["network 10.8.0.1 is inconsistent with netmask 255.255.255.0"]);
+        assert_eq!(check("server 10.8.0.0 255.0.255.0\n"), vec!["invalid netmask 255.0.255.0"]);
+        assert_eq!(check("ifconfig-pool 10.8.0.100 10.8.0.2\n"), vec!["pool start 10.8.0.100 is after the end 10.8.0.2"]);
+        assert_eq!(check("ifconfig 10.8.0.1 somehost\n"), vec!["invalid IPv4 address somehost"]);
+        assert_eq!(check("server-ipv6 fd00::/129\n"), vec!["invalid IPv6 prefix fd00::/129"]);
+    }
+}
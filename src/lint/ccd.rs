@@ -0,0 +1,51 @@
+use ParsedConfigFile;
+use super::{Diagnostic, Linter, PushedOptions, Rule, Severity};
+
+/// Reports options in a `client-config-dir` file which openvpn doesn't
+/// allow there. Only a small set of options, such as `iroute`,
+/// `ifconfig-push` and `push`, can be set for an individual client.
+pub struct CcdFragment;
+
+impl Rule for CcdFragment {
+    fn name(&self) -> &'static str {
+        "ccd-fragment"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        config.success_lines.iter().filter(|l| !l.result.is_ccd_option()).map(|line| Diagnostic::new(
+            self.name(),
+            Severity::Error,
+            format!("{} can't be used in a client-config-dir file", line.result.openvpn_option_name()),
+            vec![line.number],
+        )).collect()
+    }
+}
+
+/// Check a `client-config-dir` file, reporting options which aren't allowed
+/// there and pushed options clients won't accept
+pub fn validate_ccd_fragment(config: &ParsedConfigFile) -> Vec<Diagnostic> {
+    let mut linter = Linter::empty();
+    linter.add_rule(CcdFragment).add_rule(PushedOptions);
+    linter.lint(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn validate(contents: &str) -> Vec<String> {
+        let config = parse_str(contents);
+        validate_ccd_fragment(&config).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_ccd_fragment() {
+        assert_eq!(validate("ifconfig-push 10.8.0.5 255.255.255.0\niroute 192.168.1.0 255.255.255.0\n\
+                             push \"route 192.168.2.0 255.255.255.0\"\npush-reset\n"), Vec::<String>::new());
+        assert_eq!(validate("iroute 192.168.1.0 255.255.255.0\nroute 192.168.1.0 255.255.255.0\npush \"tls-auth ta.key\"\n"), vec![
+            "route can't be used in a client-config-dir file",
+            "tls-auth can't be pushed to clients",
+        ]);
+    }
+}
@@ -0,0 +1,156 @@
+use {ConfigDirective, CrlVerifyFlag, File, Mode, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity, option_lines};
+
+/// Options which enable TLS mode, which can't be used with `--secret`
+const TLS_OPTIONS: &[&str] = &[
+    "tls-client", "tls-server", "client", "tls-auth", "tls-crypt", "tls-crypt-v2",
+    "ca", "cert", "key", "pkcs12", "peer-fingerprint",
+];
+
+/// Reports combinations of options which openvpn rejects or which make no
+/// sense together
+pub struct ConflictingOptions;
+
+impl ConflictingOptions {
+    fn conflict(&self, severity: Severity, message: &str, mut lines: Vec<i32>) -> Diagnostic {
+        lines.sort();
+        Diagnostic::new(self.name(), severity, message, lines)
+    }
+}
+
+/// Whether a `--proto` argument is one of the tcp protocols
+fn is_tcp(proto: &str) -> bool {
+    proto.starts_with("tcp")
+}
+
+impl Rule for ConflictingOptions {
+    fn name(&self) -> &'static str {
+        "conflicting-options"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+
+        let tls_auth = option_lines(config, &["tls-auth"]);
+        let tls_crypt = option_lines(config, &["tls-crypt", "tls-crypt-v2"]);
+        if !tls_auth.is_empty() && !tls_crypt.is_empty() {
+            result.push(self.conflict(
+                Severity::Error,
+                "tls-auth can't be used together with tls-crypt or tls-crypt-v2",
+                [tls_auth, tls_crypt].concat(),
+            ));
+        }
+
+        let secret = option_lines(config, &["secret"]);
+        let tls = option_lines(config, TLS_OPTIONS);
+        if !secret.is_empty() && !tls.is_empty() {
+            result.push(self.conflict(
+                Severity::Error,
+                "secret selects static key mode, which can't be used with TLS options",
+                [secret, tls].concat(),
+            ));
+        }
+
+        let comp_lzo = option_lines(config, &["comp-lzo"]);
+        let compress = option_lines(config, &["compress"]);
+        if !comp_lzo.is_empty() && !compress.is_empty() {
+            result.push(self.conflict(
+                Severity::Warning,
+                "comp-lzo and compress both set the compression algorithm, only the last one will be used",
+                [comp_lzo, compress].concat(),
+            ));
+        }
+
+        let client = option_lines(config, &["client"]);
+        let server_mode: Vec<i32> = config.success_lines.iter().filter(|l| matches!(
+            l.result,
+            ConfigDirective::Mode{m: Mode::Server} | ConfigDirective::Server{..} | ConfigDirective::ServerBridge(_)
+        )).map(|l| l.number).collect();
+        if !client.is_empty() && !server_mode.is_empty() {
+            result.push(self.conflict(
+                Severity::Error,
+                "client can't be used in server mode",
+                [client, server_mode].concat(),
+            ));
+        }
+
+        let fragment = option_lines(config, &["fragment"]);
+        let tcp: Vec<i32> = config.success_lines.iter().filter(|l| match l.result {
+            ConfigDirective::Proto{ref p} => is_tcp(p),
+            ConfigDirective::Remote{proto: Some(ref p), ..} => is_tcp(p),
+            _ => false,
+        }).map(|l| l.number).collect();
+        if !fragment.is_empty() && !tcp.is_empty() {
+            result.push(self.conflict(
+                Severity::Error,
+                "fragment can only be used with udp",
+                [fragment, tcp].concat(),
+            ));
+        }
+
+        // An inline block can't have the flag, but a directive built in code
+        // can
+        let inline_crl_dir: Vec<i32> = config.success_lines.iter().filter(|l| matches!(
+            l.result,
+            ConfigDirective::CrlVerify{file: File::InlineFileContents(_), flag: Some(CrlVerifyFlag::Dir)}
+        )).map(|l| l.number).collect();
+        if !inline_crl_dir.is_empty() {
+            result.push(self.conflict(
+                Severity::Error,
+                "crl-verify dir needs a directory, it can't be used with inline contents",
+                inline_crl_dir,
+            ));
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        ConflictingOptions.check(&config)
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let diagnostics = check("client\nremote somehost\ntls-auth ta.key 1\ntls-crypt tc.key\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].lines, vec![2, 3]);
+
+        let diagnostics = check("secret static.key\nca ca.crt\ncomp-lzo\ncompress lz4\n");
+        assert_eq!(diagnostics.iter().map(|d| d.lines.clone()).collect::<Vec<_>>(), vec![
+            vec![0, 1],
+            vec![2, 3],
+        ]);
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+
+        assert_eq!(check("client\nmode server\n")[0].lines, vec![0, 1]);
+        assert_eq!(check("fragment 1300\nremote somehost 1194 tcp-client\n")[0].lines, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_inline_crl_verify_dir() {
+        let mut config = parse_str("client\ncrl-verify crls dir\n");
+        assert_eq!(ConflictingOptions.check(&config), vec![]);
+        config.success_lines[1].result = ConfigDirective::CrlVerify{
+            file: File::InlineFileContents("crl".to_string()),
+            flag: Some(CrlVerifyFlag::Dir),
+        };
+        let diagnostics = ConflictingOptions.check(&config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].lines, vec![1]);
+    }
+
+    #[test]
+    fn test_no_conflicts() {
+        assert_eq!(check("client\nremote somehost 1194 udp\ntls-crypt tc.key\ncompress lz4\nfragment 1300\n"), vec![]);
+        assert_eq!(check("secret static.key\nremote somehost\n"), vec![]);
+    }
+}
@@ -0,0 +1,77 @@
+use {ConfigDirective, File, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity, option_lines};
+
+/// Directories which are usually readable (and often writable) by every
+/// user on the machine
+const SHARED_DIRECTORIES: &[&str] = &[
+    "/tmp/", "/var/tmp/", "/dev/shm/", "/users/public/", "c:/users/public/", "c:/windows/temp/",
+];
+
+fn looks_world_readable(path: &str) -> bool {
+    let path = path.replace('\\', "/").to_lowercase();
+    SHARED_DIRECTORIES.iter().any(|d| path.starts_with(d)) || path.contains("/public/")
+}
+
+/// Reports credentials read from a file without `auth-nocache`, which
+/// leaves them cached in memory, and credentials files in directories
+/// other users can read.
+pub struct CredentialCaching;
+
+impl Rule for CredentialCaching {
+    fn name(&self) -> &'static str {
+        "credential-caching"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        let mut credential_files = Vec::new();
+        for line in config.success_lines.iter() {
+            let path = match line.result {
+                ConfigDirective::AuthUserPass{up: Some(File::FilePath(ref path))} |
+                ConfigDirective::Askpass{file: Some(ref path)} => path,
+                _ => continue,
+            };
+            credential_files.push(line.number);
+            if looks_world_readable(path) {
+                result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Warning,
+                    format!("credentials file {} is in a directory other users can read", path),
+                    vec![line.number],
+                ));
+            }
+        }
+        if !credential_files.is_empty() && option_lines(config, &["auth-nocache"]).is_empty() {
+            result.insert(0, Diagnostic::new(
+                self.name(),
+                Severity::Info,
+                "credentials are read from a file without auth-nocache, so they will be cached in memory",
+                credential_files,
+            ));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        CredentialCaching.check(&config)
+    }
+
+    #[test]
+    fn test_credential_caching() {
+        assert_eq!(check("auth-user-pass\naskpass\n"), vec![]);
+        assert_eq!(check("auth-user-pass creds.txt\nauth-nocache\n"), vec![]);
+        assert_eq!(check("<auth-user-pass>\nuser\npass\n</auth-user-pass>\n"), vec![]);
+        let diagnostics = check("client\nauth-user-pass /tmp/creds.txt\naskpass C:\\\\Users\\\\Public\\\\pass.txt\n");
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].lines, vec![1, 2]);
+        assert_eq!(diagnostics[1].message, "credentials file /tmp/creds.txt is in a directory other users can read");
+        assert_eq!(diagnostics[2].lines, vec![2]);
+    }
+}
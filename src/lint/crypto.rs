@@ -0,0 +1,113 @@
+use {ConfigDirective, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity, option_lines};
+
+/// Prefixes of ciphers with a 64 bit block size, which are vulnerable to
+/// SWEET32, or which are otherwise broken
+const WEAK_CIPHER_PREFIXES: &[&str] = &["BF-", "DES-", "DESX-", "DES-EDE", "CAST5-", "RC2-", "IDEA-", "RC4"];
+
+fn is_weak_cipher(cipher: &str) -> bool {
+    let cipher = cipher.to_uppercase();
+    cipher == "NONE" || WEAK_CIPHER_PREFIXES.iter().any(|p| cipher.starts_with(p))
+}
+
+/// Reports weak ciphers and digests, options which disable security
+/// features and compression, which makes the tunnel vulnerable to VORACLE
+/// style attacks.
+pub struct WeakCrypto;
+
+impl WeakCrypto {
+    fn finding<M: Into<String>>(&self, severity: Severity, message: M, line: i32) -> Diagnostic {
+        Diagnostic::new(self.name(), severity, message, vec![line])
+    }
+}
+
+impl Rule for WeakCrypto {
+    fn name(&self) -> &'static str {
+        "weak-crypto"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        for line in config.success_lines.iter() {
+            let n = line.number;
+            match line.result {
+                ConfigDirective::Cipher{ref alg} | ConfigDirective::DataCiphersFallback{ref alg} if is_weak_cipher(alg) => {
+                    result.push(self.finding(Severity::Warning, format!("weak cipher {}", alg), n));
+                },
+                ConfigDirective::NcpCiphers{ref cipher_list} | ConfigDirective::DataCiphers{ref cipher_list} => {
+                    for cipher in cipher_list.split(':').filter(|c| is_weak_cipher(c)) {
+                        result.push(self.finding(Severity::Warning, format!("weak cipher {}", cipher), n));
+                    }
+                },
+                ConfigDirective::Auth{ref alg} => {
+                    match alg.to_uppercase().as_ref() {
+                        "NONE" => result.push(self.finding(Severity::Warning, "auth none disables packet authentication", n)),
+                        "MD5" | "RSA-MD5" => result.push(self.finding(Severity::Warning, format!("weak digest {}", alg), n)),
+                        "SHA1" | "SHA" | "RSA-SHA1" => result.push(self.finding(Severity::Info, format!("weak digest {}", alg), n)),
+                        _ => {},
+                    }
+                },
+                ConfigDirective::Keysize{..} => {
+                    result.push(self.finding(Severity::Info, "keysize is deprecated, use a cipher with the key size required", n));
+                },
+                ConfigDirective::NoReplay => {
+                    result.push(self.finding(Severity::Warning, "no-replay disables replay protection", n));
+                },
+                ConfigDirective::NoIv => {
+                    result.push(self.finding(Severity::Warning, "no-iv disables the cipher IV", n));
+                },
+                ConfigDirective::Prng{..} => {
+                    result.push(self.finding(Severity::Info, "prng is deprecated, remove it to use the default", n));
+                },
+                ConfigDirective::RenegSec{ref n} if n == "0" => {
+                    result.push(self.finding(Severity::Warning, "reneg-sec 0 disables key renegotiation", line.number));
+                },
+                ConfigDirective::CompLzo{ref mode} if mode.as_ref().is_none_or(|m| m != "no") => {
+                    result.push(self.finding(Severity::Warning, "compression is enabled, which can leak information", n));
+                },
+                ConfigDirective::Compress{algorithm: Some(ref algorithm)} if !algorithm.starts_with("stub") => {
+                    result.push(self.finding(Severity::Warning, "compression is enabled, which can leak information", n));
+                },
+                ConfigDirective::AllowCompression{ref mode} if mode == "yes" => {
+                    result.push(self.finding(Severity::Warning, "allow-compression yes allows compression, which can leak information", n));
+                },
+                _ => {},
+            }
+        }
+        let static_key = !option_lines(config, &["secret"]).is_empty();
+        if !static_key && option_lines(config, &["tls-version-min"]).is_empty() {
+            result.push(Diagnostic::new(
+                self.name(),
+                Severity::Info,
+                "no tls-version-min, add `tls-version-min 1.2` to prevent the use of old TLS versions",
+                Vec::new(),
+            ));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<(Severity, Vec<i32>)> {
+        let config = parse_str(contents);
+        WeakCrypto.check(&config).into_iter().map(|d| (d.severity, d.lines)).collect()
+    }
+
+    #[test]
+    fn test_weak_crypto() {
+        assert_eq!(check("tls-version-min 1.2\ncipher AES-256-GCM\nauth SHA256\ncompress stub-v2\ncomp-lzo no\n"), vec![]);
+        assert_eq!(check("tls-version-min 1.2\ncipher BF-CBC\nauth SHA1\nreneg-sec 0\ncomp-lzo\n"), vec![
+            (Severity::Warning, vec![1]),
+            (Severity::Info, vec![2]),
+            (Severity::Warning, vec![3]),
+            (Severity::Warning, vec![4]),
+        ]);
+        assert_eq!(check("tls-version-min 1.2\ndata-ciphers AES-256-GCM:DES-EDE3-CBC:bf-cbc\n").len(), 2);
+        assert_eq!(check("cipher AES-256-GCM\n"), vec![(Severity::Info, vec![])]);
+        assert_eq!(check("secret static.key\n"), vec![]);
+    }
+}
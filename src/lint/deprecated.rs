@@ -0,0 +1,70 @@
+use {ParsedConfigFile, Version};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports options which are deprecated or removed in a target version of
+/// openvpn, along with the option to use instead where there is one.
+/// Removed options are errors as openvpn will refuse to start.
+pub struct DeprecatedOptions {
+    target: Version,
+}
+
+impl DeprecatedOptions {
+    pub fn new(target: Version) -> DeprecatedOptions {
+        DeprecatedOptions{target}
+    }
+}
+
+impl Rule for DeprecatedOptions {
+    fn name(&self) -> &'static str {
+        "deprecated-options"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        config.success_lines.iter().filter_map(|line| {
+            let versions = line.result.versions();
+            let option = line.result.openvpn_option_name();
+            let (severity, mut message) = match (versions.deprecated, versions.removed) {
+                (_, Some(removed)) if removed <= self.target => {
+                    (Severity::Error, format!("{} was removed in openvpn {}", option, removed))
+                },
+                (Some(deprecated), _) if deprecated <= self.target => {
+                    (Severity::Warning, format!("{} is deprecated since openvpn {}", option, deprecated))
+                },
+                _ => return None,
+            };
+            if let Some(replacement) = versions.replaced_by {
+                message.push_str(&format!(", use {} instead", replacement));
+            }
+            Some(Diagnostic::new(self.name(), severity, message, vec![line.number]))
+        }).collect()
+    }
+}
+
+/// Report the options in a config which are deprecated or removed in the
+/// `target` version of openvpn
+pub fn deprecated_options(config: &ParsedConfigFile, target: Version) -> Vec<Diagnostic> {
+    DeprecatedOptions::new(target).check(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str, target: Version) -> Vec<(Severity, String)> {
+        let config = parse_str(contents);
+        deprecated_options(&config, target).into_iter().map(|d| (d.severity, d.message)).collect()
+    }
+
+    #[test]
+    fn test_deprecated_options() {
+        let contents = "client\nns-cert-type server\nkey-method 2\ncomp-lzo\n";
+        assert_eq!(check(contents, Version::new(2, 3)), vec![]);
+        assert_eq!(check(contents, Version::new(2, 4)), vec![
+            (Severity::Warning, "ns-cert-type is deprecated since openvpn 2.4, use remote-cert-tls instead".to_string()),
+            (Severity::Warning, "key-method is deprecated since openvpn 2.4".to_string()),
+            (Severity::Warning, "comp-lzo is deprecated since openvpn 2.4, use compress instead".to_string()),
+        ]);
+        assert_eq!(check(contents, Version::new(2, 5))[1], (Severity::Error, "key-method was removed in openvpn 2.5".to_string()));
+    }
+}
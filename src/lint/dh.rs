@@ -0,0 +1,59 @@
+use crypto::dh_bits;
+use {ConfigDirective, File, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+/// The smallest DH group size which is considered secure
+const MIN_DH_BITS: u32 = 2048;
+
+/// Reports inline `dh` parameters with a group smaller than 2048 bits. Only
+/// available with the `crypto` feature.
+pub struct DhStrength;
+
+impl Rule for DhStrength {
+    fn name(&self) -> &'static str {
+        "dh-strength"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        config.success_lines.iter().filter_map(|line| {
+            let contents = match line.result {
+                ConfigDirective::Dh{file: File::InlineFileContents(ref contents)} => contents,
+                _ => return None,
+            };
+            let (severity, message) = match dh_bits(contents) {
+                Ok(bits) if bits < MIN_DH_BITS => (
+                    Severity::Warning,
+                    format!("{} bit DH parameters are weak, use dh none with ECDH instead", bits),
+                ),
+                Ok(_) => return None,
+                Err(_) => (Severity::Error, "unable to parse the DH parameters".to_string()),
+            };
+            Some(Diagnostic::new(self.name(), severity, message, vec![line.number]))
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    const DH_1024: &str = include_str!("../../tests/certs/dh1024.pem");
+    const DH_2048: &str = include_str!("../../tests/certs/dh2048.pem");
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        DhStrength.check(&config)
+    }
+
+    #[test]
+    fn test_dh_strength() {
+        let diagnostics = check(&format!("dev tun\n<dh>\n{}</dh>\n", DH_1024));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].lines, vec![1]);
+        assert_eq!(diagnostics[0].message, "1024 bit DH parameters are weak, use dh none with ECDH instead");
+        assert_eq!(check(&format!("<dh>\n{}</dh>\n", DH_2048)), vec![]);
+        assert_eq!(check("dh none\ndh dh.pem\n"), vec![]);
+        assert_eq!(check("<dh>\nnot dh parameters\n</dh>\n")[0].severity, Severity::Error);
+    }
+}
@@ -0,0 +1,84 @@
+use {ConfigDirective, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports configs which redirect the default gateway through the tunnel
+/// but can still leak traffic outside it: DNS queries on Windows without
+/// `block-outside-dns`, and IPv6 traffic when only IPv4 is redirected.
+/// Options pushed by a server are checked as well as options in a client
+/// profile.
+pub struct DnsLeaks;
+
+impl Rule for DnsLeaks {
+    fn name(&self) -> &'static str {
+        "dns-leaks"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut redirect = None;
+        let mut block_outside_dns = false;
+        let mut block_ipv6 = false;
+        for line in config.success_lines.iter() {
+            let mut words: Vec<&str> = match line.result {
+                ConfigDirective::Push{ref option} => option.split_whitespace().collect(),
+                _ => vec![line.result.openvpn_option_name()],
+            };
+            if let ConfigDirective::RedirectGateway{ref flags} = line.result {
+                words.extend(flags.iter().map(|f| &f[..]));
+            }
+            match words.first() {
+                Some(&"redirect-gateway") => {
+                    let ipv6 = words.contains(&"ipv6");
+                    redirect = Some((line.number, ipv6));
+                },
+                Some(&"block-outside-dns") => block_outside_dns = true,
+                Some(&"block-ipv6") => block_ipv6 = true,
+                _ => {},
+            }
+        }
+        let mut result = Vec::new();
+        let (line, ipv6) = match redirect {
+            Some(redirect) => redirect,
+            None => return result,
+        };
+        if !block_outside_dns {
+            result.push(Diagnostic::new(
+                self.name(),
+                Severity::Info,
+                "Windows clients may leak DNS queries outside the tunnel, add block-outside-dns",
+                vec![line],
+            ));
+        }
+        if !ipv6 && !block_ipv6 {
+            result.push(Diagnostic::new(
+                self.name(),
+                Severity::Info,
+                "only IPv4 traffic is redirected, add the ipv6 flag to redirect-gateway or block-ipv6",
+                vec![line],
+            ));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<String> {
+        let config = parse_str(contents);
+        DnsLeaks.check(&config).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_dns_leaks() {
+        assert_eq!(check("client\nredirect-private def1\n"), Vec::<String>::new());
+        assert_eq!(check("redirect-gateway def1 ipv6\nblock-outside-dns\n"), Vec::<String>::new());
+        assert_eq!(check("push \"redirect-gateway def1\"\npush \"block-outside-dns\"\nblock-ipv6\n"), Vec::<String>::new());
+        assert_eq!(check("client\nredirect-gateway def1\n"), vec![
+            "Windows clients may leak DNS queries outside the tunnel, add block-outside-dns",
+            "only IPv4 traffic is redirected, add the ipv6 flag to redirect-gateway or block-ipv6",
+        ]);
+        assert_eq!(check("push \"redirect-gateway def1 ipv6\"\n").len(), 1);
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::{Duration, SystemTime};
+use x509::inline_certificates;
+use ParsedConfigFile;
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports inline certificates which have expired, aren't valid yet or will
+/// expire within a window, 30 days by default. Only available with the
+/// `x509` feature.
+pub struct CertificateExpiry {
+    window: Duration,
+    now: Option<SystemTime>,
+}
+
+impl CertificateExpiry {
+    /// Warn about certificates which expire within `window`
+    pub fn new(window: Duration) -> CertificateExpiry {
+        CertificateExpiry{window, now: None}
+    }
+
+    /// Check the certificates at `now` rather than the current time
+    pub fn at(mut self, now: SystemTime) -> CertificateExpiry {
+        self.now = Some(now);
+        self
+    }
+}
+
+impl Default for CertificateExpiry {
+    fn default() -> CertificateExpiry {
+        CertificateExpiry::new(Duration::from_secs(30 * 24 * 60 * 60))
+    }
+}
+
+impl Rule for CertificateExpiry {
+    fn name(&self) -> &'static str {
+        "certificate-expiry"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let now = self.now.unwrap_or_else(SystemTime::now);
+        inline_certificates(config).into_iter().filter_map(|inline| {
+            let certificate = &inline.certificate;
+            let (severity, problem) = if certificate.is_expired(now) {
+                (Severity::Error, "has expired")
+            } else if certificate.is_not_yet_valid(now) {
+                (Severity::Warning, "is not valid yet")
+            } else if certificate.not_after < now + self.window {
+                (Severity::Warning, "expires soon")
+            } else {
+                return None;
+            };
+            Some(Diagnostic::new(
+                self.name(),
+                severity,
+                format!("{} certificate {} {}", inline.option, certificate.subject, problem),
+                vec![inline.line],
+            ))
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    const CERT: &str = include_str!("../../tests/certs/client.crt");
+
+    #[test]
+    fn test_certificate_expiry() {
+        let contents = format!("<cert>\n{}</cert>\n", CERT);
+        let config = parse_str(&contents);
+        let year = Duration::from_secs(365 * 24 * 60 * 60);
+        let now = SystemTime::now();
+        assert_eq!(CertificateExpiry::default().check(&config), vec![]);
+        let rule = CertificateExpiry::new(year * 200).at(now);
+        assert_eq!(rule.check(&config)[0].message, "cert certificate CN=client expires soon");
+        let rule = CertificateExpiry::default().at(now + year * 200);
+        assert_eq!(rule.check(&config)[0].severity, Severity::Error);
+        let rule = CertificateExpiry::default().at(now - year);
+        assert_eq!(rule.check(&config)[0].message, "cert certificate CN=client is not valid yet");
+    }
+}
@@ -0,0 +1,128 @@
+use {ConfigDirective, Ipv4Network, Ipv6Network, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports `iroute` and `iroute-ipv6` networks which overlap the VPN
+/// address pool, or which have no `route` (or `route-ipv6`) sending their
+/// traffic into the tunnel. Either mistake breaks routing to the networks
+/// behind a client.
+pub struct Iroutes;
+
+impl Rule for Iroutes {
+    fn name(&self) -> &'static str {
+        "iroutes"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut pools = Vec::new();
+        let mut pools_ipv6 = Vec::new();
+        let mut routes = Vec::new();
+        let mut routes_ipv6 = Vec::new();
+        let mut iroutes = Vec::new();
+        let mut iroutes_ipv6 = Vec::new();
+        for line in config.success_lines.iter() {
+            let n = line.number;
+            match line.result {
+                ConfigDirective::Server{ref network, ref netmask, ..} => {
+                    if let Some(network) = Ipv4Network::from_route(network, Some(netmask)) {
+                        pools.push((n, network.range()));
+                    }
+                },
+                ConfigDirective::IfconfigPool{ref start_ip, ref end_ip, ..} => {
+                    if let (Ok(start), Ok(end)) = (start_ip.parse(), end_ip.parse()) {
+                        pools.push((n, (start, end)));
+                    }
+                },
+                ConfigDirective::ServerIpv6{ref ipv6addr} | ConfigDirective::IfconfigIpv6Pool{ref ipv6addr} => {
+                    pools_ipv6.extend(ipv6addr.parse::<Ipv6Network>().ok().map(|network| (n, network)));
+                },
+                ConfigDirective::Route{ref network_or_ip, ref netmask, ..} => {
+                    routes.extend(Ipv4Network::from_route(network_or_ip, netmask.as_deref()));
+                },
+                ConfigDirective::RouteIpv6{ref ipv6addr, ..} => {
+                    routes_ipv6.extend(ipv6addr.parse::<Ipv6Network>().ok());
+                },
+                ConfigDirective::Iroute{ref network, ref netmask} => {
+                    iroutes.extend(Ipv4Network::from_route(network, netmask.as_deref()).map(|network| (n, network)));
+                },
+                ConfigDirective::IrouteIpv6{ref ipv6addr} => {
+                    iroutes_ipv6.extend(ipv6addr.parse::<Ipv6Network>().ok().map(|network| (n, network)));
+                },
+                _ => {},
+            }
+        }
+
+        let mut result = Vec::new();
+        for (line, iroute) in iroutes {
+            let (first, last) = iroute.range();
+            for &(pool_line, (start, end)) in pools.iter() {
+                if first <= end && start <= last {
+                    result.push(Diagnostic::new(
+                        self.name(),
+                        Severity::Error,
+                        format!("iroute {} overlaps the VPN address pool", iroute),
+                        vec![pool_line.min(line), pool_line.max(line)],
+                    ));
+                }
+            }
+            if !routes.iter().any(|route| route.contains(&iroute)) {
+                result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Warning,
+                    format!("no route for iroute {}, add `route {} {}`", iroute, iroute.address(), iroute.netmask()),
+                    vec![line],
+                ));
+            }
+        }
+        for (line, iroute) in iroutes_ipv6 {
+            for &(pool_line, pool) in pools_ipv6.iter() {
+                if pool.overlaps(&iroute) {
+                    result.push(Diagnostic::new(
+                        self.name(),
+                        Severity::Error,
+                        format!("iroute-ipv6 {} overlaps the VPN address pool", iroute),
+                        vec![pool_line.min(line), pool_line.max(line)],
+                    ));
+                }
+            }
+            if !routes_ipv6.iter().any(|route| route.contains(&iroute)) {
+                result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Warning,
+                    format!("no route for iroute-ipv6 {}, add `route-ipv6 {}`", iroute, iroute),
+                    vec![line],
+                ));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        Iroutes.check(&config)
+    }
+
+    #[test]
+    fn test_iroutes() {
+        assert_eq!(check("server 10.8.0.0 255.255.255.0\nroute 192.168.0.0 255.255.0.0\niroute 192.168.1.0 255.255.255.0\n"), vec![]);
+        assert_eq!(check("server-ipv6 2001:db8::/64\nroute-ipv6 2001:db9::/48\niroute-ipv6 2001:db9:0:1::/64\n"), vec![]);
+
+        let diagnostics = check("server 10.8.0.0 255.255.255.0\niroute 10.8.0.0 255.255.0.0\n");
+        assert_eq!(diagnostics.iter().map(|d| &d.message[..]).collect::<Vec<_>>(), vec![
+            "iroute 10.8.0.0/16 overlaps the VPN address pool",
+            "no route for iroute 10.8.0.0/16, add `route 10.8.0.0 255.255.0.0`",
+        ]);
+        assert_eq!(diagnostics[0].lines, vec![0, 1]);
+
+        let diagnostics = check("ifconfig-pool 10.8.0.10 10.8.0.20\nroute 10.8.0.16 255.255.255.252\niroute 10.8.0.16 255.255.255.252\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+
+        assert_eq!(check("server-ipv6 2001:db8::/64\niroute-ipv6 2001:db8::/48\n").len(), 2);
+    }
+}
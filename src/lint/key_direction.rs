@@ -0,0 +1,90 @@
+use {ConfigDirective, File, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports `tls-auth` and `secret` key directions which contradict a
+/// `key-direction` option, and inline `tls-auth` keys with no direction. A
+/// key used with no direction on one side and a direction on the other
+/// causes the TLS handshake to fail.
+pub struct KeyDirection;
+
+impl Rule for KeyDirection {
+    fn name(&self) -> &'static str {
+        "key-direction"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        // openvpn uses the last key-direction wherever it appears
+        let key_direction = config.success_lines.iter().rev().find_map(|l| match l.result {
+            ConfigDirective::KeyDirection{ref direction} => Some((l.number, direction)),
+            _ => None,
+        });
+        for line in config.success_lines.iter() {
+            let (option, file, direction) = match line.result {
+                ConfigDirective::KeyDirection{ref direction} => (None, None, Some(direction)),
+                ConfigDirective::TlsAuth{ref file, ref direction} => (Some("tls-auth"), Some(file), direction.as_ref()),
+                ConfigDirective::Secret{ref file, ref direction} => (Some("secret"), Some(file), direction.as_ref()),
+                _ => continue,
+            };
+            if let Some(direction) = direction {
+                if direction != "0" && direction != "1" {
+                    result.push(Diagnostic::new(
+                        self.name(),
+                        Severity::Error,
+                        format!("invalid key direction {}, it must be 0 or 1", direction),
+                        vec![line.number],
+                    ));
+                }
+            }
+            let option = match option {
+                Some(option) => option,
+                None => continue,
+            };
+            match (direction, key_direction) {
+                (Some(direction), Some((key_direction_line, key_direction))) if direction != key_direction => {
+                    let mut lines = vec![key_direction_line, line.number];
+                    lines.sort();
+                    result.push(Diagnostic::new(
+                        self.name(),
+                        Severity::Warning,
+                        format!("{} direction {} contradicts key-direction {}", option, direction, key_direction),
+                        lines,
+                    ));
+                },
+                (None, None) if option == "tls-auth" => {
+                    if let Some(&File::InlineFileContents(_)) = file {
+                        result.push(Diagnostic::new(
+                            self.name(),
+                            Severity::Warning,
+                            "inline tls-auth key without a key-direction, the key will be used in both directions",
+                            vec![line.number],
+                        ));
+                    }
+                },
+                _ => {},
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        KeyDirection.check(&config)
+    }
+
+    #[test]
+    fn test_key_direction() {
+        assert_eq!(check("key-direction 1\n<tls-auth>\nkey\n</tls-auth>\ntls-auth ta.key 1\n"), vec![]);
+        assert_eq!(check("key-direction 1\ntls-auth ta.key 0\n")[0].lines, vec![0, 1]);
+        assert_eq!(check("<tls-auth>\nkey\n</tls-auth>\nkey-direction 1\n"), vec![]);
+        assert_eq!(check("<tls-auth>\nkey\n</tls-auth>\n")[0].lines, vec![0]);
+        assert_eq!(check("tls-auth ta.key\n"), vec![]);
+        assert_eq!(check("secret static.key 2\n")[0].severity, Severity::Error);
+    }
+}
@@ -0,0 +1,97 @@
+use crypto::{cert_chains_to_ca, cert_matches_key};
+use {ConfigDirective, File, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+/// The line number and contents of the last inline file for `option`
+fn inline_file<'a>(config: &'a ParsedConfigFile, option: &str) -> Option<(i32, &'a str)> {
+    config.success_lines.iter().rev().find_map(|l| match l.result {
+        ConfigDirective::Ca{file: File::InlineFileContents(ref contents)} if option == "ca" => Some((l.number, &contents[..])),
+        ConfigDirective::Cert{file: File::InlineFileContents(ref contents)} if option == "cert" => Some((l.number, &contents[..])),
+        ConfigDirective::Key{file: File::InlineFileContents(ref contents)} if option == "key" => Some((l.number, &contents[..])),
+        ConfigDirective::ExtraCerts{file: File::InlineFileContents(ref contents)} if option == "extra-certs" => Some((l.number, &contents[..])),
+        _ => None,
+    })
+}
+
+/// Reports an inline `cert` which doesn't match the inline `key`, or which
+/// isn't issued by the inline `ca` (possibly via the inline `extra-certs`).
+/// Only available with the `crypto` feature.
+pub struct CertificateKeyPair;
+
+impl Rule for CertificateKeyPair {
+    fn name(&self) -> &'static str {
+        "certificate-key-pair"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        let (cert_line, cert) = match inline_file(config, "cert") {
+            Some(cert) => cert,
+            None => return result,
+        };
+        if let Some((key_line, key)) = inline_file(config, "key") {
+            match cert_matches_key(cert, key) {
+                Ok(true) => {},
+                Ok(false) => result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Error,
+                    "the private key doesn't match the certificate",
+                    vec![cert_line, key_line],
+                )),
+                Err(_) => result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Error,
+                    "unable to parse the certificate or private key",
+                    vec![cert_line, key_line],
+                )),
+            }
+        }
+        if let Some((ca_line, ca)) = inline_file(config, "ca") {
+            let extra_certs = inline_file(config, "extra-certs").map(|(_, contents)| contents);
+            match cert_chains_to_ca(cert, ca, extra_certs) {
+                Ok(true) => {},
+                Ok(false) => result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Error,
+                    "the certificate isn't issued by the CA",
+                    vec![ca_line, cert_line],
+                )),
+                Err(_) => result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Error,
+                    "unable to parse the certificate or CA",
+                    vec![ca_line, cert_line],
+                )),
+            }
+        }
+        result.iter_mut().for_each(|d| d.lines.sort());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    const CA: &str = include_str!("../../tests/certs/ca.crt");
+    const OTHER_CA: &str = include_str!("../../tests/certs/other-ca.crt");
+    const CERT: &str = include_str!("../../tests/certs/client.crt");
+    const KEY: &str = include_str!("../../tests/certs/client.key");
+    const OTHER_KEY: &str = include_str!("../../tests/certs/other.key");
+
+    fn check(ca: &str, key: &str) -> Vec<String> {
+        let contents = format!("<ca>\n{}</ca>\n<cert>\n{}</cert>\n<key>\n{}</key>\n", ca, CERT, key);
+        let config = parse_str(&contents);
+        CertificateKeyPair.check(&config).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_certificate_key_pair() {
+        assert_eq!(check(CA, KEY), Vec::<String>::new());
+        assert_eq!(check(OTHER_CA, OTHER_KEY), vec![
+            "the private key doesn't match the certificate",
+            "the certificate isn't issued by the CA",
+        ]);
+    }
+}
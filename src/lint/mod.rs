@@ -0,0 +1,296 @@
+//! Checks which can be run on a parsed config file.
+//!
+//! A check is a `Rule`, which looks at a `ParsedConfigFile` and returns a
+//! `Diagnostic` for each problem it finds. The `lint` function runs the
+//! rules built in to this crate, a `Linter` can be used to run additional
+//! rules alongside (or instead of) them.
+//!
+//! Rules which only make sense for a particular kind of config, such as
+//! `ClientProfile`, aren't run by `lint`. They have their own entry points,
+//! e.g `validate_client_profile` or `validate_ccd_fragment` for the files in
+//! a `client-config-dir`. Likewise the security audit rules, which
+//! report settings that are weak rather than wrong, are run by
+//! `security_audit`.
+//!
+//! ```
+//! use std::io::BufReader;
+//! use ovpnfile::lint::{self, Severity};
+//!
+//! let contents = r"
+//! client
+//! remote-random-hostnames
+//! ".as_bytes();
+//!
+//! let config = ovpnfile::parse(BufReader::new(contents)).unwrap();
+//! let diagnostics = lint::lint(&config);
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(diagnostics[0].severity, Severity::Warning);
+//! assert_eq!(diagnostics[0].lines, vec![2]);
+//! ```
+use std::fmt;
+use {ParsedConfigFile, ParseWarning};
+
+mod addresses;
+mod ccd;
+mod conflicts;
+mod credentials;
+mod crypto;
+mod deprecated;
+#[cfg(feature = "crypto")]
+mod dh;
+mod dns_leaks;
+#[cfg(feature = "x509")]
+mod expiry;
+mod iroutes;
+mod key_direction;
+#[cfg(feature = "crypto")]
+mod key_pair;
+mod ports;
+mod pushed;
+mod remotes;
+mod required;
+mod scripts;
+mod static_keys;
+mod topology;
+mod values;
+
+pub use self::addresses::Addresses;
+pub use self::ccd::{CcdFragment, validate_ccd_fragment};
+pub use self::conflicts::ConflictingOptions;
+pub use self::credentials::CredentialCaching;
+pub use self::crypto::WeakCrypto;
+pub use self::deprecated::{DeprecatedOptions, deprecated_options};
+#[cfg(feature = "crypto")]
+pub use self::dh::DhStrength;
+pub use self::dns_leaks::DnsLeaks;
+#[cfg(feature = "x509")]
+pub use self::expiry::CertificateExpiry;
+pub use self::iroutes::Iroutes;
+pub use self::key_direction::KeyDirection;
+#[cfg(feature = "crypto")]
+pub use self::key_pair::CertificateKeyPair;
+pub use self::ports::Ports;
+pub use self::pushed::PushedOptions;
+pub use self::remotes::DuplicateRemotes;
+pub use self::required::{ClientProfile, ServerConfig, validate_client_profile, validate_server_config};
+pub use self::scripts::ScriptSecurity;
+pub use self::static_keys::StaticKeys;
+pub use self::topology::DeviceTopology;
+pub use self::values::ArgumentValues;
+
+/// How serious the problem a diagnostic reports is
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub enum Severity {
+    /// Not a problem, but something the user may want to know about
+    Info,
+    /// Something which is likely a mistake or is insecure
+    Warning,
+    /// Something which will stop openvpn from using the config
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A problem found by a `Rule`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Diagnostic {
+    /// The name of the rule which produced the diagnostic
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// The numbers of the lines involved, counting from 0 as `parse` does,
+    /// empty if the problem is with the config as a whole (e.g a missing
+    /// option). They're displayed counting from 1, as editors do.
+    pub lines: Vec<i32>,
+}
+
+impl Diagnostic {
+    pub fn new<M: Into<String>>(rule: &'static str, severity: Severity, message: M, lines: Vec<i32>) -> Diagnostic {
+        Diagnostic{rule, severity, message: message.into(), lines}
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.lines.is_empty() {
+            let lines: Vec<String> = self.lines.iter().map(|l| (l + 1).to_string()).collect();
+            write!(f, "line {}: ", lines.join(", "))?;
+        }
+        write!(f, "{}: {} [{}]", self.severity, self.message, self.rule)
+    }
+}
+
+/// A check on a parsed config file
+pub trait Rule {
+    /// A short identifier for the rule, e.g `parse-warnings`
+    fn name(&self) -> &'static str;
+    /// Check the config, returning a diagnostic for each problem found
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic>;
+}
+
+/// A set of rules to run on a config file
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Linter {
+    /// A linter with the rules built in to this crate
+    pub fn new() -> Linter {
+        Linter{rules: default_rules()}
+    }
+
+    /// A linter with the security audit rules built in to this crate
+    pub fn security() -> Linter {
+        Linter{rules: security_rules()}
+    }
+
+    /// A linter with no rules
+    pub fn empty() -> Linter {
+        Linter{rules: Vec::new()}
+    }
+
+    /// Add a rule, it will be run after the rules already added
+    pub fn add_rule<R: Rule + 'static>(&mut self, rule: R) -> &mut Linter {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// The names of the rules this linter will run
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|r| r.name()).collect()
+    }
+
+    /// Run each rule in turn and collect their diagnostics
+    pub fn lint(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        self.rules.iter().flat_map(|r| r.check(config)).collect()
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Linter {
+        Linter::new()
+    }
+}
+
+fn default_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(ParseWarnings),
+        Box::new(ConflictingOptions),
+        Box::new(KeyDirection),
+        Box::new(ScriptSecurity),
+        Box::new(Addresses),
+        Box::new(Ports),
+        Box::new(StaticKeys),
+        Box::new(DeviceTopology),
+        Box::new(PushedOptions),
+        Box::new(DuplicateRemotes),
+        Box::new(ArgumentValues),
+        #[cfg(feature = "crypto")]
+        Box::new(CertificateKeyPair),
+        #[cfg(feature = "x509")]
+        Box::new(CertificateExpiry::default()),
+    ]
+}
+
+/// The numbers of the lines which contain any of the given options
+fn option_lines(config: &ParsedConfigFile, options: &[&str]) -> Vec<i32> {
+    config.success_lines.iter()
+        .filter(|l| options.contains(&l.result.openvpn_option_name()))
+        .map(|l| l.number)
+        .collect()
+}
+
+fn security_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(WeakCrypto),
+        Box::new(CredentialCaching),
+        Box::new(DnsLeaks),
+        #[cfg(feature = "crypto")]
+        Box::new(DhStrength),
+    ]
+}
+
+/// Run the rules built in to this crate on a config file
+pub fn lint(config: &ParsedConfigFile) -> Vec<Diagnostic> {
+    Linter::new().lint(config)
+}
+
+/// Run the security audit rules on a config file. These report weak
+/// settings rather than mistakes, `Severity::Warning` findings are insecure
+/// and `Severity::Info` findings are hardening suggestions.
+pub fn security_audit(config: &ParsedConfigFile) -> Vec<Diagnostic> {
+    Linter::security().lint(config)
+}
+
+/// Reports the lines which could not be parsed
+pub struct ParseWarnings;
+
+impl Rule for ParseWarnings {
+    fn name(&self) -> &'static str {
+        "parse-warnings"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        config.warning_lines.iter().map(|line| {
+            let message = match line.result {
+                ParseWarning::NoMatchingCommand => "unknown option",
+                ParseWarning::NotEnoughArguments => "missing required arguments",
+                ParseWarning::InvalidArgument => "invalid argument",
+                ParseWarning::LineTooLong => "line is longer than openvpn's limit of 256 bytes",
+                ParseWarning::UnclosedInlineFile => "inline file is never closed",
+            };
+            Diagnostic::new(self.name(), Severity::Warning, message, vec![line.number])
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    struct NoRemote;
+
+    impl Rule for NoRemote {
+        fn name(&self) -> &'static str {
+            "no-remote"
+        }
+
+        fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+            if config.iter().any(|d| d.openvpn_option_name() == "remote") {
+                return Vec::new()
+            }
+            vec![Diagnostic::new(self.name(), Severity::Error, "no remote", Vec::new())]
+        }
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let config = parse_str("client\nunknown-option\n");
+        let mut linter = Linter::empty();
+        linter.add_rule(ParseWarnings).add_rule(NoRemote);
+        assert_eq!(linter.rule_names(), vec!["parse-warnings", "no-remote"]);
+        assert_eq!(linter.lint(&config), vec![
+            Diagnostic::new("parse-warnings", Severity::Warning, "unknown option", vec![1]),
+            Diagnostic::new("no-remote", Severity::Error, "no remote", Vec::new()),
+        ]);
+
+        let mut linter = Linter::empty();
+        linter.add_rule(NoRemote);
+        assert_eq!(linter.lint(&config).len(), 1);
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = Diagnostic::new("some-rule", Severity::Error, "broken", vec![1, 3]);
+        assert_eq!(diagnostic.to_string(), "line 2, 4: error: broken [some-rule]");
+    }
+}
@@ -0,0 +1,77 @@
+use {ConfigDirective, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+fn is_valid_port(port: &str) -> bool {
+    port.parse::<u16>().map(|p| p != 0).unwrap_or(false)
+}
+
+/// Reports port arguments which aren't numbers between 1 and 65535, and
+/// `remote` options which look like they have the host and port swapped.
+pub struct Ports;
+
+impl Rule for Ports {
+    fn name(&self) -> &'static str {
+        "ports"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        for line in config.success_lines.iter() {
+            let port = match line.result {
+                ConfigDirective::Port{ref port} |
+                ConfigDirective::Lport{ref port} |
+                ConfigDirective::Rport{ref port} |
+                ConfigDirective::PortShare{ref port, ..} |
+                ConfigDirective::HttpProxy{ref port, ..} => Some(port.clone()),
+                ConfigDirective::Remote{ref host, port: Some(ref port), ..} => {
+                    if is_valid_port(host) && !is_valid_port(port) {
+                        result.push(Diagnostic::new(
+                            self.name(),
+                            Severity::Error,
+                            format!("remote {} {} looks like the host and port are swapped", host, port),
+                            vec![line.number],
+                        ));
+                        continue;
+                    }
+                    Some(port.clone())
+                },
+                // `management socket-name unix` listens on a unix socket
+                ConfigDirective::Management{ref port, ..} if port != "unix" => Some(port.clone()),
+                ConfigDirective::SocksProxy{port: Some(port), ..} => Some(port.to_string()),
+                _ => None,
+            };
+            if let Some(port) = port {
+                if !is_valid_port(&port) {
+                    result.push(Diagnostic::new(
+                        self.name(),
+                        Severity::Error,
+                        format!("invalid port {}, it must be a number between 1 and 65535", port),
+                        vec![line.number],
+                    ));
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<String> {
+        let config = parse_str(contents);
+        Ports.check(&config).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_ports() {
+        let contents = "port 1194\nremote somehost 443 tcp\nmanagement /run/ovpn.sock unix\nsocks-proxy localhost 9050\n";
+        assert_eq!(check(contents), Vec::<String>::new());
+        assert_eq!(check("lport 65536\n"), vec!["invalid port 65536, it must be a number between 1 and 65535"]);
+        assert_eq!(check("socks-proxy localhost 0\n"), vec!["invalid port 0, it must be a number between 1 and 65535"]);
+        assert_eq!(check("http-proxy proxy.example.com http\n").len(), 1);
+        assert_eq!(check("remote 1194 somehost\n"), vec!["remote 1194 somehost looks like the host and port are swapped"]);
+    }
+}
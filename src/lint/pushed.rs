@@ -0,0 +1,50 @@
+use {ParsedConfigFile, ParseWarning};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports `push` directives which clients will reject, because the pushed
+/// option can't be pushed or doesn't parse.
+pub struct PushedOptions;
+
+impl Rule for PushedOptions {
+    fn name(&self) -> &'static str {
+        "pushed-options"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        config.success_lines.iter().filter_map(|line| {
+            let message = match line.result.pushed_directive()? {
+                Ok(ref directive) if directive.is_pushable() => return None,
+                Ok(directive) => format!("{} can't be pushed to clients", directive.openvpn_option_name()),
+                Err(ParseWarning::NoMatchingCommand) => "unknown pushed option".to_string(),
+                Err(ParseWarning::NotEnoughArguments) => "missing required arguments in pushed option".to_string(),
+                Err(ParseWarning::InvalidArgument) => "invalid argument in pushed option".to_string(),
+                Err(ParseWarning::LineTooLong) => "pushed option is too long".to_string(),
+                Err(ParseWarning::UnclosedInlineFile) => "pushed option has an unclosed inline file".to_string(),
+            };
+            Some(Diagnostic::new(self.name(), Severity::Error, message, vec![line.number]))
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        PushedOptions.check(&config)
+    }
+
+    #[test]
+    fn test_pushed_options() {
+        assert_eq!(check("push \"route 10.0.0.0 255.255.255.0\"\npush \"dhcp-option DNS 10.8.0.1\"\npush \"persist-tun\"\n"), vec![]);
+        let diagnostics = check("server 10.8.0.0 255.255.255.0\npush \"tls-auth ta.key 0\"\npush \"route\"\npush \"not-an-option\"\n");
+        assert_eq!(diagnostics.iter().map(|d| &d.message[..]).collect::<Vec<_>>(), vec![
+            "tls-auth can't be pushed to clients",
+            "missing required arguments in pushed option",
+            "unknown pushed option",
+        ]);
+        assert_eq!(diagnostics[0].lines, vec![1]);
+    }
+}
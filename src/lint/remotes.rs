@@ -0,0 +1,37 @@
+use remotes::duplicate_remotes;
+use ParsedConfigFile;
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports `remote`s which connect to the same host, port and protocol as
+/// another `remote`, which skews the order openvpn tries them in.
+/// `ParsedConfigFile::dedup_remotes` removes them.
+pub struct DuplicateRemotes;
+
+impl Rule for DuplicateRemotes {
+    fn name(&self) -> &'static str {
+        "duplicate-remotes"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        duplicate_remotes(config).into_iter().map(|lines| Diagnostic::new(
+            self.name(),
+            Severity::Warning,
+            "duplicate remote",
+            lines,
+        )).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_duplicate_remotes() {
+        let config = parse_str("remote a 1194 udp\nremote b\nremote a\n");
+        let diagnostics = DuplicateRemotes.check(&config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].lines, vec![0, 2]);
+    }
+}
@@ -0,0 +1,148 @@
+use {ConfigDirective, Mode, ParsedConfigFile};
+use super::{Diagnostic, Iroutes, Linter, Rule, Severity, option_lines};
+
+fn has_any(config: &ParsedConfigFile, options: &[&str]) -> bool {
+    !option_lines(config, options).is_empty()
+}
+
+/// Reports options a client profile needs to be usable: a `remote` to
+/// connect to, a `dev`, something to verify the server with and credentials
+/// to authenticate to it. Static key (`secret`) profiles only need the
+/// remote and dev.
+pub struct ClientProfile;
+
+impl ClientProfile {
+    fn missing(&self, message: &str) -> Diagnostic {
+        Diagnostic::new(self.name(), Severity::Error, message, Vec::new())
+    }
+}
+
+impl Rule for ClientProfile {
+    fn name(&self) -> &'static str {
+        "client-profile"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        if !has_any(config, &["remote"]) {
+            result.push(self.missing("no remote, add a `remote` with the server's address"));
+        }
+        if !has_any(config, &["dev"]) {
+            result.push(self.missing("no dev, add `dev tun` or `dev tap`"));
+        }
+        if has_any(config, &["secret"]) {
+            return result;
+        }
+        if !has_any(config, &["ca", "capath", "peer-fingerprint", "pkcs12"]) {
+            result.push(self.missing("nothing to verify the server with, add a `ca`, `peer-fingerprint` or `pkcs12`"));
+        }
+        let external_cert = has_any(config, &[
+            "pkcs12", "cryptoapicert", "pkcs11-id", "pkcs11-id-management", "management-external-cert",
+        ]);
+        let cert = has_any(config, &["cert"]) || external_cert;
+        let key = has_any(config, &["key", "management-external-key"]) || external_cert;
+        if cert && !key {
+            result.push(self.missing("cert without a private key, add a `key`"));
+        } else if key && !cert {
+            result.push(self.missing("key without a certificate, add a `cert`"));
+        } else if !cert && !has_any(config, &["auth-user-pass"]) {
+            result.push(self.missing("no credentials, add a `cert` and `key` or `auth-user-pass`"));
+        }
+        result
+    }
+}
+
+/// Check that a client profile has the options it needs to be usable
+pub fn validate_client_profile(config: &ParsedConfigFile) -> Vec<Diagnostic> {
+    ClientProfile.check(config)
+}
+
+/// Reports options a server config needs: something which sets up server
+/// mode and an address pool, a CA, certificate and key, and DH parameters
+/// (or `dh none` to only use ECDH).
+pub struct ServerConfig;
+
+impl ServerConfig {
+    fn missing(&self, message: &str) -> Diagnostic {
+        Diagnostic::new(self.name(), Severity::Error, message, Vec::new())
+    }
+}
+
+impl Rule for ServerConfig {
+    fn name(&self) -> &'static str {
+        "server-config"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        let mode_server = config.success_lines.iter()
+            .any(|l| l.result == ConfigDirective::Mode{m: Mode::Server});
+        let pool = has_any(config, &["ifconfig-pool", "ifconfig-ipv6-pool"]);
+        if !has_any(config, &["server", "server-bridge", "server-ipv6"]) {
+            if !mode_server {
+                result.push(self.missing("not in server mode, add a `server` or `server-bridge`"));
+            } else if !pool {
+                result.push(self.missing("no address pool, add an `ifconfig-pool` or use `server` instead of `mode server`"));
+            }
+        }
+        if !has_any(config, &["ca", "capath", "peer-fingerprint", "pkcs12"]) {
+            result.push(self.missing("nothing to verify clients with, add a `ca`"));
+        }
+        let external_cert = has_any(config, &["pkcs12", "cryptoapicert", "pkcs11-id", "management-external-cert"]);
+        if !has_any(config, &["cert"]) && !external_cert {
+            result.push(self.missing("no server certificate, add a `cert`"));
+        }
+        if !has_any(config, &["key", "management-external-key"]) && !external_cert {
+            result.push(self.missing("no private key, add a `key`"));
+        }
+        if !has_any(config, &["dh"]) {
+            result.push(self.missing("no DH parameters, add `dh none` to use ECDH or a `dh` parameters file"));
+        }
+        result
+    }
+}
+
+/// Check that a server config has the options it needs to be usable and
+/// that its `iroute`s are consistent with its address pool and routes
+pub fn validate_server_config(config: &ParsedConfigFile) -> Vec<Diagnostic> {
+    let mut linter = Linter::empty();
+    linter.add_rule(ServerConfig).add_rule(Iroutes);
+    linter.lint(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn validate(contents: &str) -> Vec<String> {
+        let config = parse_str(contents);
+        validate_client_profile(&config).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_client_profile() {
+        assert_eq!(validate("client\nremote somehost\ndev tun\nca ca.crt\ncert c.crt\nkey c.key\n"), Vec::<String>::new());
+        assert_eq!(validate("client\nremote somehost\ndev tun\npeer-fingerprint AB:CD\nauth-user-pass\n"), Vec::<String>::new());
+        assert_eq!(validate("remote somehost\ndev tun\nsecret static.key\n"), Vec::<String>::new());
+        assert_eq!(validate("client\n").len(), 4);
+        assert_eq!(validate("client\nremote somehost\ndev tun\nca ca.crt\ncert c.crt\n"), vec![
+            "cert without a private key, add a `key`",
+        ]);
+    }
+
+    #[test]
+    fn test_server_config() {
+        let validate = |contents: &str| -> Vec<String> {
+            let config = parse_str(contents);
+            validate_server_config(&config).into_iter().map(|d| d.message).collect()
+        };
+        assert_eq!(validate("server 10.8.0.0 255.255.255.0\nca ca.crt\ncert s.crt\nkey s.key\ndh none\n"), Vec::<String>::new());
+        assert_eq!(validate("mode server\nifconfig-pool 10.8.0.2 10.8.0.100\npkcs12 s.p12\ndh dh.pem\n"), Vec::<String>::new());
+        assert_eq!(validate("mode server\nca ca.crt\ncert s.crt\nkey s.key\n"), vec![
+            "no address pool, add an `ifconfig-pool` or use `server` instead of `mode server`",
+            "no DH parameters, add `dh none` to use ECDH or a `dh` parameters file",
+        ]);
+        assert_eq!(validate("dev tun\n").len(), 5);
+    }
+}
@@ -0,0 +1,78 @@
+use {ConfigDirective, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity, option_lines};
+
+/// Options which run a user defined script, these need `script-security 2`
+const SCRIPT_OPTIONS: &[&str] = &[
+    "up", "down", "route-up", "route-pre-down", "ipchange", "client-connect", "client-disconnect",
+    "learn-address", "auth-user-pass-verify", "tls-verify", "tls-crypt-v2-verify",
+];
+
+/// Reports scripts which openvpn won't run because `script-security` is
+/// below 2, and passing passwords to scripts in environment variables.
+pub struct ScriptSecurity;
+
+impl Rule for ScriptSecurity {
+    fn name(&self) -> &'static str {
+        "script-security"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        // openvpn's default level is 1, which only allows built in commands
+        let (level_line, level) = config.success_lines.iter().rev().find_map(|l| match l.result {
+            ConfigDirective::ScriptSecurity{ref level} => Some((Some(l.number), level.parse().unwrap_or(1))),
+            _ => None,
+        }).unwrap_or((None, 1));
+
+        let mut scripts = option_lines(config, SCRIPT_OPTIONS);
+        if level < 2 && !scripts.is_empty() {
+            scripts.extend(level_line);
+            scripts.sort();
+            result.push(Diagnostic::new(
+                self.name(),
+                Severity::Warning,
+                "scripts will not be run unless script-security is at least 2",
+                scripts,
+            ));
+        }
+
+        if level >= 3 {
+            for line in config.success_lines.iter() {
+                if let ConfigDirective::AuthUserPassVerify{ref method, ..} = line.result {
+                    if method == "via-env" {
+                        let mut lines = vec![line.number];
+                        lines.extend(level_line);
+                        lines.sort();
+                        result.push(Diagnostic::new(
+                            self.name(),
+                            Severity::Info,
+                            "auth-user-pass-verify via-env passes passwords in the environment, consider via-file",
+                            lines,
+                        ));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<(Severity, Vec<i32>)> {
+        let config = parse_str(contents);
+        ScriptSecurity.check(&config).into_iter().map(|d| (d.severity, d.lines)).collect()
+    }
+
+    #[test]
+    fn test_script_security() {
+        assert_eq!(check("script-security 2\nup up.sh\n"), vec![]);
+        assert_eq!(check("up up.sh\ndown down.sh\n"), vec![(Severity::Warning, vec![0, 1])]);
+        assert_eq!(check("up up.sh\nscript-security 1\n"), vec![(Severity::Warning, vec![0, 1])]);
+        assert_eq!(check("script-security 3\nauth-user-pass-verify check.sh via-env\n"), vec![(Severity::Info, vec![0, 1])]);
+        assert_eq!(check("script-security 3\nauth-user-pass-verify check.sh via-file\n"), vec![]);
+    }
+}
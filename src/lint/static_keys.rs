@@ -0,0 +1,51 @@
+use {ConfigDirective, File, ParsedConfigFile, StaticKey};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports inline `tls-auth`, `tls-crypt` and `secret` keys which aren't
+/// well formed OpenVPN static keys, usually the result of a broken copy and
+/// paste.
+pub struct StaticKeys;
+
+impl Rule for StaticKeys {
+    fn name(&self) -> &'static str {
+        "static-keys"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        config.success_lines.iter().filter_map(|line| {
+            let contents = match line.result {
+                ConfigDirective::TlsAuth{file: File::InlineFileContents(ref contents), ..} |
+                ConfigDirective::TlsCrypt{file: File::InlineFileContents(ref contents)} |
+                ConfigDirective::Secret{file: File::InlineFileContents(ref contents), ..} => contents,
+                _ => return None,
+            };
+            contents.parse::<StaticKey>().err().map(|e| Diagnostic::new(
+                self.name(),
+                Severity::Error,
+                format!("invalid {} key: {}", line.result.openvpn_option_name(), e),
+                vec![line.number],
+            ))
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_static_keys() {
+        let hex = "0123456789abcdef".repeat(32);
+        let contents = format!(
+            "<tls-crypt>\n-----BEGIN OpenVPN Static key V1-----\n{}\n-----END OpenVPN Static key V1-----\n</tls-crypt>\n\
+             <tls-auth>\n-----BEGIN OpenVPN Static key V1-----\n{}\n</tls-auth>\ntls-auth ta.key 1\n",
+            hex, hex,
+        );
+        let config = parse_str(&contents);
+        let diagnostics = StaticKeys.check(&config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].lines, vec![5]);
+        assert_eq!(diagnostics[0].message, "invalid tls-auth key: missing the \"-----END OpenVPN Static key V1-----\" line");
+    }
+}
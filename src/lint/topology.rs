@@ -0,0 +1,127 @@
+use std::net::Ipv4Addr;
+use config_directive::is_valid_netmask;
+use {ConfigDirective, ParsedConfigFile};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports `dev`, `topology`, `ifconfig` and `server-bridge` combinations
+/// which openvpn rejects or quietly ignores: a `topology` on a tap device,
+/// `server-bridge` on a tun device and `ifconfig` arguments which don't
+/// match the topology.
+pub struct DeviceTopology;
+
+impl DeviceTopology {
+    fn problem(&self, severity: Severity, message: &str, lines: Vec<Option<i32>>) -> Diagnostic {
+        let mut lines: Vec<i32> = lines.into_iter().flatten().collect();
+        lines.sort();
+        Diagnostic::new(self.name(), severity, message, lines)
+    }
+}
+
+/// Whether `address` parses as a valid IPv4 netmask
+fn is_netmask(address: &str) -> bool {
+    address.parse().ok().is_some_and(is_valid_netmask)
+}
+
+/// Whether two addresses are usable host addresses in the same /30
+fn same_net30(local: Ipv4Addr, remote: Ipv4Addr) -> bool {
+    let (local, remote) = (u32::from(local), u32::from(remote));
+    let host = |a: u32| a & 3 != 0 && a & 3 != 3;
+    local & !3 == remote & !3 && host(local) && host(remote)
+}
+
+impl Rule for DeviceTopology {
+    fn name(&self) -> &'static str {
+        "device-topology"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut topology = None;
+        let mut ifconfig = None;
+        let mut server_bridge = None;
+        for line in config.success_lines.iter() {
+            let n = line.number;
+            match line.result {
+                ConfigDirective::Topology{ref mode} => topology = Some((n, &mode[..])),
+                ConfigDirective::Ifconfig{ref l, ref rn} => ifconfig = Some((n, &l[..], &rn[..])),
+                ConfigDirective::ServerBridge(_) => server_bridge = Some(n),
+                _ => {},
+            }
+        }
+        let (device_line, tun) = match config.device() {
+            Some((n, tap)) => (Some(n), !tap),
+            None => return Vec::new(),
+        };
+
+        let mut result = Vec::new();
+        if let (false, Some((topology_line, _))) = (tun, topology) {
+            result.push(self.problem(
+                Severity::Warning,
+                "topology only applies to tun devices and is ignored with tap",
+                vec![device_line, Some(topology_line)],
+            ));
+        }
+        if let (true, Some(bridge_line)) = (tun, server_bridge) {
+            result.push(self.problem(
+                Severity::Error,
+                "server-bridge needs a tap device",
+                vec![device_line, Some(bridge_line)],
+            ));
+        }
+        if let Some((ifconfig_line, local, remote)) = ifconfig {
+            let topology_line = topology.map(|(n, _)| n);
+            match (tun, topology.map(|(_, mode)| mode)) {
+                (false, _) | (true, Some("subnet")) if !is_netmask(remote) => result.push(self.problem(
+                    Severity::Error,
+                    "the second ifconfig argument must be a netmask with tap devices or topology subnet",
+                    vec![device_line, topology_line, Some(ifconfig_line)],
+                )),
+                (true, Some("p2p")) | (true, Some("net30")) if is_netmask(remote) => result.push(self.problem(
+                    Severity::Warning,
+                    "the second ifconfig argument looks like a netmask, but this topology expects the remote address",
+                    vec![topology_line, Some(ifconfig_line)],
+                )),
+                (true, Some("net30")) => {
+                    if let (Ok(local), Ok(remote)) = (local.parse(), remote.parse()) {
+                        if !same_net30(local, remote) {
+                            result.push(self.problem(
+                                Severity::Warning,
+                                "with topology net30 the ifconfig addresses should be hosts in the same /30",
+                                vec![topology_line, Some(ifconfig_line)],
+                            ));
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        DeviceTopology.check(&config)
+    }
+
+    #[test]
+    fn test_device_topology() {
+        assert_eq!(check("dev tun\ntopology subnet\nifconfig 10.8.0.1 255.255.255.0\n"), vec![]);
+        assert_eq!(check("dev tun0\ntopology net30\nifconfig 10.8.0.1 10.8.0.2\n"), vec![]);
+        assert_eq!(check("dev tap\nifconfig 10.8.0.1 255.255.255.0\nserver-bridge nogw\n"), vec![]);
+
+        let diagnostics = check("dev tap\ntopology subnet\nifconfig 10.8.0.1 10.8.0.2\n");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].lines, vec![0, 1]);
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].lines, vec![0, 1, 2]);
+
+        assert_eq!(check("dev tun\nserver-bridge nogw\n")[0].message, "server-bridge needs a tap device");
+        assert_eq!(check("dev tun\ntopology p2p\nifconfig 10.8.0.1 255.255.255.0\n")[0].lines, vec![1, 2]);
+        assert_eq!(check("dev-type tun\ndev vpn\ntopology net30\nifconfig 10.8.0.2 10.8.0.3\n")[0].lines, vec![2, 3]);
+    }
+}
@@ -0,0 +1,49 @@
+use {ParsedConfigFile, argument_choices};
+use super::{Diagnostic, Rule, Severity};
+
+/// Reports arguments with a fixed vocabulary, such as the `topology` mode,
+/// which have a value openvpn doesn't understand
+pub struct ArgumentValues;
+
+impl Rule for ArgumentValues {
+    fn name(&self) -> &'static str {
+        "argument-values"
+    }
+
+    fn check(&self, config: &ParsedConfigFile) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+        for line in config.success_lines.iter() {
+            let option = line.result.openvpn_option_name();
+            for (argument, value) in line.result.unexpected_argument_values() {
+                let choices = argument_choices(option, argument).unwrap_or(&[]);
+                result.push(Diagnostic::new(
+                    self.name(),
+                    Severity::Warning,
+                    format!("unknown {} {} {}, expected one of {}", option, argument, value, choices.join(", ")),
+                    vec![line.number],
+                ));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn check(contents: &str) -> Vec<Diagnostic> {
+        let config = parse_str(contents);
+        ArgumentValues.check(&config)
+    }
+
+    #[test]
+    fn test_argument_values() {
+        assert_eq!(check("topology subnet\nremote somehost 1194 tcp\nauth-retry nointeract\ncomp-lzo\n"), vec![]);
+        let diagnostics = check("client\ntopology subnets\nremote somehost 1194 tcpp\n");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "unknown topology mode subnets, expected one of net30, p2p, subnet");
+        assert_eq!(diagnostics[1].lines, vec![2]);
+    }
+}
@@ -0,0 +1,83 @@
+//! Combining a config with another layered on top of it, as openvpn does
+//! with a server config and a `client-config-dir` file
+use {ConfigDirective, ConfigLine, ParsedConfigFile};
+
+/// A line of the base config which was dropped when merging
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Replacement {
+    /// The line of the base config
+    pub base: ConfigLine<ConfigDirective>,
+    /// The line of the overlay which replaced or removed it
+    pub by: ConfigLine<ConfigDirective>,
+}
+
+/// The result of `merge`
+pub struct MergedConfig {
+    /// The base config's lines which are still in effect followed by the
+    /// overlay's lines. Line numbers are those of the file each line came
+    /// from.
+    pub config: ParsedConfigFile,
+    /// The lines of the base config which the overlay replaced
+    pub replaced: Vec<Replacement>,
+}
+
+/// The line of `overlay` which replaces or removes `base`, if any
+fn replaced_by<'a>(base: &ConfigDirective, overlay: &'a ParsedConfigFile) -> Option<&'a ConfigLine<ConfigDirective>> {
+    overlay.success_lines.iter().find(|line| match (base, &line.result) {
+        (ConfigDirective::Push{..}, ConfigDirective::PushReset) => true,
+        (ConfigDirective::Push{option}, ConfigDirective::PushRemove{opt}) => option.contains(&opt[..]),
+        (base, overlay) => !base.is_repeatable() && base.openvpn_option_name() == overlay.openvpn_option_name(),
+    })
+}
+
+/// Merge `overlay` into `base` the way openvpn applies a `client-config-dir`
+/// file or command line options to a config. Repeatable options, such as
+/// `route` or `push`, from both configs are kept, other options in the
+/// overlay replace the same option in the base. `push-reset` and
+/// `push-remove` in the overlay remove the base's matching `push`es.
+pub fn merge(base: &ParsedConfigFile, overlay: &ParsedConfigFile) -> MergedConfig {
+    let mut success_lines = Vec::new();
+    let mut replaced = Vec::new();
+    for line in base.success_lines.iter() {
+        match replaced_by(&line.result, overlay) {
+            Some(by) => replaced.push(Replacement{base: line.clone(), by: by.clone()}),
+            None => success_lines.push(line.clone()),
+        }
+    }
+    success_lines.extend(overlay.success_lines.iter().cloned());
+    let warning_lines = base.warning_lines.iter().chain(overlay.warning_lines.iter()).cloned().collect();
+    MergedConfig{config: ParsedConfigFile{success_lines, warning_lines}, replaced}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_merge() {
+        let base = parse_str("server 10.8.0.0 255.255.255.0\nroute 10.1.0.0 255.255.0.0\n\
+                              push \"route 10.1.0.0 255.255.0.0\"\npush \"dhcp-option DNS 10.8.0.1\"\ninactive 600\n");
+        let overlay = parse_str("route 10.2.0.0 255.255.0.0\ninactive 60\npush-remove dhcp-option\n");
+        let merged = merge(&base, &overlay);
+        let options: Vec<String> = merged.config.directives().iter().map(|d| d.as_ovpn_config()).collect();
+        assert_eq!(options, vec![
+            "server 10.8.0.0 255.255.255.0",
+            "route 10.1.0.0 255.255.0.0",
+            "push \"route 10.1.0.0 255.255.0.0\"",
+            "route 10.2.0.0 255.255.0.0",
+            "inactive 60",
+            "push-remove dhcp-option",
+        ]);
+        assert_eq!(merged.replaced.iter().map(|r| (r.base.number, r.by.number)).collect::<Vec<_>>(), vec![(3, 2), (4, 1)]);
+
+        let hash = "verify-hash 00:11:22:33:44:55:66:77:88:99:aa:bb:cc:dd:ee:ff:00:11:22:33\n";
+        let merged = merge(&parse_str(hash), &parse_str(&hash.replace("00:11", "ff:ee")));
+        assert_eq!(merged.config.success_lines.len(), 2);
+        assert!(merged.replaced.is_empty());
+
+        let merged = merge(&base, &parse_str("push-reset\n"));
+        assert_eq!(merged.replaced.len(), 2);
+        assert_eq!(merged.config.success_lines.len(), 4);
+    }
+}
@@ -0,0 +1,156 @@
+//! Rewriting legacy options to their openvpn 2.6 equivalents
+use {ConfigDirective, ConfigLine, ParsedConfigFile, Version};
+
+/// The ciphers openvpn 2.6 negotiates by default
+const DEFAULT_DATA_CIPHERS: &[&str] = &["AES-256-GCM", "AES-128-GCM", "CHACHA20-POLY1305"];
+
+/// How a line was changed by `modernized`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ModernizeChange {
+    /// The line was removed as the option no longer does anything, or has
+    /// been removed without a replacement
+    Dropped(ConfigLine<ConfigDirective>),
+    /// The line was replaced by the given directives
+    Rewritten{original: ConfigLine<ConfigDirective>, replacements: Vec<ConfigDirective>},
+}
+
+/// The result of `modernized`
+pub struct Modernized {
+    /// The config with legacy options rewritten. Rewritten lines keep their
+    /// line numbers.
+    pub config: ParsedConfigFile,
+    /// The lines which were dropped or rewritten, in the order they appear
+    pub changes: Vec<ModernizeChange>,
+}
+
+/// What a legacy directive becomes, `None` if it's kept as it is. `data_ciphers`
+/// is whether the config already has a `data-ciphers` or `ncp-ciphers`,
+/// `static_key` whether it has a `secret` and `allow_compression` whether it
+/// already has an `allow-compression`.
+fn modernize(directive: &ConfigDirective, data_ciphers: bool, static_key: bool, allow_compression: bool)
+    -> Option<Vec<ConfigDirective>>
+{
+    let replacements = match *directive {
+        ConfigDirective::NsCertType{ref client_or_server} => {
+            vec![ConfigDirective::RemoteCertTls{client_or_server: client_or_server.clone()}]
+        },
+        ConfigDirective::NcpCiphers{ref cipher_list} => vec![ConfigDirective::DataCiphers{cipher_list: cipher_list.clone()}],
+        // Static key mode doesn't negotiate ciphers, `cipher` is the only
+        // way to choose one
+        ConfigDirective::Cipher{..} if static_key => return None,
+        ConfigDirective::Cipher{ref alg} if data_ciphers => vec![ConfigDirective::DataCiphersFallback{alg: alg.clone()}],
+        ConfigDirective::Cipher{ref alg} => {
+            // Peers which negotiate ciphers can still pick the old one from
+            // `data-ciphers`, peers too old to negotiate need the fallback
+            let mut ciphers = DEFAULT_DATA_CIPHERS.to_vec();
+            if !ciphers.iter().any(|c| c.eq_ignore_ascii_case(alg)) {
+                ciphers.push(alg);
+            }
+            vec![
+                ConfigDirective::DataCiphers{cipher_list: ciphers.join(":")},
+                ConfigDirective::DataCiphersFallback{alg: alg.clone()},
+            ]
+        },
+        // `compress` without an algorithm frames packets differently to
+        // `comp-lzo no`, which has no other spelling
+        ConfigDirective::CompLzo{mode: Some(ref mode)} if mode == "no" => return None,
+        ConfigDirective::CompLzo{..} => {
+            // Keep the lzo framing so that peers which still use `comp-lzo`
+            // can connect, but only decompress what they send
+            let mut replacements = vec![ConfigDirective::Compress{algorithm: Some("lzo".to_string())}];
+            if !allow_compression {
+                replacements.push(ConfigDirective::AllowCompression{mode: "asym".to_string()});
+            }
+            replacements
+        },
+        ConfigDirective::TlsRemote{ref x509name} => vec![ConfigDirective::VerifyX509Name{
+            name: x509name.clone(),
+            verify_x509_name_type: "name".to_string(),
+        }],
+        ConfigDirective::ClientCertNotRequired => {
+            vec![ConfigDirective::VerifyClientCert{none_optional_require: "none".to_string()}]
+        },
+        ConfigDirective::IfconfigPoolLinear => vec![ConfigDirective::Topology{mode: "p2p".to_string()}],
+        _ => {
+            let removed = directive.versions().removed.is_some_and(|v| v <= Version::new(2, 6));
+            return if removed { Some(Vec::new()) } else { None };
+        },
+    };
+    Some(replacements)
+}
+
+impl ParsedConfigFile {
+    /// This config with legacy options rewritten to their openvpn 2.6
+    /// equivalents, with a list of the changes so they can be reviewed
+    /// before the config is saved. `ns-cert-type` becomes
+    /// `remote-cert-tls`, `ncp-ciphers` becomes `data-ciphers`, `cipher`
+    /// becomes `data-ciphers` and `data-ciphers-fallback` (only the
+    /// fallback if there already is a `data-ciphers`, and it's kept with a
+    /// static `secret`), `comp-lzo` becomes `compress lzo` and
+    /// `allow-compression asym` (`comp-lzo no` is kept), `tls-remote`
+    /// becomes `verify-x509-name` and options removed by 2.6 are rewritten
+    /// to their replacement or dropped.
+    pub fn modernized(&self) -> Modernized {
+        let options: Vec<&str> = self.success_lines.iter().map(|l| l.result.openvpn_option_name()).collect();
+        let data_ciphers = options.contains(&"data-ciphers") || options.contains(&"ncp-ciphers");
+        let static_key = options.contains(&"secret");
+        let mut allow_compression = options.contains(&"allow-compression");
+        let mut success_lines = Vec::new();
+        let mut changes = Vec::new();
+        for line in self.success_lines.iter() {
+            match modernize(&line.result, data_ciphers, static_key, allow_compression) {
+                Some(replacements) => {
+                    if replacements.is_empty() {
+                        changes.push(ModernizeChange::Dropped(line.clone()));
+                        continue;
+                    }
+                    allow_compression |= replacements.iter().any(|r| r.openvpn_option_name() == "allow-compression");
+                    success_lines.extend(replacements.iter().map(|r| ConfigLine{number: line.number, result: r.clone()}));
+                    changes.push(ModernizeChange::Rewritten{original: line.clone(), replacements});
+                },
+                None => success_lines.push(line.clone()),
+            }
+        }
+        Modernized{
+            config: ParsedConfigFile{success_lines, warning_lines: self.warning_lines.clone()},
+            changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn modernized(contents: &str) -> (Vec<String>, Vec<ModernizeChange>) {
+        let result = parse_str(contents).modernized();
+        let lines = result.config.success_lines.iter().map(|l| l.result.as_ovpn_config()).collect();
+        (lines, result.changes)
+    }
+
+    #[test]
+    fn test_modernized() {
+        let (lines, changes) = modernized("client\nns-cert-type server\ncipher AES-256-CBC\ncomp-lzo\n\
+                                           tls-remote vpn.example.com\nkey-method 2\nverb 3\n");
+        assert_eq!(lines, vec![
+            "client",
+            "remote-cert-tls server",
+            "data-ciphers AES-256-GCM:AES-128-GCM:CHACHA20-POLY1305:AES-256-CBC",
+            "data-ciphers-fallback AES-256-CBC",
+            "compress lzo",
+            "allow-compression asym",
+            "verify-x509-name vpn.example.com name",
+            "verb 3",
+        ]);
+        assert_eq!(changes.len(), 5);
+        assert_eq!(changes[4], ModernizeChange::Dropped(ConfigLine{number: 5, result: ConfigDirective::KeyMethod{m: "2".to_string()}}));
+
+        let (lines, _) = modernized("data-ciphers AES-256-GCM\ncipher BF-CBC\ncomp-lzo yes\nallow-compression yes\n");
+        assert_eq!(lines, vec!["data-ciphers AES-256-GCM", "data-ciphers-fallback BF-CBC", "compress lzo", "allow-compression yes"]);
+
+        let (lines, changes) = modernized("secret static.key\ncipher AES-256-CBC\ncomp-lzo no\n");
+        assert_eq!(lines, vec!["secret static.key", "cipher AES-256-CBC", "comp-lzo no"]);
+        assert!(changes.is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+//! IPv4 and IPv6 networks in CIDR form, for comparing the networks used by
+//! `server`, `route`, `iroute` and friends
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use config_directive::is_valid_netmask;
+
+/// An IPv4 network, e.g `10.8.0.0/24`
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct Ipv4Network {
+    address: Ipv4Addr,
+    prefix_len: u8,
+}
+
+impl Ipv4Network {
+    /// The network containing `address` with a prefix of `prefix_len` bits,
+    /// `None` if `prefix_len` is more than 32
+    pub fn new(address: Ipv4Addr, prefix_len: u8) -> Option<Ipv4Network> {
+        if prefix_len > 32 {
+            return None;
+        }
+        let mask = Ipv4Network::mask_bits(prefix_len);
+        Some(Ipv4Network{address: Ipv4Addr::from(u32::from(address) & mask), prefix_len})
+    }
+
+    /// The network from an address and netmask, as used by openvpn options.
+    /// `None` if the netmask isn't contiguous.
+    pub fn with_netmask(address: Ipv4Addr, netmask: Ipv4Addr) -> Option<Ipv4Network> {
+        if !is_valid_netmask(netmask) {
+            return None;
+        }
+        Ipv4Network::new(address, u32::from(netmask).leading_ones() as u8)
+    }
+
+    /// The network of a `network [netmask]` pair as given to `route` and
+    /// `iroute`, a missing netmask meaning a single host. `None` if either
+    /// isn't a valid address.
+    pub fn from_route(network: &str, netmask: Option<&str>) -> Option<Ipv4Network> {
+        let netmask = netmask.map_or(Some(Ipv4Addr::BROADCAST), |n| n.parse().ok())?;
+        Ipv4Network::with_netmask(network.parse().ok()?, netmask)
+    }
+
+    fn mask_bits(prefix_len: u8) -> u32 {
+        u32::MAX.checked_shl(32 - u32::from(prefix_len)).unwrap_or(0)
+    }
+
+    /// The network address
+    pub fn address(&self) -> Ipv4Addr {
+        self.address
+    }
+
+    /// The number of bits in the network prefix
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// The netmask in dotted form, e.g `255.255.255.0`
+    pub fn netmask(&self) -> Ipv4Addr {
+        Ipv4Addr::from(Ipv4Network::mask_bits(self.prefix_len))
+    }
+
+    /// The first and last addresses in the network
+    pub fn range(&self) -> (Ipv4Addr, Ipv4Addr) {
+        let first = u32::from(self.address);
+        (Ipv4Addr::from(first), Ipv4Addr::from(first | !Ipv4Network::mask_bits(self.prefix_len)))
+    }
+
+    /// Whether every address in `other` is in this network
+    pub fn contains(&self, other: &Ipv4Network) -> bool {
+        self.prefix_len <= other.prefix_len && Ipv4Network::new(other.address, self.prefix_len) == Some(*self)
+    }
+
+    /// Whether any address is in both networks
+    pub fn overlaps(&self, other: &Ipv4Network) -> bool {
+        self.contains(other) || other.contains(self)
+    }
+}
+
+impl fmt::Display for Ipv4Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+/// The error returned when parsing a network fails
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct NetworkParseError;
+
+impl fmt::Display for NetworkParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid network")
+    }
+}
+
+/// Parse `address[/bits]`, the prefix length defaults to the full address
+fn split_prefix<A: FromStr>(s: &str, max_len: u8) -> Result<(A, u8), NetworkParseError> {
+    let mut parts = s.splitn(2, '/');
+    let address = parts.next().unwrap_or("").parse().map_err(|_| NetworkParseError)?;
+    let prefix_len = match parts.next() {
+        Some(bits) => bits.parse().map_err(|_| NetworkParseError)?,
+        None => max_len,
+    };
+    Ok((address, prefix_len))
+}
+
+impl FromStr for Ipv4Network {
+    type Err = NetworkParseError;
+
+    fn from_str(s: &str) -> Result<Ipv4Network, NetworkParseError> {
+        let (address, prefix_len) = split_prefix(s, 32)?;
+        Ipv4Network::new(address, prefix_len).ok_or(NetworkParseError)
+    }
+}
+
+/// The address of a `dns server n address` without its port, which may be
+/// given as `address:port` or `[address]:port`
+pub(crate) fn dns_address(address: &str) -> Option<IpAddr> {
+    if let Ok(address) = address.parse() {
+        return Some(address);
+    }
+    let host = match address.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next()?,
+        None => address.split(':').next()?,
+    };
+    host.parse().ok()
+}
+
+/// An IPv6 network, e.g `2001:db8::/64`
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct Ipv6Network {
+    address: Ipv6Addr,
+    prefix_len: u8,
+}
+
+impl Ipv6Network {
+    /// The network containing `address` with a prefix of `prefix_len` bits,
+    /// `None` if `prefix_len` is more than 128
+    pub fn new(address: Ipv6Addr, prefix_len: u8) -> Option<Ipv6Network> {
+        if prefix_len > 128 {
+            return None;
+        }
+        let mask = Ipv6Network::mask_bits(prefix_len);
+        Some(Ipv6Network{address: Ipv6Addr::from(u128::from(address) & mask), prefix_len})
+    }
+
+    fn mask_bits(prefix_len: u8) -> u128 {
+        u128::MAX.checked_shl(128 - u32::from(prefix_len)).unwrap_or(0)
+    }
+
+    /// The network address
+    pub fn address(&self) -> Ipv6Addr {
+        self.address
+    }
+
+    /// The number of bits in the network prefix
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Whether every address in `other` is in this network
+    pub fn contains(&self, other: &Ipv6Network) -> bool {
+        self.prefix_len <= other.prefix_len && Ipv6Network::new(other.address, self.prefix_len) == Some(*self)
+    }
+
+    /// Whether any address is in both networks
+    pub fn overlaps(&self, other: &Ipv6Network) -> bool {
+        self.contains(other) || other.contains(self)
+    }
+}
+
+impl fmt::Display for Ipv6Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+impl FromStr for Ipv6Network {
+    type Err = NetworkParseError;
+
+    fn from_str(s: &str) -> Result<Ipv6Network, NetworkParseError> {
+        let (address, prefix_len) = split_prefix(s, 128)?;
+        Ipv6Network::new(address, prefix_len).ok_or(NetworkParseError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipv4_network() {
+        let network: Ipv4Network = "10.8.0.1/24".parse().unwrap();
+        assert_eq!(network.to_string(), "10.8.0.0/24");
+        assert_eq!(network.netmask(), Ipv4Addr::new(255, 255, 255, 0));
+        assert_eq!(network.range(), (Ipv4Addr::new(10, 8, 0, 0), Ipv4Addr::new(10, 8, 0, 255)));
+        let host = Ipv4Network::with_netmask(Ipv4Addr::new(10, 8, 0, 7), Ipv4Addr::new(255, 255, 255, 255)).unwrap();
+        assert!(network.contains(&host));
+        assert!(host.overlaps(&network));
+        assert!(!network.overlaps(&"10.9.0.0/16".parse().unwrap()));
+        assert!("0.0.0.0/0".parse::<Ipv4Network>().unwrap().contains(&network));
+        assert_eq!(Ipv4Network::with_netmask(Ipv4Addr::new(10, 8, 0, 0), Ipv4Addr::new(255, 0, 255, 0)), None);
+        assert!("10.8.0.0/33".parse::<Ipv4Network>().is_err());
+        assert_eq!(Ipv4Network::from_route("10.1.0.0", Some("255.255.0.0")), "10.1.0.0/16".parse().ok());
+        assert_eq!(Ipv4Network::from_route("10.1.0.1", None), "10.1.0.1/32".parse().ok());
+        assert_eq!(Ipv4Network::from_route("vpn.example.com", None), None);
+    }
+
+    #[test]
+    fn test_ipv6_network() {
+        let network: Ipv6Network = "2001:db8::1/64".parse().unwrap();
+        assert_eq!(network.to_string(), "2001:db8::/64");
+        assert!(network.contains(&"2001:db8::2".parse().unwrap()));
+        assert!(!network.overlaps(&"2001:db9::/64".parse().unwrap()));
+        assert!("::/0".parse::<Ipv6Network>().unwrap().contains(&network));
+        assert!("2001:db8::/129".parse::<Ipv6Network>().is_err());
+    }
+
+    #[test]
+    fn test_dns_address() {
+        assert_eq!(dns_address("10.8.0.1"), "10.8.0.1".parse().ok());
+        assert_eq!(dns_address("10.8.0.1:5353"), "10.8.0.1".parse().ok());
+        assert_eq!(dns_address("2001:db8::1"), "2001:db8::1".parse().ok());
+        assert_eq!(dns_address("[2001:db8::1]:5353"), "2001:db8::1".parse().ok());
+        assert_eq!(dns_address("dns.example.com"), None);
+    }
+}
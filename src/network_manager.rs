@@ -0,0 +1,497 @@
+//! Converting configs to and from the settings of NetworkManager's openvpn plugin
+use std::collections::BTreeMap;
+use std::path::Path;
+use config_directive::quote_arg;
+use errors;
+use {parse, ConfigDirective, File, Ipv4Network, ParsedConfigFile};
+
+/// The `service-type` of NetworkManager's openvpn plugin
+pub const NETWORK_MANAGER_SERVICE_TYPE: &str = "org.freedesktop.NetworkManager.openvpn";
+
+/// Options NetworkManager doesn't need a setting for, because it always
+/// runs openvpn as a client with them or they only affect logging
+const IMPLIED_OPTIONS: &[&str] = &[
+    "client", "pull", "tls-client", "nobind", "persist-key", "persist-tun", "resolv-retry", "verb", "mute",
+    "auth-nocache", "key-direction", "port", "rport", "proto",
+];
+
+/// The settings of a NetworkManager openvpn connection
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct NetworkManagerSettings {
+    /// The `vpn` setting's data items
+    pub data: BTreeMap<String, String>,
+    /// The `vpn` setting's secrets
+    pub secrets: BTreeMap<String, String>,
+    /// The `ipv4` setting
+    pub ipv4: BTreeMap<String, String>,
+    /// The lines of options which NetworkManager has no setting for
+    pub unsupported: Vec<i32>,
+}
+
+impl NetworkManagerSettings {
+    fn set(&mut self, key: &str, value: &str) {
+        self.data.insert(key.to_string(), value.to_string());
+    }
+
+    /// The settings as a NetworkManager keyfile for a connection called
+    /// `id`, as found in `/etc/NetworkManager/system-connections`
+    pub fn to_keyfile(&self, id: &str) -> String {
+        let mut result = format!("[connection]\nid={}\ntype=vpn\n\n[vpn]\nservice-type={}\n", id, NETWORK_MANAGER_SERVICE_TYPE);
+        for (key, value) in self.data.iter() {
+            result.push_str(&format!("{}={}\n", key, value));
+        }
+        if !self.secrets.is_empty() {
+            result.push_str("\n[vpn-secrets]\n");
+            for (key, value) in self.secrets.iter() {
+                result.push_str(&format!("{}={}\n", key, value));
+            }
+        }
+        result.push_str("\n[ipv4]\nmethod=auto\n");
+        for (key, value) in self.ipv4.iter() {
+            result.push_str(&format!("{}={}\n", key, value));
+        }
+        result
+    }
+
+    /// The settings in a NetworkManager keyfile, the `vpn`, `vpn-secrets`
+    /// and `ipv4` sections, other than the `service-type` and `method`
+    pub fn from_keyfile(keyfile: &str) -> NetworkManagerSettings {
+        let mut settings = NetworkManagerSettings::default();
+        let mut section = "";
+        for line in keyfile.lines().map(str::trim) {
+            if line.starts_with('[') && line.ends_with(']') {
+                section = &line[1..line.len() - 1];
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(i) if !line.starts_with('#') => (line[..i].trim().to_string(), line[i + 1..].trim().to_string()),
+                _ => continue,
+            };
+            match section {
+                "vpn" if key != "service-type" => settings.data.insert(key, value),
+                "vpn-secrets" => settings.secrets.insert(key, value),
+                "ipv4" if key != "method" => settings.ipv4.insert(key, value),
+                _ => None,
+            };
+        }
+        settings
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.data.get(key).map(|v| &v[..])
+    }
+}
+
+/// The path of an option's file, which is never inline once the config has
+/// been externalized
+fn path(directive: &ConfigDirective) -> String {
+    match directive.file() {
+        Some(File::FilePath(path)) => path.clone(),
+        _ => String::new(),
+    }
+}
+
+/// The settings of a NetworkManager openvpn connection equivalent to a
+/// client config. NetworkManager only refers to certificates and keys by
+/// path, so inline files are written to `cert_dir` first, as by
+/// `ParsedConfigFile::externalized`. Inline proxy credentials become the
+/// `http-proxy-username` and the `http-proxy-password` secret, and inline
+/// `auth-user-pass` credentials the `username` and the `password` secret.
+/// Options which NetworkManager has no setting for are listed in
+/// `unsupported`.
+pub fn to_network_manager<P: AsRef<Path>>(config: &ParsedConfigFile, cert_dir: P) -> errors::Result<NetworkManagerSettings> {
+    let mut settings = NetworkManagerSettings::default();
+    let mut config = ParsedConfigFile{
+        success_lines: config.success_lines.clone(),
+        warning_lines: config.warning_lines.clone(),
+    };
+    // NetworkManager keeps proxy credentials itself rather than in a file
+    config.success_lines.retain(|line| match line.result {
+        ConfigDirective::HttpProxyUserPass{file: File::InlineFileContents(ref contents)} => {
+            let mut lines = contents.lines();
+            settings.set("http-proxy-username", lines.next().unwrap_or(""));
+            settings.secrets.insert("http-proxy-password".to_string(), lines.next().unwrap_or("").to_string());
+            false
+        },
+        _ => true,
+    });
+    let config = config.externalized(cert_dir)?;
+
+    let mut remotes = Vec::new();
+    let mut routes = Vec::new();
+    let mut key_direction = None;
+    let (mut cert, mut static_key, mut password) = (false, false, false);
+    for line in config.success_lines.iter() {
+        match line.result {
+            ConfigDirective::Remote{ref host, ref port, ref proto} => {
+                let mut remote = host.clone();
+                if let Some(ref port) = *port {
+                    remote = format!("{}:{}", remote, port);
+                    if let Some(ref proto) = *proto {
+                        remote = format!("{}:{}", remote, proto);
+                    }
+                }
+                remotes.push(remote);
+            },
+            ConfigDirective::Port{ref port} | ConfigDirective::Rport{ref port} => settings.set("port", port),
+            ConfigDirective::Proto{ref p} => settings.set("proto-tcp", if p.starts_with("tcp") { "yes" } else { "no" }),
+            ConfigDirective::RemoteRandom => settings.set("remote-random", "yes"),
+            ConfigDirective::Float => settings.set("float", "yes"),
+            ConfigDirective::Dev{ref devarg} => settings.set("dev", devarg),
+            ConfigDirective::DevType{ref device_type} => settings.set("dev-type", device_type),
+            ConfigDirective::TunIpv6 => settings.set("tun-ipv6", "yes"),
+            ConfigDirective::Ca{..} => settings.set("ca", &path(&line.result)),
+            ConfigDirective::ExtraCerts{..} => settings.set("extra-certs", &path(&line.result)),
+            ConfigDirective::CrlVerify{..} => settings.set("crl-verify-file", &path(&line.result)),
+            ConfigDirective::Cert{..} => {
+                cert = true;
+                settings.set("cert", &path(&line.result));
+            },
+            ConfigDirective::Key{..} => settings.set("key", &path(&line.result)),
+            ConfigDirective::Pkcs12{..} => {
+                // NetworkManager takes everything from the bundle
+                cert = true;
+                for key in ["ca", "cert", "key"].iter() {
+                    settings.set(key, &path(&line.result));
+                }
+            },
+            ConfigDirective::TlsAuth{ref direction, ..} => {
+                settings.set("ta", &path(&line.result));
+                if let Some(ref direction) = *direction {
+                    settings.set("ta-dir", direction);
+                }
+            },
+            ConfigDirective::TlsCrypt{..} => settings.set("tls-crypt", &path(&line.result)),
+            ConfigDirective::TlsCryptV2{..} => settings.set("tls-crypt-v2", &path(&line.result)),
+            ConfigDirective::Secret{ref direction, ..} => {
+                static_key = true;
+                settings.set("static-key", &path(&line.result));
+                if let Some(ref direction) = *direction {
+                    settings.set("static-key-direction", direction);
+                }
+            },
+            ConfigDirective::KeyDirection{ref direction} => key_direction = Some(direction.clone()),
+            ConfigDirective::Ifconfig{ref l, ref rn} => {
+                settings.set("local-ip", l);
+                settings.set("remote-ip", rn);
+            },
+            ConfigDirective::AuthUserPass{ref up} => {
+                password = true;
+                if let Some(File::InlineFileContents(ref contents)) = *up {
+                    let mut lines = contents.lines();
+                    settings.set("username", lines.next().unwrap_or(""));
+                    settings.secrets.insert("password".to_string(), lines.next().unwrap_or("").to_string());
+                }
+            },
+            ConfigDirective::Cipher{ref alg} => settings.set("cipher", alg),
+            ConfigDirective::DataCiphers{ref cipher_list} => settings.set("data-ciphers", cipher_list),
+            ConfigDirective::DataCiphersFallback{ref alg} => settings.set("data-ciphers-fallback", alg),
+            ConfigDirective::Auth{ref alg} => settings.set("auth", alg),
+            ConfigDirective::Keysize{ref n} => settings.set("keysize", n),
+            ConfigDirective::TlsCipher{ref l} => settings.set("tls-cipher", l),
+            ConfigDirective::TlsVersionMin{ref version, ref or_highest} => {
+                settings.set("tls-version-min", version);
+                if or_highest.is_some() {
+                    settings.set("tls-version-min-or-highest", "yes");
+                }
+            },
+            ConfigDirective::TlsVersionMax{ref version} => settings.set("tls-version-max", version),
+            ConfigDirective::RemoteCertTls{ref client_or_server} => settings.set("remote-cert-tls", client_or_server),
+            ConfigDirective::NsCertType{ref client_or_server} => settings.set("ns-cert-type", client_or_server),
+            ConfigDirective::VerifyX509Name{ref name, ref verify_x509_name_type} => {
+                settings.set("verify-x509-name", &format!("{}:{}", verify_x509_name_type, name));
+            },
+            ConfigDirective::RenegSec{ref n} => settings.set("reneg-seconds", n),
+            ConfigDirective::CompLzo{ref mode} => {
+                let mode = match mode.as_ref().map(|m| &m[..]) {
+                    Some("no") => "no-by-default",
+                    Some("yes") => "yes",
+                    _ => "adaptive",
+                };
+                settings.set("comp-lzo", mode);
+            },
+            ConfigDirective::Compress{ref algorithm} => settings.set("compress", algorithm.as_ref().map_or("yes", |a| a)),
+            ConfigDirective::TunMtu{ref n} => settings.set("tunnel-mtu", n),
+            ConfigDirective::Fragment{ref max, ..} => settings.set("fragment-size", max),
+            ConfigDirective::Mssfix{ref max, ..} => settings.set("mssfix", max.as_ref().map_or("yes", |m| m)),
+            ConfigDirective::MtuDisc{ref mtu_disc_type} => settings.set("mtu-disc", mtu_disc_type),
+            ConfigDirective::Ping{ref n} => settings.set("ping", n),
+            ConfigDirective::PingExit{ref n} => settings.set("ping-exit", n),
+            ConfigDirective::PingRestart{ref n} => settings.set("ping-restart", n),
+            ConfigDirective::ConnectTimeout{ref n} => settings.set("connect-timeout", n),
+            ConfigDirective::PushPeerInfo => settings.set("push-peer-info", "yes"),
+            ConfigDirective::AllowPullFqdn => settings.set("allow-pull-fqdn", "yes"),
+            ConfigDirective::HttpProxy{ref server, ref port, ..} => {
+                settings.set("proxy-type", "http");
+                settings.set("proxy-server", server);
+                settings.set("proxy-port", port);
+            },
+            ConfigDirective::SocksProxy{ref server, ..} => {
+                settings.set("proxy-type", "socks");
+                settings.set("proxy-server", server);
+                settings.set("proxy-port", &line.result.socks_proxy_port().unwrap_or(1080).to_string());
+            },
+            ConfigDirective::RouteNopull => {
+                settings.ipv4.insert("ignore-auto-routes".to_string(), "true".to_string());
+            },
+            ConfigDirective::Route{ref network_or_ip, ref netmask, ..} => {
+                match Ipv4Network::from_route(network_or_ip, netmask.as_deref()) {
+                    Some(network) => routes.push(network.to_string()),
+                    None => settings.unsupported.push(line.number),
+                }
+            },
+            ref directive if IMPLIED_OPTIONS.contains(&directive.openvpn_option_name()) => {},
+            _ => settings.unsupported.push(line.number),
+        }
+    }
+
+    if !remotes.is_empty() {
+        settings.set("remote", &remotes.join(", "));
+    }
+    if let Some(direction) = key_direction {
+        for key in ["ta", "static-key"].iter() {
+            let direction_key = format!("{}-{}", key, if *key == "ta" { "dir" } else { "direction" });
+            if settings.data.contains_key(*key) && !settings.data.contains_key(&direction_key) {
+                settings.set(&direction_key, &direction);
+            }
+        }
+    }
+    let connection_type = match (static_key, cert, password) {
+        (true, _, _) => "static-key",
+        (false, true, true) => "password-tls",
+        (false, false, true) => "password",
+        (false, _, false) => "tls",
+    };
+    settings.set("connection-type", connection_type);
+    for (i, route) in routes.into_iter().enumerate() {
+        settings.ipv4.insert(format!("route{}", i + 1), route);
+    }
+    Ok(settings)
+}
+
+/// Data items which are the same as an openvpn option with a single
+/// argument
+const SIMPLE_OPTIONS: &[(&str, &str)] = &[
+    ("dev", "dev"), ("dev-type", "dev-type"), ("extra-certs", "extra-certs"), ("crl-verify-file", "crl-verify"),
+    ("tls-crypt", "tls-crypt"), ("tls-crypt-v2", "tls-crypt-v2"), ("cipher", "cipher"), ("data-ciphers", "data-ciphers"),
+    ("data-ciphers-fallback", "data-ciphers-fallback"), ("auth", "auth"), ("keysize", "keysize"),
+    ("tls-cipher", "tls-cipher"), ("tls-version-max", "tls-version-max"), ("remote-cert-tls", "remote-cert-tls"),
+    ("ns-cert-type", "ns-cert-type"), ("reneg-seconds", "reneg-sec"), ("tunnel-mtu", "tun-mtu"),
+    ("fragment-size", "fragment"), ("mtu-disc", "mtu-disc"), ("ping", "ping"), ("ping-exit", "ping-exit"),
+    ("ping-restart", "ping-restart"), ("connect-timeout", "connect-timeout"),
+];
+
+/// Data items which are `yes` to turn on an openvpn option with no
+/// arguments
+const FLAG_OPTIONS: &[&str] = &["remote-random", "float", "tun-ipv6", "push-peer-info", "allow-pull-fqdn"];
+
+/// Build an option line, quoting the arguments
+fn option(name: &str, args: &[&str]) -> String {
+    let mut line = name.to_string();
+    for arg in args {
+        line.push(' ');
+        line.push_str(&quote_arg(arg.to_string()));
+    }
+    line
+}
+
+/// A NetworkManager `remote`, `host[:port[:proto]]`, as a `remote` option
+fn remote(remote: &str) -> String {
+    let parts: Vec<&str> = remote.splitn(3, ':').collect();
+    option("remote", &parts)
+}
+
+/// A NetworkManager route, `network/prefix[,gateway[,metric]]`, as a `route`
+/// option
+fn nm_route(route: &str) -> Option<String> {
+    let mut parts = route.split(',').map(str::trim);
+    let network: Ipv4Network = parts.next()?.parse().ok()?;
+    let (address, netmask) = (network.address().to_string(), network.netmask().to_string());
+    let mut args = vec![&address[..], &netmask[..]];
+    args.extend(parts.filter(|p| !p.is_empty()));
+    Some(option("route", &args))
+}
+
+/// The config equivalent to the settings of a NetworkManager openvpn
+/// connection, the reverse of `to_network_manager`. Proxy credentials are
+/// put inline. Settings which have no openvpn option, such as the
+/// `username`, are left out, and options whose values openvpn wouldn't
+/// accept are in the config's `warning_lines`.
+pub fn from_network_manager(settings: &NetworkManagerSettings) -> ParsedConfigFile {
+    let mut lines = Vec::new();
+    let connection_type = settings.get("connection-type").unwrap_or("tls");
+    if connection_type != "static-key" {
+        lines.push(option("client", &[]));
+    }
+    if let Some(remotes) = settings.get("remote") {
+        lines.extend(remotes.split(|c: char| c == ',' || c.is_whitespace()).filter(|r| !r.is_empty()).map(remote));
+    }
+    if let Some(port) = settings.get("port") {
+        lines.push(option("port", &[port]));
+    }
+    if settings.get("proto-tcp") == Some("yes") {
+        lines.push(option("proto", &["tcp-client"]));
+    }
+    for flag in FLAG_OPTIONS {
+        if settings.get(flag) == Some("yes") {
+            lines.push(option(flag, &[]));
+        }
+    }
+    if let (Some(local), Some(remote)) = (settings.get("local-ip"), settings.get("remote-ip")) {
+        lines.push(option("ifconfig", &[local, remote]));
+    }
+    if connection_type == "password" || connection_type == "password-tls" {
+        lines.push(option("auth-user-pass", &[]));
+    }
+    let (ca, cert, key) = (settings.get("ca"), settings.get("cert"), settings.get("key"));
+    match (ca, cert, key) {
+        (Some(ca), Some(cert), Some(key)) if ca == cert && cert == key => lines.push(option("pkcs12", &[ca])),
+        _ => {
+            for &(name, path) in [("ca", ca), ("cert", cert), ("key", key)].iter() {
+                if let Some(path) = path {
+                    lines.push(option(name, &[path]));
+                }
+            }
+        },
+    }
+    for &(file_key, direction_key, name) in [("ta", "ta-dir", "tls-auth"), ("static-key", "static-key-direction", "secret")].iter() {
+        if let Some(path) = settings.get(file_key) {
+            let mut args = vec![path];
+            args.extend(settings.get(direction_key));
+            lines.push(option(name, &args));
+        }
+    }
+    for &(key, name) in SIMPLE_OPTIONS {
+        if let Some(value) = settings.get(key) {
+            lines.push(option(name, &[value]));
+        }
+    }
+    if let Some(version) = settings.get("tls-version-min") {
+        let mut args = vec![version];
+        if settings.get("tls-version-min-or-highest") == Some("yes") {
+            args.push("or-highest");
+        }
+        lines.push(option("tls-version-min", &args));
+    }
+    if let Some(name) = settings.get("verify-x509-name") {
+        match name.find(':') {
+            Some(i) => lines.push(option("verify-x509-name", &[&name[i + 1..], &name[..i]])),
+            None => lines.push(option("verify-x509-name", &[name, "subject"])),
+        }
+    }
+    match settings.get("comp-lzo") {
+        Some("no-by-default") | Some("no") => lines.push(option("comp-lzo", &["no"])),
+        Some(mode) => lines.push(option("comp-lzo", &[mode])),
+        None => {},
+    }
+    match settings.get("compress") {
+        Some("yes") => lines.push(option("compress", &[])),
+        Some(algorithm) => lines.push(option("compress", &[algorithm])),
+        None => {},
+    }
+    match settings.get("mssfix") {
+        Some("yes") => lines.push(option("mssfix", &[])),
+        Some(max) => lines.push(option("mssfix", &[max])),
+        None => {},
+    }
+    if let (Some(proxy_type), Some(server)) = (settings.get("proxy-type"), settings.get("proxy-server")) {
+        let name = if proxy_type == "socks" { "socks-proxy" } else { "http-proxy" };
+        let port = settings.get("proxy-port").unwrap_or(if proxy_type == "socks" { "1080" } else { "8080" });
+        lines.push(option(name, &[server, port]));
+    }
+    if let Some(username) = settings.get("http-proxy-username") {
+        let password = settings.secrets.get("http-proxy-password").map_or("", |p| &p[..]);
+        lines.push(format!("<http-proxy-user-pass>\n{}\n{}\n</http-proxy-user-pass>", username, password));
+    }
+    if settings.ipv4.get("ignore-auto-routes").map(|v| &v[..]) == Some("true") {
+        lines.push(option("route-nopull", &[]));
+    }
+    let mut routes: Vec<(usize, &String)> = settings.ipv4.iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix("route")?.parse().ok()?, value)))
+        .collect();
+    routes.sort();
+    lines.extend(routes.into_iter().filter_map(|(_, route)| nm_route(route)));
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    parse(text.as_bytes()).expect("reading from a string can't fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, process};
+    use parse_str;
+
+    #[test]
+    fn test_to_network_manager() {
+        let dir = env::temp_dir().join(format!("ovpnfile-network-manager-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = parse_str(
+            "client\nremote a.example.com 443 tcp-client\nremote b.example.com\nproto tcp\nca /etc/openvpn/ca.crt\n\
+             <tls-auth>\nstatic key\n</tls-auth>\nkey-direction 1\n<auth-user-pass>\nbob\nsecret\n</auth-user-pass>\n\
+             cert client.crt\nkey client.key\n\
+             cipher AES-256-GCM\nroute 10.1.0.0 255.255.0.0\nroute-nopull\n<http-proxy-user-pass>\nuser\npass\n</http-proxy-user-pass>\n\
+             http-proxy proxy.example.com 3128\nsndbuf 0\n",
+        );
+        let settings = to_network_manager(&config, &dir).unwrap();
+        let ta = dir.join("tls-auth.key").to_string_lossy().into_owned();
+        let data: Vec<(&str, &str)> = settings.data.iter().map(|(k, v)| (&k[..], &v[..])).collect();
+        assert_eq!(data, vec![
+            ("ca", "/etc/openvpn/ca.crt"),
+            ("cert", "client.crt"),
+            ("cipher", "AES-256-GCM"),
+            ("connection-type", "password-tls"),
+            ("http-proxy-username", "user"),
+            ("key", "client.key"),
+            ("proto-tcp", "yes"),
+            ("proxy-port", "3128"),
+            ("proxy-server", "proxy.example.com"),
+            ("proxy-type", "http"),
+            ("remote", "a.example.com:443:tcp-client, b.example.com"),
+            ("ta", &ta[..]),
+            ("ta-dir", "1"),
+            ("username", "bob"),
+        ]);
+        assert_eq!(settings.secrets.get("http-proxy-password").map(|p| &p[..]), Some("pass"));
+        assert_eq!(settings.secrets.get("password").map(|p| &p[..]), Some("secret"));
+        assert_eq!(settings.ipv4.get("route1").map(|r| &r[..]), Some("10.1.0.0/16"));
+        assert_eq!(settings.ipv4.get("ignore-auto-routes").map(|r| &r[..]), Some("true"));
+        assert_eq!(settings.unsupported, vec![23]);
+        assert!(settings.to_keyfile("work").starts_with("[connection]\nid=work\ntype=vpn\n\n[vpn]\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_from_network_manager() {
+        let keyfile = "[connection]\nid=work\ntype=vpn\n\n[vpn]\nservice-type=org.freedesktop.NetworkManager.openvpn\n\
+                       connection-type=password-tls\nremote=a.example.com:443:tcp-client, b.example.com\nproto-tcp=yes\n\
+                       ca=/etc/openvpn/ca.crt\ncert=/etc/openvpn/client.crt\nkey=/etc/openvpn/client.key\nta=/etc/openvpn/ta key\n\
+                       ta-dir=1\ncipher=AES-256-GCM\ncomp-lzo=no-by-default\nverify-x509-name=name:vpn.example.com\n\
+                       proxy-type=http\nproxy-server=proxy.example.com\nproxy-port=3128\nhttp-proxy-username=user\nusername=bob\n\
+                       \n[vpn-secrets]\nhttp-proxy-password=pass\n\n[ipv4]\nmethod=auto\nroute1=10.1.0.0/16\nignore-auto-routes=true\n";
+        let settings = NetworkManagerSettings::from_keyfile(keyfile);
+        assert_eq!(settings.get("remote-random"), None);
+        let config = from_network_manager(&settings);
+        assert_eq!(config.warning_lines, vec![]);
+        let options: Vec<String> = config.directives().iter().map(|d| d.as_ovpn_config()).collect();
+        assert_eq!(options, vec![
+            "client",
+            "remote a.example.com 443 tcp-client",
+            "remote b.example.com",
+            "proto tcp-client",
+            "auth-user-pass",
+            "ca /etc/openvpn/ca.crt",
+            "cert /etc/openvpn/client.crt",
+            "key /etc/openvpn/client.key",
+            "tls-auth \"/etc/openvpn/ta key\" 1",
+            "cipher AES-256-GCM",
+            "verify-x509-name vpn.example.com name",
+            "comp-lzo no",
+            "http-proxy proxy.example.com 3128",
+            "<http-proxy-user-pass>\nuser\npass\n</http-proxy-user-pass>",
+            "route-nopull",
+            "route 10.1.0.0 255.255.0.0",
+        ]);
+    }
+}
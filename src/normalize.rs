@@ -0,0 +1,117 @@
+//! Putting a config into a canonical form, so that configs which mean the
+//! same thing serialize the same way
+use remotes::remote_endpoints;
+use {ConfigDirective, ParsedConfigFile};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// The directive with openvpn's defaults filled in for omitted arguments
+fn with_defaults(directive: &ConfigDirective) -> ConfigDirective {
+    match *directive {
+        ConfigDirective::ExplicitExitNotify{..} => {
+            ConfigDirective::ExplicitExitNotify{n: directive.explicit_exit_notify_count()}
+        },
+        ConfigDirective::SocksProxy{ref server, ref authfile, ..} => ConfigDirective::SocksProxy{
+            server: server.clone(),
+            port: directive.socks_proxy_port(),
+            authfile: authfile.clone(),
+        },
+        ConfigDirective::Status{ref file, ..} => ConfigDirective::Status{
+            file: file.clone(),
+            n: directive.status_interval().map(|interval| interval.as_secs()),
+        },
+        _ => directive.clone(),
+    }
+}
+
+impl ParsedConfigFile {
+    /// A canonical form of this config: only the last of each option which
+    /// can't be repeated is kept, `remote`s get an explicit port and
+    /// protocol, omitted arguments with a default are filled in and the
+    /// directives are sorted by option name. Repeatable options such as
+    /// `remote` and `route` keep their order relative to each other. Two
+    /// configs which mean the same thing normalize to the same directives
+    /// and so serialize identically with `as_ovpn_config`. Line numbers are
+    /// those of the original config, warnings are kept as they are.
+    pub fn normalize(&self) -> ParsedConfigFile {
+        let endpoints = remote_endpoints(self);
+        let mut success_lines: Vec<_> = self.success_lines.iter().enumerate().filter(|&(index, line)| {
+            // Keep the last of each option which can't be repeated
+            line.result.is_repeatable() || !self.success_lines[index + 1..].iter()
+                .any(|l| l.result.openvpn_option_name() == line.result.openvpn_option_name())
+        }).map(|(_, line)| {
+            let mut line = line.clone();
+            line.result = match line.result {
+                ConfigDirective::Remote{..} => match endpoints.iter().find(|&&(number, _)| number == line.number) {
+                    Some((_, endpoint)) => ConfigDirective::Remote{
+                        host: endpoint.host.clone(),
+                        port: Some(endpoint.port.clone()),
+                        proto: Some(endpoint.proto.clone()),
+                    },
+                    // A `remote` outside the config's `<connection>` blocks,
+                    // which openvpn ignores
+                    None => line.result.clone(),
+                },
+                ref directive => with_defaults(directive),
+            };
+            line
+        }).collect();
+        success_lines.sort_by(|a, b| a.result.openvpn_option_name().cmp(b.result.openvpn_option_name()));
+        ParsedConfigFile{success_lines, warning_lines: self.warning_lines.clone()}
+    }
+
+    /// A hash of what this config means, which is the same for configs that
+    /// normalize to the same directives whatever their comments, whitespace
+    /// or order and changes when an option that matters does. Lines which
+    /// didn't parse are ignored. The hash is FNV-1a of the normalized
+    /// config, so it is stable between runs and platforms.
+    pub fn fingerprint(&self) -> u64 {
+        self.normalize().success_lines.iter().fold(FNV_OFFSET_BASIS, |hash, line| {
+            line.result.as_ovpn_config().bytes().chain(Some(b'\n')).fold(hash, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+
+    fn normalized(contents: &str) -> Vec<String> {
+        let config = parse_str(contents);
+        config.normalize().directives().iter().map(|d| d.as_ovpn_config()).collect()
+    }
+
+    #[test]
+    fn test_normalize() {
+        let a = normalized("client\nremote a.example.com\nverb 3\nremote b.example.com 443 tcp\nverb  4\nexplicit-exit-notify\n");
+        let b = normalized("explicit-exit-notify 1\nverb 4\nclient\nremote A.example.com 1194 udp\nremote b.example.com 443 tcp-client\n");
+        assert_eq!(a, b);
+        assert_eq!(a, vec![
+            "client",
+            "explicit-exit-notify 1",
+            "remote a.example.com 1194 udp",
+            "remote b.example.com 443 tcp-client",
+            "verb 4",
+        ]);
+    }
+
+    #[test]
+    fn test_normalize_keeps_repeated_verify_hashes() {
+        let hashes = "verify-hash 00:11:22:33:44:55:66:77:88:99:aa:bb:cc:dd:ee:ff:00:11:22:33\n\
+                      verify-hash ff:ee:dd:cc:bb:aa:99:88:77:66:55:44:33:22:11:00:ff:ee:dd:cc\n";
+        assert_eq!(normalized(hashes).len(), 2);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let fingerprint = |contents: &str| parse_str(contents).fingerprint();
+        let a = fingerprint("# a comment\nclient\nremote a.example.com\nverb 3\n");
+        assert_eq!(a, fingerprint("verb   3\nremote a.example.com 1194 udp\nclient\n"));
+        assert_ne!(a, fingerprint("client\nremote a.example.com\nverb 4\n"));
+        assert_eq!(fingerprint(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint("client\n"), 0x6a2a_0328_8050_513c);
+    }
+}
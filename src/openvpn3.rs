@@ -0,0 +1,113 @@
+//! Exporting profiles for OpenVPN 3 clients, such as OpenVPN Connect and
+//! openvpn3-linux, which reject or ignore a number of openvpn 2 options
+use {ConfigDirective, ConfigLine, ParsedConfigFile};
+
+/// Options OpenVPN 3 doesn't support: scripts, plugins, daemon and logging
+/// options which the client manages itself, platform specific device
+/// options, static key mode and fragmentation
+const UNSUPPORTED_OPTIONS: &[&str] = &[
+    "up", "down", "down-pre", "route-up", "route-pre-down", "ipchange", "tls-verify", "learn-address",
+    "script-security", "plugin", "user", "group", "cd", "chroot", "daemon", "log", "log-append", "writepid",
+    "status", "status-version", "syslog", "management", "management-hold", "management-query-passwords",
+    "management-client", "askpass", "capath", "cryptoapicert", "dev-node", "lladdr", "ifconfig-noexec",
+    "route-noexec", "client-nat", "mlock", "fragment", "secret", "service", "win-sys", "register-dns",
+];
+
+/// How a line was changed by `openvpn3_export`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Openvpn3Change {
+    /// The line was removed as OpenVPN 3 doesn't support it
+    Dropped(ConfigLine<ConfigDirective>),
+    /// The line was replaced by the equivalent OpenVPN 3 option
+    Rewritten{original: ConfigLine<ConfigDirective>, replacement: ConfigDirective},
+}
+
+/// The result of `openvpn3_export`
+pub struct Openvpn3Export {
+    /// The profile restricted to the options OpenVPN 3 supports. Rewritten
+    /// lines keep their line numbers.
+    pub config: ParsedConfigFile,
+    /// The lines which were dropped or rewritten, in the order they appear
+    pub changes: Vec<Openvpn3Change>,
+}
+
+/// The OpenVPN 3 equivalent of a directive it doesn't accept
+fn rewritten(directive: &ConfigDirective) -> Option<ConfigDirective> {
+    match *directive {
+        ConfigDirective::NcpCiphers{ref cipher_list} => Some(ConfigDirective::DataCiphers{cipher_list: cipher_list.clone()}),
+        ConfigDirective::NsCertType{ref client_or_server} => {
+            Some(ConfigDirective::RemoteCertTls{client_or_server: client_or_server.clone()})
+        },
+        _ => None,
+    }
+}
+
+/// Whether OpenVPN 3 rejects or ignores the directive, it only supports
+/// tun devices so the device options of a config with a tap device are
+/// unsupported too
+fn unsupported(directive: &ConfigDirective, tap: bool) -> bool {
+    match *directive {
+        ConfigDirective::Dev{..} | ConfigDirective::DevType{..} => tap,
+        _ => UNSUPPORTED_OPTIONS.contains(&directive.openvpn_option_name()),
+    }
+}
+
+impl ParsedConfigFile {
+    /// This profile restricted to the options OpenVPN 3 clients support,
+    /// with a list of the lines which were dropped or rewritten. Unsupported
+    /// options are dropped and options OpenVPN 3 only knows by a newer name,
+    /// such as `ncp-ciphers` and `ns-cert-type`, are rewritten. OpenVPN
+    /// Connect also only reads inline certificates and keys, which
+    /// `ParsedConfigFile::inlined` takes care of.
+    pub fn openvpn3_export(&self) -> Openvpn3Export {
+        let mut success_lines = Vec::new();
+        let mut changes = Vec::new();
+        let tap = self.is_tap();
+        for line in self.success_lines.iter() {
+            if unsupported(&line.result, tap) {
+                changes.push(Openvpn3Change::Dropped(line.clone()));
+            } else if let Some(replacement) = rewritten(&line.result) {
+                success_lines.push(ConfigLine{number: line.number, result: replacement.clone()});
+                changes.push(Openvpn3Change::Rewritten{original: line.clone(), replacement});
+            } else {
+                success_lines.push(line.clone());
+            }
+        }
+        Openvpn3Export{
+            config: ParsedConfigFile{success_lines, warning_lines: self.warning_lines.clone()},
+            changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_openvpn3_export() {
+        let contents = "client\ndev tun\nremote vpn.example.com 1194\nncp-ciphers AES-256-GCM\nns-cert-type server\n\
+                        script-security 2\nup /etc/openvpn/update-resolv-conf\nuser nobody\nfragment 1300\n";
+        let config = parse_str(contents);
+        let export = config.openvpn3_export();
+        let options: Vec<String> = export.config.directives().iter().map(|d| d.as_ovpn_config()).collect();
+        assert_eq!(options, vec![
+            "client",
+            "dev tun",
+            "remote vpn.example.com 1194",
+            "data-ciphers AES-256-GCM",
+            "remote-cert-tls server",
+        ]);
+        let changed: Vec<(i32, bool)> = export.changes.iter().map(|change| match *change {
+            Openvpn3Change::Dropped(ref line) => (line.number, false),
+            Openvpn3Change::Rewritten{ref original, ..} => (original.number, true),
+        }).collect();
+        assert_eq!(changed, vec![(3, true), (4, true), (5, false), (6, false), (7, false), (8, false)]);
+
+        let config = parse_str("dev tap0\n");
+        assert_eq!(config.openvpn3_export().changes, vec![
+            Openvpn3Change::Dropped(ConfigLine{number: 0, result: ConfigDirective::Dev{devarg: "tap0".to_string()}}),
+        ]);
+    }
+}
@@ -0,0 +1,190 @@
+//! The certificates and keys on a PKCS#11 token selected by `pkcs11-id`
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use ConfigDirective;
+
+/// The object a `pkcs11-id` selects. This is either the serialized form
+/// `--show-pkcs11-ids` prints,
+///
+/// ```text
+/// manufacturer/model/serial/token label/hex id
+/// ```
+///
+/// with characters escaped as `\xHH`, or an RFC 7512 `pkcs11:` URI. The
+/// object's label and type can only be given in a URI.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Pkcs11Id {
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    /// The token's label
+    pub token: Option<String>,
+    /// The object's label
+    pub object: Option<String>,
+    /// The object's `CKA_ID`
+    pub id: Option<Vec<u8>>,
+    /// The object's type, e.g `cert` or `private`
+    pub object_type: Option<String>,
+}
+
+/// The reasons a `pkcs11-id` can't be parsed
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Pkcs11IdError {
+    /// A serialized id has the given number of components rather than 5
+    WrongComponentCount(usize),
+    /// A `\x` or `%` escape isn't followed by two hex digits
+    InvalidEscape,
+    /// The id isn't hex digits
+    InvalidId,
+}
+
+impl fmt::Display for Pkcs11IdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Pkcs11IdError::WrongComponentCount(count) => write!(f, "the id has {} components rather than 5", count),
+            Pkcs11IdError::InvalidEscape => f.write_str("the id contains an invalid escape"),
+            Pkcs11IdError::InvalidId => f.write_str("the object id isn't hex digits"),
+        }
+    }
+}
+
+impl Error for Pkcs11IdError {}
+
+/// `value` with each escape, `escape` followed by two hex digits, replaced
+/// by the byte it encodes
+fn unescape(value: &str, escape: &str) -> Result<Vec<u8>, Pkcs11IdError> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(escape) {
+        bytes.extend_from_slice(&rest.as_bytes()[..start]);
+        let hex = rest[start + escape.len()..].get(..2).ok_or(Pkcs11IdError::InvalidEscape)?;
+        bytes.push(hex_byte(hex).ok_or(Pkcs11IdError::InvalidEscape)?);
+        rest = &rest[start + escape.len() + 2..];
+    }
+    bytes.extend_from_slice(rest.as_bytes());
+    Ok(bytes)
+}
+
+fn hex_byte(hex: &str) -> Option<u8> {
+    if hex.len() == 2 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        u8::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    }
+}
+
+fn unescape_string(value: &str, escape: &str) -> Result<Option<String>, Pkcs11IdError> {
+    let bytes = unescape(value, escape)?;
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()).filter(|s| !s.is_empty()))
+}
+
+impl Pkcs11Id {
+    fn from_uri(uri: &str) -> Result<Pkcs11Id, Pkcs11IdError> {
+        let mut result = Pkcs11Id::default();
+        // Query attributes, such as the PIN, don't identify the object
+        let path = uri.split('?').next().unwrap_or("");
+        for attribute in path.split(';').filter(|a| !a.is_empty()) {
+            let mut parts = attribute.splitn(2, '=');
+            let name = parts.next().unwrap_or("");
+            let value = parts.next().unwrap_or("");
+            match name {
+                "manufacturer" => result.manufacturer = unescape_string(value, "%")?,
+                "model" => result.model = unescape_string(value, "%")?,
+                "serial" => result.serial = unescape_string(value, "%")?,
+                "token" => result.token = unescape_string(value, "%")?,
+                "object" => result.object = unescape_string(value, "%")?,
+                "id" => result.id = Some(unescape(value, "%")?),
+                "type" => result.object_type = unescape_string(value, "%")?,
+                _ => {},
+            }
+        }
+        Ok(result)
+    }
+
+    fn from_serialized(id: &str) -> Result<Pkcs11Id, Pkcs11IdError> {
+        let components: Vec<&str> = id.split('/').collect();
+        if components.len() != 5 {
+            return Err(Pkcs11IdError::WrongComponentCount(components.len()));
+        }
+        let object_id = unescape(components[4], "\\x")?;
+        let object_id = String::from_utf8(object_id).map_err(|_| Pkcs11IdError::InvalidId)?;
+        if object_id.len() % 2 != 0 {
+            return Err(Pkcs11IdError::InvalidId);
+        }
+        let object_id = (0..object_id.len()).step_by(2)
+            .map(|i| object_id.get(i..i + 2).and_then(hex_byte).ok_or(Pkcs11IdError::InvalidId))
+            .collect::<Result<Vec<u8>, Pkcs11IdError>>()?;
+        Ok(Pkcs11Id{
+            manufacturer: unescape_string(components[0], "\\x")?,
+            model: unescape_string(components[1], "\\x")?,
+            serial: unescape_string(components[2], "\\x")?,
+            token: unescape_string(components[3], "\\x")?,
+            object: None,
+            id: Some(object_id),
+            object_type: None,
+        })
+    }
+}
+
+impl FromStr for Pkcs11Id {
+    type Err = Pkcs11IdError;
+    fn from_str(s: &str) -> Result<Pkcs11Id, Pkcs11IdError> {
+        match s.strip_prefix("pkcs11:") {
+            Some(uri) => Pkcs11Id::from_uri(uri),
+            None => Pkcs11Id::from_serialized(s),
+        }
+    }
+}
+
+impl ConfigDirective {
+    /// The object a `pkcs11-id` directive selects. Returns `None` for any
+    /// other directive.
+    pub fn pkcs11_id(&self) -> Option<Result<Pkcs11Id, Pkcs11IdError>> {
+        match *self {
+            ConfigDirective::Pkcs11Id{ref name} => Some(name.parse()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_pkcs11_id() {
+        let directive = ConfigDirective::Pkcs11Id{
+            name: "piv_II/PKCS\\x2315\\x20emulated/1234567890abcdef/PIV_II\\x20\\x28PIV\\x20Card\\x29/01a2".to_string(),
+        };
+        assert_eq!(directive.pkcs11_id(), Some(Ok(Pkcs11Id{
+            manufacturer: Some("piv_II".to_string()),
+            model: Some("PKCS#15 emulated".to_string()),
+            serial: Some("1234567890abcdef".to_string()),
+            token: Some("PIV_II (PIV Card)".to_string()),
+            object: None,
+            id: Some(vec![0x01, 0xa2]),
+            object_type: None,
+        })));
+        assert_eq!(ConfigDirective::Client.pkcs11_id(), None);
+
+        assert_eq!("a/b/c".parse::<Pkcs11Id>(), Err(Pkcs11IdError::WrongComponentCount(3)));
+        assert_eq!("a/b/c/d\\x2/01".parse::<Pkcs11Id>(), Err(Pkcs11IdError::InvalidEscape));
+        assert_eq!("a/b/c/d/0g".parse::<Pkcs11Id>(), Err(Pkcs11IdError::InvalidId));
+    }
+
+    #[test]
+    fn test_pkcs11_uri() {
+        let id: Pkcs11Id = "pkcs11:token=My%20Token;serial=42;id=%01%02;object=my-key;type=private?pin-value=1234"
+            .parse().unwrap();
+        assert_eq!(id, Pkcs11Id{
+            serial: Some("42".to_string()),
+            token: Some("My Token".to_string()),
+            object: Some("my-key".to_string()),
+            id: Some(vec![1, 2]),
+            object_type: Some("private".to_string()),
+            ..Pkcs11Id::default()
+        });
+        assert_eq!("pkcs11:token=%zz".parse::<Pkcs11Id>(), Err(Pkcs11IdError::InvalidEscape));
+    }
+}
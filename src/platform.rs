@@ -0,0 +1,121 @@
+//! Finding the options of a profile which won't work on a given platform
+use {ConfigDirective, ParsedConfigFile};
+
+/// Options which only work on Windows, mostly configuring its tap driver
+/// and DHCP client
+const WINDOWS_ONLY_OPTIONS: &[&str] = &[
+    "ip-win32", "route-method", "tap-sleep", "show-net-up", "block-outside-dns", "windows-driver", "dhcp-renew",
+    "dhcp-release", "dhcp-pre-release", "register-dns", "pause-exit", "msg-channel", "service", "show-adapters",
+    "allow-nonadmin", "show-valid-subnets", "show-net", "win-sys", "cryptoapicert",
+];
+
+/// Options which only work on Linux
+const LINUX_ONLY_OPTIONS: &[&str] = &["iproute", "mark", "mktun", "rmtun"];
+
+/// Options which need a unix system, to drop privileges, lock memory or
+/// run as a daemon
+const UNIX_ONLY_OPTIONS: &[&str] = &["daemon", "user", "group", "chroot", "syslog", "mlock", "lladdr"];
+
+/// Options Android's VPN clients don't support, as they can't run scripts
+/// or plugins, and set up the device and manage openvpn themselves
+const ANDROID_UNSUPPORTED_OPTIONS: &[&str] = &[
+    "up", "down", "down-pre", "route-up", "route-pre-down", "ipchange", "learn-address", "tls-verify",
+    "script-security", "plugin", "management", "log", "log-append", "writepid",
+];
+
+/// A platform openvpn runs on
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Platform {
+    Linux,
+    Windows,
+    MacOs,
+    Android,
+}
+
+/// Why an option doesn't work on a platform
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PortabilityReason {
+    /// The option only works on Windows
+    WindowsOnly,
+    /// The option only works on Linux
+    LinuxOnly,
+    /// The option needs a unix system
+    UnixOnly,
+    /// The platform only supports tun devices
+    TapUnsupported,
+    /// The option isn't supported by the platform's clients
+    Unsupported,
+}
+
+/// An option which won't work on a platform
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PortabilityIssue {
+    pub option: &'static str,
+    pub line: i32,
+    pub reason: PortabilityReason,
+}
+
+/// Why `directive` doesn't work on `platform`, if it doesn't, `tap` being
+/// whether the config uses a tap device
+fn portability_reason(directive: &ConfigDirective, platform: Platform, tap: bool) -> Option<PortabilityReason> {
+    let name = directive.openvpn_option_name();
+    if WINDOWS_ONLY_OPTIONS.contains(&name) && platform != Platform::Windows {
+        return Some(PortabilityReason::WindowsOnly);
+    }
+    if LINUX_ONLY_OPTIONS.contains(&name) && platform != Platform::Linux {
+        return Some(PortabilityReason::LinuxOnly);
+    }
+    if platform == Platform::Windows && UNIX_ONLY_OPTIONS.contains(&name) {
+        return Some(PortabilityReason::UnixOnly);
+    }
+    if platform == Platform::Android {
+        if tap && (name == "dev" || name == "dev-type") {
+            return Some(PortabilityReason::TapUnsupported);
+        }
+        if UNIX_ONLY_OPTIONS.contains(&name) || ANDROID_UNSUPPORTED_OPTIONS.contains(&name) {
+            return Some(PortabilityReason::Unsupported);
+        }
+    }
+    None
+}
+
+impl ParsedConfigFile {
+    /// The options in this profile which are unsupported or meaningless on
+    /// `platform`, such as `block-outside-dns` anywhere but Windows or
+    /// `user` on Windows, in the order they appear
+    pub fn portability_issues(&self, platform: Platform) -> Vec<PortabilityIssue> {
+        let tap = self.is_tap();
+        self.success_lines.iter().filter_map(|line| {
+            portability_reason(&line.result, platform, tap).map(|reason| PortabilityIssue{
+                option: line.result.openvpn_option_name(),
+                line: line.number,
+                reason,
+            })
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_portability_issues() {
+        let contents = "client\ndev tap\nblock-outside-dns\nuser nobody\niproute /sbin/ip\nup up.sh\n";
+        let config = parse_str(contents);
+        let issues = |platform| -> Vec<(i32, PortabilityReason)> {
+            config.portability_issues(platform).into_iter().map(|i| (i.line, i.reason)).collect()
+        };
+        assert_eq!(issues(Platform::Linux), vec![(2, PortabilityReason::WindowsOnly)]);
+        assert_eq!(issues(Platform::Windows), vec![(3, PortabilityReason::UnixOnly), (4, PortabilityReason::LinuxOnly)]);
+        assert_eq!(issues(Platform::MacOs), vec![(2, PortabilityReason::WindowsOnly), (4, PortabilityReason::LinuxOnly)]);
+        assert_eq!(issues(Platform::Android), vec![
+            (1, PortabilityReason::TapUnsupported),
+            (2, PortabilityReason::WindowsOnly),
+            (3, PortabilityReason::Unsupported),
+            (4, PortabilityReason::LinuxOnly),
+            (5, PortabilityReason::Unsupported),
+        ]);
+    }
+}
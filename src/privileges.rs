@@ -0,0 +1,139 @@
+//! What a config needs from the system openvpn runs on, so that deployment
+//! tools can check an environment before starting it
+use std::net::IpAddr;
+use {ConfigDirective, ParsedConfigFile, WindowsDriver};
+
+/// Options which create or configure a network interface or routes, and so
+/// need root or `CAP_NET_ADMIN` on Linux
+const NET_ADMIN_OPTIONS: &[&str] = &[
+    "dev", "mktun", "rmtun", "lladdr", "ifconfig", "ifconfig-ipv6", "route", "route-ipv6", "redirect-gateway",
+    "redirect-private", "server", "server-ipv6", "tun-mtu", "mark",
+];
+
+/// The management interface a config opens
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ManagementInterface {
+    pub line: i32,
+    /// The address it listens on, or the path of the socket for a unix
+    /// socket
+    pub address: String,
+    /// The port it listens on, `None` for a unix socket
+    pub port: Option<String>,
+    /// Whether it listens on an address other hosts can connect to
+    pub remote_access: bool,
+    /// Whether clients have to give a password
+    pub password_protected: bool,
+}
+
+/// What a config needs at runtime
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Privileges {
+    /// The lines of options which create the tun or tap device or set its
+    /// addresses and routes, and so need openvpn to start as root or with
+    /// `CAP_NET_ADMIN` on Linux
+    pub net_admin: Vec<i32>,
+    /// The user openvpn switches to once it's set up, from `user`
+    pub user: Option<String>,
+    /// The group openvpn switches to once it's set up, from `group`
+    pub group: Option<String>,
+    /// The directory openvpn `chroot`s into, which needs root or
+    /// `CAP_SYS_CHROOT`
+    pub chroot: Option<String>,
+    /// The driver the device needs on Windows: the one given by
+    /// `windows-driver`, or tap-windows6 for a tap device. `None` if the
+    /// default for a tun device is used, which depends on the version.
+    pub windows_driver: Option<WindowsDriver>,
+    /// Whether `mlock` is set, which needs enough `RLIMIT_MEMLOCK` or
+    /// `CAP_IPC_LOCK` to lock openvpn's memory
+    pub mlock: bool,
+    /// The management interface, if any
+    pub management: Option<ManagementInterface>,
+}
+
+impl Privileges {
+    /// Whether openvpn has to be started as root or with `CAP_NET_ADMIN`
+    pub fn needs_net_admin(&self) -> bool {
+        !self.net_admin.is_empty()
+    }
+}
+
+/// Whether a management interface listening on `address` can only be
+/// reached from the same host
+fn is_loopback(address: &str) -> bool {
+    address == "localhost" || address.parse::<IpAddr>().map(|a| a.is_loopback()).unwrap_or(false)
+}
+
+impl ParsedConfigFile {
+    /// What this config needs at runtime: the privileges to set up the
+    /// device, the user and group it drops to, the Windows driver, locked
+    /// memory and the management interface it exposes. Pushed options may
+    /// add routes but need the same privileges as the device.
+    pub fn privileges(&self) -> Privileges {
+        let mut privileges = Privileges::default();
+        for line in self.success_lines.iter() {
+            match line.result {
+                ConfigDirective::User{ref user} => privileges.user = Some(user.clone()),
+                ConfigDirective::Group{ref group} => privileges.group = Some(group.clone()),
+                ConfigDirective::Chroot{ref dir} => privileges.chroot = Some(dir.clone()),
+                ConfigDirective::WindowsDriver{driver} => privileges.windows_driver = Some(driver),
+                ConfigDirective::Mlock => privileges.mlock = true,
+                ConfigDirective::Management{ref ip, ref port, ref pw_file} => {
+                    let unix_socket = port == "unix";
+                    privileges.management = Some(ManagementInterface{
+                        line: line.number,
+                        address: ip.clone(),
+                        port: if unix_socket { None } else { Some(port.clone()) },
+                        remote_access: !unix_socket && !is_loopback(ip),
+                        password_protected: pw_file.is_some(),
+                    });
+                },
+                _ => {},
+            }
+            if NET_ADMIN_OPTIONS.contains(&line.result.openvpn_option_name()) {
+                privileges.net_admin.push(line.number);
+            }
+        }
+        if self.is_tap() && privileges.windows_driver.is_none() {
+            privileges.windows_driver = Some(WindowsDriver::TapWindows6);
+        }
+        privileges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_privileges() {
+        let contents = "dev tap0\nremote vpn.example.com\nroute 10.0.0.0 255.0.0.0\nuser nobody\ngroup nogroup\n\
+                        mlock\nmanagement 0.0.0.0 7505\n";
+        let config = parse_str(contents);
+        let privileges = config.privileges();
+        assert_eq!(privileges, Privileges{
+            net_admin: vec![0, 2],
+            user: Some("nobody".to_string()),
+            group: Some("nogroup".to_string()),
+            chroot: None,
+            windows_driver: Some(WindowsDriver::TapWindows6),
+            mlock: true,
+            management: Some(ManagementInterface{
+                line: 6,
+                address: "0.0.0.0".to_string(),
+                port: Some("7505".to_string()),
+                remote_access: true,
+                password_protected: false,
+            }),
+        });
+        assert!(privileges.needs_net_admin());
+
+        let contents = "dev tun\nwindows-driver wintun\nmanagement /run/openvpn.sock unix pw.txt\n";
+        let privileges = parse_str(contents).privileges();
+        assert_eq!(privileges.windows_driver, Some(WindowsDriver::Wintun));
+        let management = privileges.management.unwrap();
+        assert_eq!(management.port, None);
+        assert!(!management.remote_access);
+        assert!(management.password_protected);
+    }
+}
@@ -0,0 +1,218 @@
+//! Parsing the options a server pushes and working out a client's
+//! effective config once they are applied
+use config_directive::parse_option;
+use merge::merge;
+use {ConfigDirective, ConfigLine, ParseWarning, ParsedConfigFile, Role};
+
+/// Pushed options which `route-nopull` makes the client ignore, those in
+/// openvpn's route and DHCP/DNS option classes
+const ROUTE_NOPULL_OPTIONS: &[&str] = &[
+    "route", "route-ipv6", "redirect-gateway", "redirect-private", "client-nat", "block-outside-dns", "dhcp-option",
+    "dns", "register-dns", "ip-win32", "tap-sleep", "dhcp-renew", "dhcp-release", "dhcp-pre-release",
+];
+
+/// Why a pushed option was ignored
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum IgnoreReason {
+    /// The client config doesn't pull options, it has no `client` or `pull`
+    NotPulling,
+    /// The option can't be parsed
+    Invalid,
+    /// The option can't be pushed
+    NotPushable,
+    /// A `pull-filter ignore` on this line matched the option
+    PullFilter(i32),
+    /// The option is a route, redirect or DNS setting and `route-nopull` is
+    /// on this line
+    RouteNopull(i32),
+}
+
+/// What happened to a pushed option
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum PushOutcome {
+    /// The option was applied, replacing these lines of the client config
+    Applied(Vec<ConfigLine<ConfigDirective>>),
+    Ignored(IgnoreReason),
+    /// A `pull-filter reject` on this line matched the option, openvpn will
+    /// drop the connection
+    Rejected(i32),
+}
+
+/// A pushed option and what happened to it
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PushChange {
+    /// The option as it was pushed, with runs of whitespace collapsed to a
+    /// single space, which is what `pull-filter`s are matched against
+    pub option: String,
+    pub directive: Result<ConfigDirective, ParseWarning>,
+    pub outcome: PushOutcome,
+}
+
+/// The result of `apply_pushed`
+pub struct EffectiveConfig {
+    /// The client config's lines which are still in effect followed by the
+    /// pushed options which were applied, numbered by their position in the
+    /// pushed options
+    pub config: ParsedConfigFile,
+    /// What happened to each pushed option, in the order they were pushed
+    pub changes: Vec<PushChange>,
+}
+
+/// Parse the options in a `PUSH_REPLY` message, as logged by openvpn and
+/// shown on the management interface, e.g
+/// `PUSH_REPLY,route 10.0.0.0 255.0.0.0,dhcp-option DNS 10.8.0.1`. The
+/// `PUSH_REPLY` prefix is optional. Options are numbered by their position
+/// in the message.
+pub fn parse_push_reply(reply: &str) -> ParsedConfigFile {
+    let mut success_lines = Vec::new();
+    let mut warning_lines = Vec::new();
+    for (index, option) in push_reply_options(reply).into_iter().enumerate() {
+        let number = index as i32;
+        match parse_option(option) {
+            Ok(result) => success_lines.push(ConfigLine{number, result}),
+            Err(result) => warning_lines.push(ConfigLine{number, result}),
+        }
+    }
+    ParsedConfigFile{success_lines, warning_lines}
+}
+
+/// The options in a `PUSH_REPLY` message without parsing them, as
+/// `apply_pushed` takes them
+pub fn push_reply_options(reply: &str) -> Vec<&str> {
+    reply.trim().trim_start_matches("PUSH_REPLY").split(',').map(str::trim).filter(|o| !o.is_empty()).collect()
+}
+
+/// `option` with runs of whitespace collapsed to a single space, as openvpn
+/// has it when it matches `pull-filter`s
+fn normalize_option(option: &str) -> String {
+    option.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The outcome of the first `pull-filter` which matches `option`, `None` if
+/// none match or the matching filter accepts it
+fn pull_filter(config: &ParsedConfigFile, option: &str) -> Option<PushOutcome> {
+    config.success_lines.iter().find_map(|line| match line.result {
+        ConfigDirective::PullFilter{ref accept_or_ignore_or_reject, ref text} if option.starts_with(&text[..]) => {
+            Some(match accept_or_ignore_or_reject.as_ref() {
+                "ignore" => Some(PushOutcome::Ignored(IgnoreReason::PullFilter(line.number))),
+                "reject" => Some(PushOutcome::Rejected(line.number)),
+                _ => None,
+            })
+        },
+        _ => None,
+    }).flatten()
+}
+
+/// Apply the options a server pushed to a client config, honouring the
+/// client's `pull-filter`s and `route-nopull`, as openvpn would when it
+/// connects. The options are given as they were pushed, e.g from
+/// `push_reply_options`, as `pull-filter`s match their text before they
+/// are parsed.
+pub fn apply_pushed<S: AsRef<str>>(config: &ParsedConfigFile, pushed: &[S]) -> EffectiveConfig {
+    let pulling = config.role() == Role::Client;
+    let route_nopull = config.success_lines.iter()
+        .find(|l| l.result == ConfigDirective::RouteNopull)
+        .map(|l| l.number);
+    let mut applied = Vec::new();
+    let mut changes: Vec<PushChange> = pushed.iter().enumerate().map(|(index, option)| {
+        let option = normalize_option(option.as_ref());
+        let directive = parse_option(&option);
+        let outcome = if !pulling {
+            PushOutcome::Ignored(IgnoreReason::NotPulling)
+        } else if let Some(outcome) = pull_filter(config, &option) {
+            outcome
+        } else {
+            match directive {
+                Err(_) => PushOutcome::Ignored(IgnoreReason::Invalid),
+                Ok(ref directive) if !directive.is_pushable() => PushOutcome::Ignored(IgnoreReason::NotPushable),
+                Ok(ref directive) => match route_nopull {
+                    Some(line) if ROUTE_NOPULL_OPTIONS.contains(&directive.openvpn_option_name()) => {
+                        PushOutcome::Ignored(IgnoreReason::RouteNopull(line))
+                    },
+                    _ => {
+                        applied.push(ConfigLine{number: index as i32, result: directive.clone()});
+                        PushOutcome::Applied(Vec::new())
+                    },
+                },
+            }
+        };
+        PushChange{option, directive, outcome}
+    }).collect();
+
+    let merged = merge(config, &ParsedConfigFile{success_lines: applied, warning_lines: Vec::new()});
+    for replacement in merged.replaced {
+        if let PushOutcome::Applied(ref mut replaced) = changes[replacement.by.number as usize].outcome {
+            replaced.push(replacement.base);
+        }
+    }
+    let config = ParsedConfigFile{
+        success_lines: merged.config.success_lines,
+        warning_lines: config.warning_lines.clone(),
+    };
+    EffectiveConfig{config, changes}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_apply_pushed() {
+        let config = parse_str(
+            "client\nremote somehost\nping 10\npull-filter ignore \"route 10.2.\"\npull-filter reject \"redirect-gateway\"\n",
+        );
+        let effective = apply_pushed(&config, &[
+            "route 10.1.0.0 255.255.0.0",
+            "route  10.2.0.0\t255.255.0.0",
+            "ping 5",
+            "redirect-gateway def1",
+            "tls-auth ta.key",
+            "not-an-option",
+        ]);
+        let outcomes: Vec<PushOutcome> = effective.changes.iter().map(|c| c.outcome.clone()).collect();
+        assert_eq!(outcomes, vec![
+            PushOutcome::Applied(Vec::new()),
+            PushOutcome::Ignored(IgnoreReason::PullFilter(3)),
+            PushOutcome::Applied(vec![ConfigLine{number: 2, result: ConfigDirective::Ping{n: "10".to_string()}}]),
+            PushOutcome::Rejected(4),
+            PushOutcome::Ignored(IgnoreReason::NotPushable),
+            PushOutcome::Ignored(IgnoreReason::Invalid),
+        ]);
+        assert_eq!(effective.changes[1].option, "route 10.2.0.0 255.255.0.0");
+        assert_eq!(effective.changes[5].directive, Err(ParseWarning::NoMatchingCommand));
+        let options: Vec<&str> = effective.config.success_lines.iter().map(|l| l.result.openvpn_option_name()).collect();
+        assert_eq!(options, vec!["client", "remote", "pull-filter", "pull-filter", "route", "ping"]);
+    }
+
+    #[test]
+    fn test_parse_push_reply() {
+        let reply = parse_push_reply("PUSH_REPLY,route 10.0.0.0 255.0.0.0,dhcp-option DNS 10.8.0.1,not-an-option,ping 10,");
+        assert_eq!(reply.directives(), vec![
+            ConfigDirective::Route{
+                network_or_ip: "10.0.0.0".to_string(),
+                netmask: Some("255.0.0.0".to_string()),
+                gateway: None,
+                metric: None,
+            },
+            ConfigDirective::DhcpOption{dhcp_option_type: "DNS".to_string(), parm: Some("10.8.0.1".to_string())},
+            ConfigDirective::Ping{n: "10".to_string()},
+        ]);
+        assert_eq!(reply.success_lines[2].number, 3);
+        assert_eq!(reply.warning_lines, vec![ConfigLine{number: 2, result: ParseWarning::NoMatchingCommand}]);
+        assert_eq!(push_reply_options("PUSH_REPLY,route 10.0.0.0 255.0.0.0,ping 10"), vec!["route 10.0.0.0 255.0.0.0", "ping 10"]);
+    }
+
+    #[test]
+    fn test_route_nopull() {
+        let config = parse_str("client\nroute-nopull\n");
+        let effective = apply_pushed(&config, &["route 10.1.0.0 255.255.0.0", "redirect-gateway def1", "ping 5"]);
+        assert_eq!(effective.changes[0].outcome, PushOutcome::Ignored(IgnoreReason::RouteNopull(1)));
+        assert_eq!(effective.changes[1].outcome, PushOutcome::Ignored(IgnoreReason::RouteNopull(1)));
+        assert_eq!(effective.changes[2].outcome, PushOutcome::Applied(Vec::new()));
+
+        let config = parse_str("secret static.key\n");
+        let effective = apply_pushed(&config, &["ping 5"]);
+        assert_eq!(effective.changes[0].outcome, PushOutcome::Ignored(IgnoreReason::NotPulling));
+    }
+}
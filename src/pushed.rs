@@ -0,0 +1,141 @@
+//! A typed summary of the network settings a server pushes, which is what
+//! integrations with a system's network and DNS configuration need
+use std::net::IpAddr;
+use network::dns_address;
+use {ConfigDirective, DnsDirective, DnsServerOption, Ipv4Network, Ipv6Network, ParsedConfigFile};
+
+/// A pushed `route` or `route-ipv6`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PushedRoute<N> {
+    pub network: N,
+    /// The gateway, an address or one of openvpn's keywords such as
+    /// `vpn_gateway`, `None` to use the default
+    pub gateway: Option<String>,
+    pub metric: Option<u32>,
+}
+
+/// The network settings pushed to a client
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct PushedSettings {
+    pub routes: Vec<PushedRoute<Ipv4Network>>,
+    pub ipv6_routes: Vec<PushedRoute<Ipv6Network>>,
+    /// The gateway of routes which don't give one, from `route-gateway`
+    pub route_gateway: Option<String>,
+    /// DNS servers from `dhcp-option DNS`, `dhcp-option DNS6` and `dns
+    /// server n address`, in the order they were pushed
+    pub dns_servers: Vec<IpAddr>,
+    /// Search domains from `dhcp-option DOMAIN`, `dhcp-option
+    /// DOMAIN-SEARCH` and `dns search-domains`
+    pub search_domains: Vec<String>,
+    /// The flags of `redirect-gateway`, `None` if it isn't pushed
+    pub redirect_gateway_flags: Option<Vec<String>>,
+    /// Whether `block-outside-dns` is pushed
+    pub block_outside_dns: bool,
+}
+
+fn push_unique<T: PartialEq>(list: &mut Vec<T>, value: T) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+impl PushedSettings {
+    /// The settings given by pushed directives, such as those from
+    /// `parse_push_reply`. Directives which aren't network settings, and
+    /// routes to hostnames, are skipped.
+    pub fn from_directives(directives: &[ConfigDirective]) -> PushedSettings {
+        let mut settings = PushedSettings::default();
+        for directive in directives.iter() {
+            match *directive {
+                ConfigDirective::Route{ref network_or_ip, ref netmask, ref gateway, ref metric} => {
+                    if let Some(network) = Ipv4Network::from_route(network_or_ip, netmask.as_deref()) {
+                        settings.routes.push(PushedRoute{
+                            network,
+                            gateway: gateway.clone(),
+                            metric: metric.as_ref().and_then(|m| m.parse().ok()),
+                        });
+                    }
+                },
+                ConfigDirective::RouteIpv6{ref ipv6addr, ref gateway, ref metric} => {
+                    if let Ok(network) = ipv6addr.parse() {
+                        settings.ipv6_routes.push(PushedRoute{
+                            network,
+                            gateway: gateway.clone(),
+                            metric: metric.as_ref().and_then(|m| m.parse().ok()),
+                        });
+                    }
+                },
+                ConfigDirective::RouteGateway{ref gw_or_dhcp} => settings.route_gateway = Some(gw_or_dhcp.clone()),
+                ConfigDirective::DhcpOption{ref dhcp_option_type, parm: Some(ref parm)} => {
+                    match dhcp_option_type.as_ref() {
+                        "DNS" | "DNS6" => {
+                            if let Ok(address) = parm.parse() {
+                                push_unique(&mut settings.dns_servers, address);
+                            }
+                        },
+                        "DOMAIN" | "DOMAIN-SEARCH" => push_unique(&mut settings.search_domains, parm.clone()),
+                        _ => {},
+                    }
+                },
+                ConfigDirective::Dns(DnsDirective::Server{option: DnsServerOption::Address(ref addresses), ..}) => {
+                    for address in addresses.iter().filter_map(|a| dns_address(a)) {
+                        push_unique(&mut settings.dns_servers, address);
+                    }
+                },
+                ConfigDirective::Dns(DnsDirective::SearchDomains(ref domains)) => {
+                    for domain in domains.iter() {
+                        push_unique(&mut settings.search_domains, domain.clone());
+                    }
+                },
+                ConfigDirective::RedirectGateway{ref flags} => settings.redirect_gateway_flags = Some(flags.clone()),
+                ConfigDirective::BlockOutsideDns => settings.block_outside_dns = true,
+                _ => {},
+            }
+        }
+        settings
+    }
+}
+
+impl ParsedConfigFile {
+    /// The network settings this server config pushes to its clients with
+    /// `push`
+    pub fn pushed_settings(&self) -> PushedSettings {
+        let pushed: Vec<ConfigDirective> = self.success_lines.iter()
+            .filter_map(|line| line.result.pushed_directive().and_then(Result::ok))
+            .collect();
+        PushedSettings::from_directives(&pushed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {parse_push_reply, parse_str};
+
+    #[test]
+    fn test_pushed_settings() {
+        let contents = "server 10.8.0.0 255.255.255.0\npush \"route 10.1.0.0 255.255.0.0\"\n\
+                        push \"route-ipv6 2001:db8::/32 :: 100\"\npush \"dhcp-option DNS 10.8.0.1\"\n\
+                        push \"dhcp-option DOMAIN example.com\"\npush \"redirect-gateway def1 bypass-dhcp\"\n\
+                        route 192.168.0.0 255.255.0.0\n";
+        let settings = parse_str(contents).pushed_settings();
+        assert_eq!(settings, PushedSettings{
+            routes: vec![PushedRoute{network: "10.1.0.0/16".parse().unwrap(), gateway: None, metric: None}],
+            ipv6_routes: vec![PushedRoute{
+                network: "2001:db8::/32".parse().unwrap(),
+                gateway: Some("::".to_string()),
+                metric: Some(100),
+            }],
+            route_gateway: None,
+            dns_servers: vec!["10.8.0.1".parse().unwrap()],
+            search_domains: vec!["example.com".to_string()],
+            redirect_gateway_flags: Some(vec!["def1".to_string(), "bypass-dhcp".to_string()]),
+            block_outside_dns: false,
+        });
+
+        let reply = parse_push_reply("PUSH_REPLY,dns server 0 address [2001:db8::53]:53 10.8.0.1,block-outside-dns");
+        let settings = PushedSettings::from_directives(&reply.directives());
+        assert_eq!(settings.dns_servers, vec!["2001:db8::53".parse::<IpAddr>().unwrap(), "10.8.0.1".parse().unwrap()]);
+        assert!(settings.block_outside_dns);
+    }
+}
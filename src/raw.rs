@@ -0,0 +1,212 @@
+//! A first stage parse which only splits lines into commands and arguments,
+//! for tools which only need a few options and shouldn't pay for parsing
+//! every directive
+use std::io::{BufRead, BufReader, Read};
+use std::mem;
+use std::ops::Range;
+use config_directive::{parse_command, INLINE_FILE_OPTIONS};
+use errors::{self, ResultExt};
+use {inline_directive, is_comment, split_args, tag_name};
+use {ConfigDirective, ConfigLine, ParseWarning, ParsedConfigFile, MAX_LINE_LEN};
+
+/// An option of a config file split into its command and arguments, but not
+/// parsed into a `ConfigDirective`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RawLine {
+    /// The lines the option covers, more than one for an inline file
+    pub span: Range<i32>,
+    /// The option name, or the tag of an inline file
+    pub command: String,
+    pub args: Vec<String>,
+    /// The contents of an inline file
+    pub inline_contents: Option<String>,
+}
+
+impl RawLine {
+    /// The line the option starts on, as numbered by `parse`
+    pub fn number(&self) -> i32 {
+        self.span.start
+    }
+
+    /// Parse the option into a `ConfigDirective`
+    pub fn to_directive(&self) -> Result<ConfigDirective, ParseWarning> {
+        if let Some(ref contents) = self.inline_contents {
+            return inline_directive(&self.command, contents.clone());
+        }
+        let args: Vec<&str> = self.args.iter().map(|a| &a[..]).collect();
+        parse_command(&self.command, &args)
+    }
+}
+
+/// The result of `tokenize`
+pub struct RawConfigFile {
+    pub lines: Vec<RawLine>,
+    /// Lines which are too long, the only warning which doesn't depend on
+    /// parsing the options
+    pub warning_lines: Vec<ConfigLine<ParseWarning>>,
+}
+
+impl RawConfigFile {
+    /// The options with the given name, in the order they appear
+    pub fn find<'a>(&'a self, command: &'a str) -> impl Iterator<Item = &'a RawLine> + 'a {
+        self.lines.iter().filter(move |line| line.command == command)
+    }
+
+    /// Parse every option, giving the same result as `parse` on the same
+    /// input
+    pub fn parse(&self) -> ParsedConfigFile {
+        let mut success_lines = Vec::new();
+        let mut warning_lines = self.warning_lines.clone();
+        for line in self.lines.iter() {
+            match line.to_directive() {
+                Ok(result) => success_lines.push(ConfigLine{number: line.number(), result}),
+                Err(result) => warning_lines.push(ConfigLine{number: line.number(), result}),
+            }
+        }
+        warning_lines.sort_by_key(|l| l.number);
+        ParsedConfigFile{success_lines, warning_lines}
+    }
+}
+
+/// What a line of a config file is, as found by `Scanner`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub(crate) enum LineKind<'l> {
+    Blank,
+    Comment,
+    /// An option outside of an inline file
+    Option,
+    /// The opening tag of an inline file, with the tag's name
+    InlineOpen(&'l str),
+    /// A line of the contents of the inline file being read
+    InlineContents,
+    /// The closing tag of the inline file being read, with the tag's name
+    /// and the line it was opened on
+    InlineClose(String, i32),
+}
+
+/// A line of a config file as found by `Scanner`
+pub(crate) struct ScannedLine<'l> {
+    pub number: i32,
+    pub kind: LineKind<'l>,
+    /// Whether the line is longer than openvpn allows
+    pub too_long: bool,
+}
+
+/// Finds what each line of a config file is, keeping track of the inline
+/// file being read. This is the scanning `tokenize`, `parse` and
+/// `ConfigDocument` all use, so that they agree on where options and inline
+/// files are.
+pub(crate) struct Scanner {
+    recover_unclosed: bool,
+    next_number: i32,
+    /// The tag and first line of the inline file being read
+    inline: Option<(String, i32)>,
+}
+
+impl Scanner {
+    /// A scanner for the first line of a config file. If `recover_unclosed`
+    /// an inline file which is never closed is ended when another opens.
+    pub(crate) fn new(recover_unclosed: bool) -> Scanner {
+        Scanner{recover_unclosed, next_number: 0, inline: None}
+    }
+
+    /// What the next line is, `line` being without its line ending
+    pub(crate) fn scan<'l>(&mut self, line: &'l str) -> ScannedLine<'l> {
+        let number = self.next_number;
+        self.next_number += 1;
+        ScannedLine{number, kind: self.kind(number, line), too_long: line.len() > MAX_LINE_LEN}
+    }
+
+    fn kind<'l>(&mut self, number: i32, line: &'l str) -> LineKind<'l> {
+        let opened = tag_name(line, "<").filter(|tag| INLINE_FILE_OPTIONS.contains(tag));
+        if let Some((tag, start)) = self.inline.take() {
+            if tag_name(line, "</") == Some(&tag[..]) {
+                return LineKind::InlineClose(tag, start);
+            }
+            if !(self.recover_unclosed && opened.is_some()) {
+                self.inline = Some((tag, start));
+                return LineKind::InlineContents;
+            }
+        }
+        if let Some(tag) = opened {
+            self.inline = Some((tag.to_string(), number));
+            LineKind::InlineOpen(tag)
+        } else if is_comment(line) {
+            LineKind::Comment
+        } else if line.trim().is_empty() {
+            LineKind::Blank
+        } else {
+            LineKind::Option
+        }
+    }
+}
+
+/// Split a config file into its options without parsing them, skipping
+/// comments and blank lines as `parse` does. Options can then be parsed as
+/// they're needed with `RawLine::to_directive`.
+///
+/// ```
+/// let raw = ovpnfile::tokenize("client\nremote vpn.example.com 1194\n<ca>\nca\n</ca>\n".as_bytes()).unwrap();
+/// let remote = raw.find("remote").next().unwrap();
+/// assert_eq!(remote.args, vec!["vpn.example.com", "1194"]);
+/// assert_eq!(raw.find("ca").next().unwrap().span, 2..5);
+/// ```
+pub fn tokenize<R: Read>(input: R) -> errors::Result<RawConfigFile> {
+    let mut lines = Vec::new();
+    let mut warning_lines = Vec::new();
+    let mut scanner = Scanner::new(false);
+    // The lines so far of the inline file being read
+    let mut contents = Vec::new();
+    for line in BufReader::new(input).lines() {
+        let line = line.chain_err(|| "Error reading input")?;
+        let scanned = scanner.scan(&line);
+        let number = scanned.number;
+        if scanned.too_long {
+            warning_lines.push(ConfigLine{number, result: ParseWarning::LineTooLong});
+        }
+        match scanned.kind {
+            LineKind::InlineContents => contents.push(line.clone()),
+            LineKind::InlineClose(tag, start) => lines.push(RawLine{
+                span: start..number + 1,
+                command: tag,
+                args: Vec::new(),
+                inline_contents: Some(mem::take(&mut contents).join("\n")),
+            }),
+            LineKind::Option => {
+                let mut args = split_args(&line).into_iter().map(|a| a.into_owned());
+                if let Some(command) = args.next() {
+                    lines.push(RawLine{span: number..number + 1, command, args: args.collect(), inline_contents: None});
+                }
+            },
+            LineKind::InlineOpen(_) | LineKind::Comment | LineKind::Blank => {},
+        }
+    }
+    Ok(RawConfigFile{lines, warning_lines})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_tokenize() {
+        let contents = "client # comment\n\n<ca>\nca contents\n</ca>\nverb three\nunknown-option\nremote \"vpn example\" 1194\n";
+        let raw = tokenize(contents.as_bytes()).unwrap();
+        let commands: Vec<(&str, Range<i32>)> = raw.lines.iter().map(|l| (&l.command[..], l.span.clone())).collect();
+        assert_eq!(commands, vec![
+            ("client", 0..1),
+            ("ca", 2..5),
+            ("verb", 5..6),
+            ("unknown-option", 6..7),
+            ("remote", 7..8),
+        ]);
+        assert_eq!(raw.find("remote").next().unwrap().args, vec!["vpn example", "1194"]);
+        assert_eq!(raw.lines[3].to_directive(), Err(ParseWarning::NoMatchingCommand));
+
+        let parsed = parse_str(contents);
+        let from_raw = raw.parse();
+        assert_eq!(from_raw.success_lines, parsed.success_lines);
+        assert_eq!(from_raw.warning_lines, parsed.warning_lines);
+    }
+}
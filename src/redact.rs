@@ -0,0 +1,89 @@
+//! Hiding the secrets in a config so that it can be logged or shown safely
+use std::fmt;
+use secrets::{secrets, SecretLocation};
+use {ConfigDirective, File, ParsedConfigFile};
+
+/// What secrets are replaced with by `redacted`
+pub const REDACTED: &str = "[redacted]";
+
+impl ParsedConfigFile {
+    /// This config with the secrets it contains replaced by `REDACTED`:
+    /// inline private keys, PKCS#12 bundles, static keys, proxy and
+    /// `auth-user-pass` credentials, and `auth-token` values, including
+    /// pushed ones. Paths to files with secrets in them are left as they
+    /// are, as is everything else, so the structure of the config is
+    /// unchanged.
+    pub fn redacted(&self) -> ParsedConfigFile {
+        let inline_secrets: Vec<i32> = secrets(self).into_iter()
+            .filter(|secret| secret.location == SecretLocation::Inline)
+            .map(|secret| secret.line)
+            .collect();
+        let mut config = ParsedConfigFile{
+            success_lines: self.success_lines.clone(),
+            warning_lines: self.warning_lines.clone(),
+        };
+        for line in config.success_lines.iter_mut() {
+            if inline_secrets.contains(&line.number) {
+                if let Some(file) = line.result.file_mut() {
+                    *file = File::InlineFileContents(REDACTED.to_string());
+                }
+            }
+            match line.result {
+                ConfigDirective::AuthToken{ref mut token} => *token = REDACTED.to_string(),
+                ConfigDirective::AuthUserPass{up: Some(File::InlineFileContents(ref mut contents))} => {
+                    *contents = REDACTED.to_string();
+                },
+                ConfigDirective::Push{ref mut option} if option.starts_with("auth-token ") => {
+                    *option = format!("auth-token {}", REDACTED);
+                },
+                _ => {},
+            }
+        }
+        config
+    }
+}
+
+/// Displays a config with its secrets redacted, one directive per line, e.g
+/// for logging
+///
+/// ```
+/// use std::io::BufReader;
+/// use ovpnfile::{parse, Redacted};
+///
+/// let config = parse(BufReader::new("client\n<key>\nsecret\n</key>\n".as_bytes())).unwrap();
+/// assert_eq!(Redacted(&config).to_string(), "client\n<key>\n[redacted]\n</key>\n");
+/// ```
+pub struct Redacted<'a>(pub &'a ParsedConfigFile);
+
+impl<'a> fmt::Display for Redacted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for directive in self.0.redacted().iter() {
+            writeln!(f, "{}", directive.as_ovpn_config())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+
+    #[test]
+    fn test_redacted() {
+        let contents = "ca ca.crt\n<tls-crypt>\nstatic key\n</tls-crypt>\n<cert>\ncert contents\n</cert>\n\
+                        key client.key\nauth-token abc123\npush \"auth-token abc123\"\npush \"route 10.0.0.0 255.0.0.0\"\n\
+                        <auth-user-pass>\nuser\npass\n</auth-user-pass>\n";
+        let config = parse_str(contents);
+        let redacted: Vec<String> = config.redacted().directives().iter().map(|d| d.as_ovpn_config()).collect();
+        assert_eq!(redacted, vec![
+            "ca ca.crt",
+            "<tls-crypt>\n[redacted]\n</tls-crypt>",
+            "<cert>\ncert contents\n</cert>",
+            "key client.key",
+            "auth-token [redacted]",
+            "push \"auth-token [redacted]\"",
+            "push \"route 10.0.0.0 255.0.0.0\"",
+            "<auth-user-pass>\n[redacted]\n</auth-user-pass>",
+        ]);
+    }
+}
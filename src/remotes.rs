@@ -0,0 +1,271 @@
+//! Listing, editing and removing duplicate `remote` directives
+use {ConfigDirective, ConfigLine, ParsedConfigFile};
+
+/// openvpn's default port
+const DEFAULT_PORT: &str = "1194";
+/// openvpn's default protocol
+const DEFAULT_PROTO: &str = "udp";
+
+/// A `remote` with openvpn's defaults filled in, so that equivalent remotes
+/// compare equal
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+pub struct RemoteEndpoint {
+    pub host: String,
+    pub port: String,
+    pub proto: String,
+}
+
+/// The ports and protocol a config gives for `remote`s which don't have
+/// their own
+#[derive(Clone, Copy, Default)]
+struct Defaults<'a> {
+    port: Option<&'a str>,
+    rport: Option<&'a str>,
+    proto: Option<&'a str>,
+}
+
+impl<'a> Defaults<'a> {
+    /// These defaults overridden by the `port`, `rport` and `proto` in
+    /// `directives`
+    fn with<I: Iterator<Item = &'a ConfigDirective>>(mut self, directives: I) -> Defaults<'a> {
+        for directive in directives {
+            match *directive {
+                ConfigDirective::Port{ref port} => self.port = Some(port),
+                ConfigDirective::Rport{ref port} => self.rport = Some(port),
+                ConfigDirective::Proto{ref p} => self.proto = Some(p),
+                _ => {},
+            }
+        }
+        self
+    }
+
+    /// The endpoint of a `remote`, `None` for any other directive
+    fn endpoint(&self, remote: &ConfigDirective) -> Option<RemoteEndpoint> {
+        match *remote {
+            ConfigDirective::Remote{ref host, ref port, ref proto} => {
+                let default_port = self.rport.or(self.port).unwrap_or(DEFAULT_PORT);
+                let proto = proto.as_ref().map_or(self.proto.unwrap_or(DEFAULT_PROTO), |p| p).to_lowercase();
+                Some(RemoteEndpoint{
+                    host: host.to_lowercase(),
+                    port: port.as_ref().map_or(default_port, |p| p).to_string(),
+                    // A client's tcp is tcp-client
+                    proto: if proto == "tcp" { "tcp-client".to_string() } else { proto },
+                })
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The endpoint of each `remote` openvpn will try with its line number. If
+/// the config has `<connection>` blocks these are the `remote`s in them,
+/// numbered by the line the block starts on, as openvpn ignores any others.
+/// Ports and protocols which aren't given fall back to the block's and
+/// then the config's `rport`, `port` and `proto`, with `rport` taking
+/// precedence over `port`, then to openvpn's defaults.
+pub fn remote_endpoints(config: &ParsedConfigFile) -> Vec<(i32, RemoteEndpoint)> {
+    let defaults = Defaults::default().with(config.iter());
+    let connections: Vec<(i32, &Vec<ConfigDirective>)> = config.success_lines.iter().filter_map(|line| match line.result {
+        ConfigDirective::Connection(ref directives) => Some((line.number, directives)),
+        _ => None,
+    }).collect();
+    if connections.is_empty() {
+        return config.success_lines.iter()
+            .filter_map(|line| defaults.endpoint(&line.result).map(|endpoint| (line.number, endpoint)))
+            .collect();
+    }
+    connections.into_iter().flat_map(|(number, directives)| {
+        let defaults = defaults.with(directives.iter());
+        directives.iter().filter_map(move |d| defaults.endpoint(d)).map(move |endpoint| (number, endpoint))
+    }).collect()
+}
+
+/// The line numbers of groups of `remote`s with the same endpoint, each
+/// group in the order they appear in the config
+pub fn duplicate_remotes(config: &ParsedConfigFile) -> Vec<Vec<i32>> {
+    let mut groups: Vec<(RemoteEndpoint, Vec<i32>)> = Vec::new();
+    for (line, endpoint) in remote_endpoints(config) {
+        match groups.iter_mut().find(|&&mut (ref e, _)| *e == endpoint) {
+            Some(&mut (_, ref mut lines)) => lines.push(line),
+            None => groups.push((endpoint, vec![line])),
+        }
+    }
+    groups.into_iter().map(|(_, lines)| lines).filter(|lines| lines.len() > 1).collect()
+}
+
+impl ParsedConfigFile {
+    /// Remove `remote`s which duplicate an earlier `remote`, keeping the
+    /// order of the rest of the config. `<connection>` blocks are never
+    /// removed, as they can differ in their other options.
+    pub fn dedup_remotes(&mut self) {
+        let duplicates: Vec<i32> = duplicate_remotes(self).into_iter().flat_map(|lines| lines.into_iter().skip(1)).collect();
+        self.success_lines.retain(|l| !duplicates.contains(&l.number) || l.result.openvpn_option_name() != "remote");
+    }
+
+    /// The positions in `success_lines` of the `<connection>` blocks if
+    /// there are any, otherwise of the `remote`s
+    fn remote_positions(&self) -> Vec<usize> {
+        let option = if self.iter().any(|d| d.openvpn_option_name() == "connection") { "connection" } else { "remote" };
+        self.success_lines.iter().enumerate()
+            .filter(|&(_, line)| line.result.openvpn_option_name() == option)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The `remote` directives in the order openvpn tries them, unless
+    /// `remote_random` is set. If the config has `<connection>` blocks
+    /// these are the blocks instead, as openvpn ignores any `remote`s
+    /// outside them.
+    pub fn remotes(&self) -> Vec<&ConfigLine<ConfigDirective>> {
+        self.remote_positions().into_iter().map(|i| &self.success_lines[i]).collect()
+    }
+
+    /// Add a `remote` after the last one, or at the end of the config if
+    /// there are none. If the config has `<connection>` blocks it's added
+    /// in a block of its own after the last of them. The new line is
+    /// numbered after every other line.
+    pub fn add_remote(&mut self, host: &str, port: Option<&str>, proto: Option<&str>) {
+        let number = self.success_lines.iter().map(|l| l.number)
+            .chain(self.warning_lines.iter().map(|l| l.number))
+            .max().map_or(0, |n| n + 1);
+        let positions = self.remote_positions();
+        let position = positions.last().map_or(self.success_lines.len(), |i| i + 1);
+        let mut result = ConfigDirective::Remote{
+            host: host.to_string(),
+            port: port.map(str::to_string),
+            proto: proto.map(str::to_string),
+        };
+        if positions.last().is_some_and(|&i| self.success_lines[i].result.openvpn_option_name() == "connection") {
+            result = ConfigDirective::Connection(vec![result]);
+        }
+        self.success_lines.insert(position, ConfigLine{number, result});
+    }
+
+    /// Remove the `index`th `remote`, or `<connection>` block if the config
+    /// has them, returning it, or `None` if there are not that many
+    pub fn remove_remote(&mut self, index: usize) -> Option<ConfigLine<ConfigDirective>> {
+        let position = *self.remote_positions().get(index)?;
+        Some(self.success_lines.remove(position))
+    }
+
+    /// Move the `from`th `remote`, or `<connection>` block if the config has
+    /// them, so it is the `to`th, shifting the ones in between. The
+    /// `remote`s stay where they are in the config relative to other
+    /// options. Returns false, leaving the config as it is, if there are
+    /// not that many `remote`s.
+    pub fn move_remote(&mut self, from: usize, to: usize) -> bool {
+        let positions = self.remote_positions();
+        if from >= positions.len() || to >= positions.len() {
+            return false;
+        }
+        let mut remotes: Vec<ConfigLine<ConfigDirective>> = positions.iter().map(|&i| self.success_lines[i].clone()).collect();
+        let remote = remotes.remove(from);
+        remotes.insert(to, remote);
+        for (position, remote) in positions.into_iter().zip(remotes) {
+            self.success_lines[position] = remote;
+        }
+        true
+    }
+
+    /// Where this profile connects: the endpoint of each `remote` in the
+    /// order openvpn tries them, unless `remote_random` is set, with the
+    /// defaults filled in as by `remote_endpoints`. If the config has
+    /// `<connection>` blocks these are the `remote`s in them.
+    pub fn endpoints(&self) -> Vec<RemoteEndpoint> {
+        remote_endpoints(self).into_iter().map(|(_, endpoint)| endpoint).collect()
+    }
+
+    /// Whether `remote-random` is set, in which case openvpn tries the
+    /// `remote`s in a random order rather than the order they appear
+    pub fn remote_random(&self) -> bool {
+        self.success_lines.iter().any(|l| l.result == ConfigDirective::RemoteRandom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    const CONFIG: &str = "client\nproto tcp\nremote a.example.com\nremote b.example.com 1194\n\
+                          remote A.example.com 1194 tcp\nremote a.example.com 443\nremote b.example.com\n";
+
+    #[test]
+    fn test_duplicate_remotes() {
+        let config = parse_str(CONFIG);
+        assert_eq!(duplicate_remotes(&config), vec![vec![2, 4], vec![3, 6]]);
+        assert_eq!(remote_endpoints(&config)[0].1, RemoteEndpoint{
+            host: "a.example.com".to_string(),
+            port: "1194".to_string(),
+            proto: "tcp-client".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_dedup_remotes() {
+        let mut config = parse_str(CONFIG);
+        config.dedup_remotes();
+        let lines: Vec<i32> = config.success_lines.iter().map(|l| l.number).collect();
+        assert_eq!(lines, vec![0, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_edit_remotes() {
+        let mut config = parse_str("client\nremote a.example.com\nremote b.example.com\nverb 3\n");
+        assert!(!config.remote_random());
+        config.add_remote("c.example.com", Some("443"), Some("tcp"));
+        assert!(config.move_remote(2, 0));
+        assert!(!config.move_remote(3, 0));
+        assert_eq!(config.remove_remote(1).map(|l| l.number), Some(1));
+        assert!(config.remove_remote(2).is_none());
+        let remotes: Vec<(i32, String)> = config.remotes().iter()
+            .map(|l| (l.number, l.result.as_ovpn_config()))
+            .collect();
+        assert_eq!(remotes, vec![(4, "remote c.example.com 443 tcp".to_string()), (2, "remote b.example.com".to_string())]);
+        let options: Vec<&str> = config.success_lines.iter().map(|l| l.result.openvpn_option_name()).collect();
+        assert_eq!(options, vec!["client", "remote", "remote", "verb"]);
+
+        let config = parse_str("remote-random\n");
+        assert!(config.remote_random());
+
+        let mut config = parse_str(
+            "client\nremote ignored.example.com\n<connection>\nremote a.example.com\n</connection>\n\
+             <connection>\nremote b.example.com\nhttp-proxy proxy.example.com 3128\n</connection>\nverb 3\n",
+        );
+        config.add_remote("c.example.com", None, None);
+        assert!(config.move_remote(2, 0));
+        assert_eq!(config.remove_remote(1).map(|l| l.number), Some(2));
+        let remotes: Vec<(i32, String)> = config.remotes().iter()
+            .map(|l| (l.number, l.result.as_ovpn_config()))
+            .collect();
+        assert_eq!(remotes, vec![
+            (10, "<connection>\nremote c.example.com\n</connection>".to_string()),
+            (5, "<connection>\nremote b.example.com\nhttp-proxy proxy.example.com 3128\n</connection>".to_string()),
+        ]);
+        let options: Vec<&str> = config.success_lines.iter().map(|l| l.result.openvpn_option_name()).collect();
+        assert_eq!(options, vec!["client", "remote", "connection", "connection", "verb"]);
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let endpoints = |contents: &str| -> Vec<(String, String, String)> {
+            parse_str(contents).endpoints().into_iter()
+                .map(|e| (e.host, e.port, e.proto))
+                .collect()
+        };
+        assert_eq!(endpoints("rport 443\nport 1195\nremote a.example.com\nremote b.example.com 1194 tcp\n"), vec![
+            ("a.example.com".to_string(), "443".to_string(), "udp".to_string()),
+            ("b.example.com".to_string(), "1194".to_string(), "tcp-client".to_string()),
+        ]);
+        let contents = "proto tcp\nport 443\nremote ignored.example.com\n\
+                        <connection>\nremote a.example.com\n</connection>\n\
+                        <connection>\nremote b.example.com\nproto udp\nport 1195\n</connection>\n";
+        assert_eq!(endpoints(contents), vec![
+            ("a.example.com".to_string(), "443".to_string(), "tcp-client".to_string()),
+            ("b.example.com".to_string(), "1195".to_string(), "udp".to_string()),
+        ]);
+        let config = parse_str(contents);
+        let lines: Vec<i32> = remote_endpoints(&config).into_iter().map(|(line, _)| line).collect();
+        assert_eq!(lines, vec![3, 6]);
+    }
+}
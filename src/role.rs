@@ -0,0 +1,112 @@
+//! Working out what kind of config a config file is, and the kind of
+//! device it uses
+use {ConfigDirective, Mode, ParsedConfigFile};
+
+/// The role a config file gives openvpn
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum Role {
+    /// A client which pulls its settings from a server, e.g `client`
+    Client,
+    /// A server for multiple clients, e.g `server` or `mode server`
+    Server,
+    /// One end of a point to point tunnel, using a static key or TLS
+    /// without a server
+    PointToPoint,
+    /// The config doesn't say, or has options for both clients and servers
+    Undetermined,
+}
+
+impl ParsedConfigFile {
+    /// The role this config gives openvpn, worked out from the options which
+    /// select client, server or point to point mode
+    pub fn role(&self) -> Role {
+        let mut client = false;
+        let mut server = false;
+        let mut point_to_point = false;
+        let mut remote = false;
+        let mut ifconfig = false;
+        for line in self.success_lines.iter() {
+            match line.result {
+                ConfigDirective::Client | ConfigDirective::Pull => client = true,
+                ConfigDirective::Server{..} | ConfigDirective::ServerBridge(_) | ConfigDirective::ServerIpv6{..} |
+                ConfigDirective::Mode{m: Mode::Server} => server = true,
+                ConfigDirective::Secret{..} | ConfigDirective::TlsClient | ConfigDirective::TlsServer => point_to_point = true,
+                ConfigDirective::Remote{..} => remote = true,
+                ConfigDirective::Ifconfig{..} => ifconfig = true,
+                _ => {},
+            }
+        }
+        match (client, server) {
+            (true, true) => Role::Undetermined,
+            (true, false) => Role::Client,
+            (false, true) => Role::Server,
+            (false, false) if point_to_point || (remote && ifconfig) => Role::PointToPoint,
+            (false, false) => Role::Undetermined,
+        }
+    }
+
+    /// The line of the option which decides whether this config uses a tun
+    /// or tap device and whether it's tap. `dev-type` takes precedence,
+    /// otherwise the type comes from the name given to `dev`. `None` if
+    /// neither says, e.g `dev null`.
+    pub(crate) fn device(&self) -> Option<(i32, bool)> {
+        let mut dev = None;
+        let mut dev_type = None;
+        for line in self.success_lines.iter() {
+            match line.result {
+                ConfigDirective::Dev{ref devarg} => dev = Some((line.number, &devarg[..])),
+                ConfigDirective::DevType{ref device_type} => dev_type = Some((line.number, &device_type[..])),
+                _ => {},
+            }
+        }
+        dev_type.or(dev).and_then(|(number, name)| {
+            if name.starts_with("tap") {
+                Some((number, true))
+            } else if name.starts_with("tun") {
+                Some((number, false))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Whether this config uses a tap device, by its `dev-type` or else the
+    /// name given to `dev`
+    pub fn is_tap(&self) -> bool {
+        self.device().is_some_and(|(_, tap)| tap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    fn role(contents: &str) -> Role {
+        parse_str(contents).role()
+    }
+
+    #[test]
+    fn test_role() {
+        assert_eq!(role("client\nremote somehost\n"), Role::Client);
+        assert_eq!(role("tls-client\npull\nremote somehost\n"), Role::Client);
+        assert_eq!(role("server 10.8.0.0 255.255.255.0\ntls-server\n"), Role::Server);
+        assert_eq!(role("mode server\ntls-server\nifconfig-pool 10.8.0.2 10.8.0.100\n"), Role::Server);
+        assert_eq!(role("remote somehost\nsecret static.key\n"), Role::PointToPoint);
+        assert_eq!(role("remote somehost\nifconfig 10.8.0.1 10.8.0.2\n"), Role::PointToPoint);
+        assert_eq!(role("tls-server\ndev tun\n"), Role::PointToPoint);
+        assert_eq!(role("remote somehost\n"), Role::Undetermined);
+        assert_eq!(role("client\nmode server\n"), Role::Undetermined);
+    }
+
+    #[test]
+    fn test_is_tap() {
+        let config = |contents: &str| parse_str(contents);
+        assert!(config("dev tap0\n").is_tap());
+        assert!(config("dev mydev\ndev-type tap\n").is_tap());
+        assert!(!config("dev tap0\ndev-type tun\n").is_tap());
+        assert!(!config("dev tun\n").is_tap());
+        assert_eq!(config("dev tun\ndev-type tap\n").device(), Some((1, true)));
+        assert_eq!(config("dev null\n").device(), None);
+    }
+}
@@ -0,0 +1,104 @@
+//! An inventory of the secret material a config file contains or refers to,
+//! so that importers can warn users and store it appropriately.
+use {ConfigDirective, File, ParsedConfigFile};
+
+/// The kind of a secret
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum SecretKind {
+    /// A private key, from `key`
+    PrivateKey,
+    /// A PKCS#12 bundle, which contains a private key, from `pkcs12`
+    Pkcs12,
+    /// An OpenVPN static key, from `secret`, `tls-auth` or `tls-crypt`
+    StaticKey,
+    /// A `tls-crypt-v2` client or server key
+    TlsCryptV2Key,
+    /// A username and password for a VPN server, from `auth-user-pass`
+    UserCredentials,
+    /// A username and password for an HTTP proxy, from `http-proxy-user-pass`
+    ProxyCredentials,
+    /// The passphrase of the private key, from `askpass`
+    KeyPassphrase,
+    /// The password of the management interface, from `management`
+    ManagementPassword,
+}
+
+/// Where a secret is kept
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SecretLocation {
+    /// In an inline block in the config file itself
+    Inline,
+    /// In the file at this path
+    File(String),
+}
+
+/// A secret found in a config file
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Secret {
+    pub kind: SecretKind,
+    /// The line of the option the secret was given for
+    pub line: i32,
+    pub location: SecretLocation,
+}
+
+fn file_location(file: &File) -> Option<SecretLocation> {
+    match *file {
+        File::InlineFileContents(_) => Some(SecretLocation::Inline),
+        File::FilePath(ref path) => path_location(path),
+        File::None => None,
+    }
+}
+
+/// The location of a secret given as a path, `stdin` meaning openvpn will
+/// prompt for it instead
+fn path_location(path: &str) -> Option<SecretLocation> {
+    if path == "stdin" {
+        None
+    } else {
+        Some(SecretLocation::File(path.to_string()))
+    }
+}
+
+/// Every secret a config file contains inline or refers to by path, in the
+/// order they appear
+pub fn secrets(config: &ParsedConfigFile) -> Vec<Secret> {
+    config.success_lines.iter().filter_map(|line| {
+        let (kind, location) = match line.result {
+            ConfigDirective::Key{ref file} => (SecretKind::PrivateKey, file_location(file)),
+            ConfigDirective::Pkcs12{ref file} => (SecretKind::Pkcs12, file_location(file)),
+            ConfigDirective::Secret{ref file, ..} |
+            ConfigDirective::TlsAuth{ref file, ..} |
+            ConfigDirective::TlsCrypt{ref file} => (SecretKind::StaticKey, file_location(file)),
+            ConfigDirective::TlsCryptV2{ref file, ..} => (SecretKind::TlsCryptV2Key, file_location(file)),
+            ConfigDirective::HttpProxyUserPass{ref file} => (SecretKind::ProxyCredentials, file_location(file)),
+            ConfigDirective::AuthUserPass{up: Some(ref file)} => (SecretKind::UserCredentials, file_location(file)),
+            ConfigDirective::Askpass{file: Some(ref path)} => (SecretKind::KeyPassphrase, path_location(path)),
+            ConfigDirective::Management{pw_file: Some(ref path), ..} => (SecretKind::ManagementPassword, path_location(path)),
+            _ => return None,
+        };
+        location.map(|location| Secret{kind, line: line.number, location})
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_secrets() {
+        let contents = "client\nca ca.crt\n<key>\nkey contents\n</key>\ntls-crypt tc.key\n\
+                        auth-user-pass creds.txt\naskpass\nmanagement 127.0.0.1 7505 stdin\n\
+                        management 127.0.0.1 7506 mgmt-pw.txt\n<http-proxy-user-pass>\nuser\npass\n</http-proxy-user-pass>\n\
+                        auth-user-pass stdin\n<auth-user-pass>\nuser\npass\n</auth-user-pass>\n";
+        let config = parse_str(contents);
+        assert_eq!(secrets(&config), vec![
+            Secret{kind: SecretKind::PrivateKey, line: 2, location: SecretLocation::Inline},
+            Secret{kind: SecretKind::StaticKey, line: 5, location: SecretLocation::File("tc.key".to_string())},
+            Secret{kind: SecretKind::UserCredentials, line: 6, location: SecretLocation::File("creds.txt".to_string())},
+            Secret{kind: SecretKind::ManagementPassword, line: 9, location: SecretLocation::File("mgmt-pw.txt".to_string())},
+            Secret{kind: SecretKind::ProxyCredentials, line: 10, location: SecretLocation::Inline},
+            Secret{kind: SecretKind::UserCredentials, line: 15, location: SecretLocation::Inline},
+        ]);
+    }
+}
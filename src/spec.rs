@@ -0,0 +1,110 @@
+//! What the crate knows about each option, available at runtime for
+//! editors, documentation generators and validators
+use config_directive::DIRECTIVE_SPECS;
+use version::{OptionVersions, Version};
+use {option_versions, ConfigDirective};
+
+/// An argument of an option
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ArgumentSpec {
+    /// The argument's name, which is the name of its field in the
+    /// `ConfigDirective` variant
+    pub name: &'static str,
+    /// The type the argument is parsed into, `None` for a `String`
+    pub type_name: Option<&'static str>,
+    pub optional: bool,
+    /// Whether the argument takes all of the remaining arguments
+    pub varargs: bool,
+    /// The values the argument can take, empty if it can take any value
+    pub choices: &'static [&'static str],
+    /// The version of openvpn which added the argument to the option, `None`
+    /// if it's as old as the option
+    pub introduced: Option<Version>,
+}
+
+/// An option as described in the option table the directives are generated
+/// from
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct DirectiveSpec {
+    /// The option's name in a config file, e.g `tls-crypt`
+    pub command: &'static str,
+    /// The name of the `ConfigDirective` variant, e.g `TlsCrypt`
+    pub variant: &'static str,
+    /// The arguments in the order they're given in a config file
+    pub arguments: &'static [ArgumentSpec],
+    /// Whether the option can have inline file contents
+    pub inline: bool,
+    /// Whether a server can push the option to clients
+    pub pushable: bool,
+    /// Whether the option can be used in a `client-config-dir` file
+    pub ccd: bool,
+    /// Whether using the option again adds to the earlier uses
+    pub repeatable: bool,
+}
+
+impl DirectiveSpec {
+    /// Every option the crate knows about, in the order of the option table
+    pub fn all() -> &'static [DirectiveSpec] {
+        DIRECTIVE_SPECS
+    }
+
+    /// The option with the given name, `None` if it isn't known
+    pub fn for_option(option_name: &str) -> Option<&'static DirectiveSpec> {
+        DIRECTIVE_SPECS.iter().find(|spec| spec.command == option_name)
+    }
+
+    /// The spec of the option a directive was parsed from
+    pub fn for_directive(directive: &ConfigDirective) -> &'static DirectiveSpec {
+        DirectiveSpec::for_option(directive.openvpn_option_name()).expect("every directive is in the option table")
+    }
+
+    /// The required arguments, in order
+    pub fn required_arguments(&self) -> impl Iterator<Item = &'static ArgumentSpec> {
+        self.arguments.iter().filter(|a| !a.optional)
+    }
+
+    /// The optional arguments, in order
+    pub fn optional_arguments(&self) -> impl Iterator<Item = &'static ArgumentSpec> {
+        self.arguments.iter().filter(|a| a.optional)
+    }
+
+    /// The versions of openvpn in which the option was introduced,
+    /// deprecated and removed
+    pub fn versions(&self) -> OptionVersions {
+        option_versions(self.command).expect("every spec is in the option table")
+    }
+}
+
+impl ConfigDirective {
+    /// The spec of the option this directive was parsed from
+    pub fn spec(&self) -> &'static DirectiveSpec {
+        DirectiveSpec::for_directive(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use File;
+
+    #[test]
+    fn test_directive_specs() {
+        let spec = DirectiveSpec::for_option("tls-crypt-v2").unwrap();
+        assert_eq!(spec.variant, "TlsCryptV2");
+        assert!(spec.inline);
+        assert_eq!(spec.versions().introduced, Some(Version::new(2, 5)));
+        let arguments: Vec<(&str, Option<&str>, bool)> = spec.arguments.iter().map(|a| (a.name, a.type_name, a.optional)).collect();
+        assert_eq!(arguments, vec![("file", None, false), ("cookie_mode", Some("TlsCryptV2CookieMode"), true)]);
+        assert_eq!(spec.arguments[0].introduced, None);
+        assert_eq!(spec.arguments[1].introduced, Some(Version::new(2, 6)));
+
+        let topology = DirectiveSpec::for_option("topology").unwrap();
+        assert_eq!(topology.required_arguments().next().unwrap().choices, &["net30", "p2p", "subnet"]);
+        assert!(DirectiveSpec::for_option("redirect-gateway").unwrap().arguments[0].varargs);
+        assert_eq!(DirectiveSpec::for_option("not-an-option"), None);
+
+        let directive = ConfigDirective::Ca{file: File::FilePath("ca.crt".to_string())};
+        assert_eq!(directive.spec().command, "ca");
+        assert!(DirectiveSpec::all().iter().any(|spec| spec.command == "server-bridge"));
+    }
+}
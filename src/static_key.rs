@@ -0,0 +1,204 @@
+//! OpenVPN static keys, as used by `--secret`, `--tls-auth` and `--tls-crypt`
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use errors::{self, ResultExt};
+
+const HEADER: &str = "-----BEGIN OpenVPN Static key V1-----";
+const FOOTER: &str = "-----END OpenVPN Static key V1-----";
+
+/// The number of bytes in a static key
+pub const STATIC_KEY_LEN: usize = 256;
+
+/// The number of bytes of the key used for each direction, a 512 bit
+/// cipher key followed by a 512 bit HMAC key
+const HALF_LEN: usize = STATIC_KEY_LEN / 2;
+
+/// The number of hex digits on each line of a key's text form
+const HEX_LINE_LEN: usize = 32;
+
+/// A 2048 bit OpenVPN static key. The text form is generated by
+/// `openvpn --genkey` and looks like
+///
+/// ```text
+/// -----BEGIN OpenVPN Static key V1-----
+/// <512 hex digits split over several lines>
+/// -----END OpenVPN Static key V1-----
+/// ```
+///
+/// Any lines before the header, typically comments, are ignored.
+#[derive(Clone)]
+pub struct StaticKey([u8; STATIC_KEY_LEN]);
+
+impl StaticKey {
+    pub fn from_bytes(bytes: [u8; STATIC_KEY_LEN]) -> StaticKey {
+        StaticKey(bytes)
+    }
+
+    /// Read a key from a file, such as one generated by `openvpn --genkey`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> errors::Result<StaticKey> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).chain_err(|| format!("Error reading {}", path.display()))?;
+        text.parse().chain_err(|| format!("Invalid static key in {}", path.display()))
+    }
+
+    /// The bytes of the key
+    pub fn bytes(&self) -> &[u8; STATIC_KEY_LEN] {
+        &self.0
+    }
+
+    /// The two halves of the key, each a cipher key followed by an HMAC key.
+    /// With a key direction each side sends with one half and receives with
+    /// the other, without one both directions use the first half.
+    pub fn halves(&self) -> (&[u8], &[u8]) {
+        self.0.split_at(HALF_LEN)
+    }
+
+    /// The halves of the key used to send and receive with the given
+    /// `key-direction`, or the direction argument of `tls-auth` or
+    /// `secret`. `None` if the direction isn't `0` or `1`.
+    pub fn directional_halves(&self, direction: Option<&str>) -> Option<(&[u8], &[u8])> {
+        let (first, second) = self.halves();
+        match direction {
+            None => Some((first, first)),
+            Some("0") => Some((first, second)),
+            Some("1") => Some((second, first)),
+            Some(_) => None,
+        }
+    }
+
+    /// The key in the text form `openvpn --genkey` writes, which
+    /// `from_str` parses
+    pub fn to_text(&self) -> String {
+        let hex: String = self.0.iter().map(|b| format!("{:02x}", b)).collect();
+        let mut text = format!("#\n# 2048 bit OpenVPN static key\n#\n{}\n", HEADER);
+        for line in hex.as_bytes().chunks(HEX_LINE_LEN) {
+            text.push_str(&String::from_utf8_lossy(line));
+            text.push('\n');
+        }
+        text.push_str(FOOTER);
+        text.push('\n');
+        text
+    }
+}
+
+// Don't print the key material
+impl fmt::Debug for StaticKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("StaticKey(..)")
+    }
+}
+
+impl PartialEq for StaticKey {
+    fn eq(&self, other: &StaticKey) -> bool {
+        self.0[..] == other.0[..]
+    }
+}
+
+impl Eq for StaticKey {}
+
+/// The reasons a static key can be malformed
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum StaticKeyError {
+    MissingHeader,
+    MissingFooter,
+    /// The key contains a character which isn't a hex digit
+    InvalidHex,
+    /// The key has the given number of hex digits rather than 512
+    WrongLength(usize),
+}
+
+impl fmt::Display for StaticKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StaticKeyError::MissingHeader => write!(f, "missing the \"{}\" line", HEADER),
+            StaticKeyError::MissingFooter => write!(f, "missing the \"{}\" line", FOOTER),
+            StaticKeyError::InvalidHex => f.write_str("the key contains characters which aren't hex digits"),
+            StaticKeyError::WrongLength(len) => {
+                write!(f, "the key has {} hex digits rather than {}", len, STATIC_KEY_LEN * 2)
+            },
+        }
+    }
+}
+
+impl Error for StaticKeyError {}
+
+impl FromStr for StaticKey {
+    type Err = StaticKeyError;
+    fn from_str(s: &str) -> Result<StaticKey, StaticKeyError> {
+        let mut lines = s.lines().map(|l| l.trim()).skip_while(|l| *l != HEADER);
+        if lines.next().is_none() {
+            return Err(StaticKeyError::MissingHeader);
+        }
+        let mut hex = String::new();
+        loop {
+            match lines.next() {
+                Some(FOOTER) => break,
+                Some(line) => hex.push_str(line),
+                None => return Err(StaticKeyError::MissingFooter),
+            }
+        }
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(StaticKeyError::InvalidHex);
+        }
+        if hex.len() != STATIC_KEY_LEN * 2 {
+            return Err(StaticKeyError::WrongLength(hex.len()));
+        }
+        let mut key = [0; STATIC_KEY_LEN];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| StaticKeyError::InvalidHex)?;
+        }
+        Ok(StaticKey(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    fn key_text(hex: &str) -> String {
+        format!("#\n# 2048 bit OpenVPN static key\n#\n{}\n{}\n{}\n", HEADER, hex, FOOTER)
+    }
+
+    #[test]
+    fn test_parse_static_key() {
+        let hex: String = (0..STATIC_KEY_LEN).map(|i| format!("{:02x}", i)).collect();
+        let lines: Vec<&str> = (0..16).map(|i| &hex[i * 32..(i + 1) * 32]).collect();
+        let key: StaticKey = key_text(&lines.join("\n")).parse().unwrap();
+        assert_eq!(key.bytes()[0], 0);
+        assert_eq!(key.bytes()[255], 255);
+
+        assert_eq!("".parse::<StaticKey>(), Err(StaticKeyError::MissingHeader));
+        assert_eq!(format!("{}\n{}", HEADER, hex).parse::<StaticKey>(), Err(StaticKeyError::MissingFooter));
+        assert_eq!(key_text(&hex[2..]).parse::<StaticKey>(), Err(StaticKeyError::WrongLength(510)));
+        assert_eq!(key_text(&hex.replace("ff", "zz")).parse::<StaticKey>(), Err(StaticKeyError::InvalidHex));
+    }
+
+    #[test]
+    fn test_static_key_round_trip() {
+        let mut bytes = [0; STATIC_KEY_LEN];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let key = StaticKey::from_bytes(bytes);
+        let text = key.to_text();
+        assert_eq!(text.lines().count(), 21);
+        assert_eq!(text.parse::<StaticKey>(), Ok(key.clone()));
+
+        let (first, second) = key.halves();
+        assert_eq!((first[0], second[0]), (0, 128));
+        assert_eq!(key.directional_halves(None), Some((first, first)));
+        assert_eq!(key.directional_halves(Some("1")), Some((second, first)));
+        assert_eq!(key.directional_halves(Some("2")), None);
+
+        let path = env::temp_dir().join(format!("ovpnfile-static-key-{}", process::id()));
+        fs::write(&path, &text).unwrap();
+        assert_eq!(StaticKey::from_file(&path).unwrap(), key);
+        fs::write(&path, "not a key").unwrap();
+        assert!(StaticKey::from_file(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
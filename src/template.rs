@@ -0,0 +1,250 @@
+//! Template profiles with `{{NAME}}` placeholders, which are filled in to
+//! make a config for each site or user
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+use config_directive::parse_command;
+use errors;
+use raw::tokenize;
+use {ConfigDirective, ConfigLine, ParseWarning, ParsedConfigFile};
+
+/// Why a template couldn't be rendered
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TemplateError {
+    /// No value was given for the variable
+    MissingVariable(String),
+    /// The value of the variable contains a line break, so the rendered
+    /// config couldn't be written back to a file
+    UnsafeValue(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TemplateError::MissingVariable(ref name) => write!(f, "no value for {{{{{}}}}}", name),
+            TemplateError::UnsafeValue(ref name) => write!(f, "the value of {{{{{}}}}} contains a line break", name),
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+/// A piece of a templated argument, either text or a placeholder
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum Segment<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+/// Split `text` into text and `{{NAME}}` placeholders. Anything between
+/// braces which isn't a valid variable name is left as text.
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut result = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let name = after.find("}}").map(|end| &after[..end])
+            .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        match name {
+            Some(name) => {
+                result.push(Segment::Text(&rest[..start]));
+                result.push(Segment::Variable(name));
+                rest = &after[name.len() + 2..];
+            },
+            None => {
+                result.push(Segment::Text(&rest[..start + 2]));
+                rest = after;
+            },
+        }
+    }
+    result.push(Segment::Text(rest));
+    result
+}
+
+/// Whether there are any placeholders in `text`
+fn has_variables(text: &str) -> bool {
+    segments(text).iter().any(|s| matches!(*s, Segment::Variable(_)))
+}
+
+/// An argument of a templated option
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TemplateArgument {
+    Literal(String),
+    /// An argument with `{{NAME}}` placeholders in it, which are filled in
+    /// when the template is rendered
+    Templated(String),
+}
+
+impl TemplateArgument {
+    fn new(arg: String) -> TemplateArgument {
+        if has_variables(&arg) {
+            TemplateArgument::Templated(arg)
+        } else {
+            TemplateArgument::Literal(arg)
+        }
+    }
+
+    /// The argument with its placeholders filled in from `vars`
+    fn render(&self, vars: &HashMap<&str, &str>) -> Result<String, TemplateError> {
+        let text = match *self {
+            TemplateArgument::Literal(ref arg) => return Ok(arg.clone()),
+            TemplateArgument::Templated(ref text) => text,
+        };
+        let mut result = String::new();
+        for segment in segments(text) {
+            match segment {
+                Segment::Text(t) => result.push_str(t),
+                Segment::Variable(name) => {
+                    let value = vars.get(name).ok_or_else(|| TemplateError::MissingVariable(name.to_string()))?;
+                    if value.contains(['\n', '\r']) {
+                        return Err(TemplateError::UnsafeValue(name.to_string()));
+                    }
+                    result.push_str(value);
+                },
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// An option of a template with placeholders in its arguments
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TemplatedOption {
+    pub number: i32,
+    pub command: String,
+    pub args: Vec<TemplateArgument>,
+}
+
+/// A line of a template
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum TemplateLine {
+    /// An option or inline file without placeholders, parsed as it is
+    Parsed(ConfigLine<Result<ConfigDirective, ParseWarning>>),
+    Templated(TemplatedOption),
+}
+
+/// A config file with `{{NAME}}` placeholders in the arguments of its
+/// options, e.g `remote {{GATEWAY}} {{PORT}}`, which can be rendered into a
+/// config by giving the value of each variable. Options without
+/// placeholders are parsed as the template is read, those with them when
+/// it's rendered. A value always fills in part of a single argument, so it
+/// can't add options or arguments to the config however it's written.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use ovpnfile::Template;
+///
+/// let template = Template::parse("client\nremote {{GATEWAY}} {{PORT}}\n".as_bytes()).unwrap();
+/// assert_eq!(template.variables(), vec!["GATEWAY", "PORT"]);
+/// let mut vars = HashMap::new();
+/// vars.insert("GATEWAY", "vpn.example.com");
+/// vars.insert("PORT", "443");
+/// let config = template.render(&vars).unwrap();
+/// assert_eq!(config.directives()[1].as_ovpn_config(), "remote vpn.example.com 443");
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Template {
+    lines: Vec<TemplateLine>,
+    warning_lines: Vec<ConfigLine<ParseWarning>>,
+}
+
+impl Template {
+    /// Read a template
+    pub fn parse<R: Read>(input: R) -> errors::Result<Template> {
+        let raw = tokenize(input)?;
+        let lines = raw.lines.into_iter().map(|line| {
+            let templated = line.inline_contents.is_none() && line.args.iter().any(|arg| has_variables(arg));
+            if templated {
+                TemplateLine::Templated(TemplatedOption{
+                    number: line.number(),
+                    command: line.command,
+                    args: line.args.into_iter().map(TemplateArgument::new).collect(),
+                })
+            } else {
+                TemplateLine::Parsed(ConfigLine{number: line.number(), result: line.to_directive()})
+            }
+        }).collect();
+        Ok(Template{lines, warning_lines: raw.warning_lines})
+    }
+
+    /// The options with placeholders in their arguments
+    pub fn templated_options(&self) -> Vec<&TemplatedOption> {
+        self.lines.iter().filter_map(|line| match *line {
+            TemplateLine::Templated(ref option) => Some(option),
+            TemplateLine::Parsed(_) => None,
+        }).collect()
+    }
+
+    /// The names of the variables in the template, in the order they first
+    /// appear
+    pub fn variables(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = Vec::new();
+        for option in self.templated_options() {
+            for arg in option.args.iter() {
+                if let TemplateArgument::Templated(ref text) = *arg {
+                    for segment in segments(text) {
+                        if let Segment::Variable(name) = segment {
+                            if !result.contains(&name) {
+                                result.push(name);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Fill in the variables with the values in `vars` and parse the
+    /// options which have them. A value can contain anything but a line
+    /// break, it's only ever part of a single argument.
+    pub fn render(&self, vars: &HashMap<&str, &str>) -> Result<ParsedConfigFile, TemplateError> {
+        let mut success_lines = Vec::new();
+        let mut warning_lines = self.warning_lines.clone();
+        for line in self.lines.iter() {
+            let (number, result) = match *line {
+                TemplateLine::Parsed(ref line) => (line.number, line.result.clone()),
+                TemplateLine::Templated(ref option) => {
+                    let args = option.args.iter().map(|arg| arg.render(vars)).collect::<Result<Vec<String>, _>>()?;
+                    let args: Vec<&str> = args.iter().map(|a| &a[..]).collect();
+                    (option.number, parse_command(&option.command, &args))
+                },
+            };
+            match result {
+                Ok(result) => success_lines.push(ConfigLine{number, result}),
+                Err(result) => warning_lines.push(ConfigLine{number, result}),
+            }
+        }
+        warning_lines.sort_by_key(|l| l.number);
+        Ok(ParsedConfigFile{success_lines, warning_lines})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template = Template::parse("remote {{HOST}} {{PORT}}\nverb {{ 3 }}\nsetenv FOO \"{{HOST}} {{GREETING}}\"\n# {{COMMENT}}\n".as_bytes()).unwrap();
+        assert_eq!(template.variables(), vec!["HOST", "PORT", "GREETING"]);
+        assert_eq!(template.templated_options()[1].args, vec![
+            TemplateArgument::Literal("FOO".to_string()),
+            TemplateArgument::Templated("{{HOST}} {{GREETING}}".to_string()),
+        ]);
+        let mut vars = HashMap::new();
+        vars.insert("HOST", "vpn.example.com");
+        vars.insert("GREETING", "hello there");
+        assert_eq!(template.render(&vars).err(), Some(TemplateError::MissingVariable("PORT".to_string())));
+        vars.insert("PORT", "1194\nscript-security 3");
+        assert_eq!(template.render(&vars).err(), Some(TemplateError::UnsafeValue("PORT".to_string())));
+        vars.insert("PORT", "1194");
+        let config = template.render(&vars).unwrap();
+        assert_eq!(config.directives()[0].as_ovpn_config(), "remote vpn.example.com 1194");
+        assert_eq!(config.directives()[2], ConfigDirective::Setenv{
+            name: "FOO".to_string(),
+            value: "vpn.example.com hello there".to_string(),
+        });
+    }
+}
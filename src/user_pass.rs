@@ -0,0 +1,132 @@
+//! The username and password files read by `auth-user-pass`,
+//! `http-proxy-user-pass` and the proxy `authfile` arguments
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use errors::{self, ResultExt};
+
+/// The longest username or password, in bytes, every build of openvpn
+/// accepts. Builds with PKCS#11 support accept longer ones, for tokens.
+pub const MAX_USER_PASS_LEN: usize = 127;
+
+/// The contents of a username and password file, the username on the first
+/// line and the password on the second. openvpn prompts for the password
+/// if the file only has a username.
+#[derive(PartialEq, Eq, Clone)]
+pub struct UserPass {
+    pub username: String,
+    pub password: Option<String>,
+}
+
+// Don't print the password
+impl fmt::Debug for UserPass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UserPass")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// The reasons a username and password can't be used in a file
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum UserPassError {
+    MissingUsername,
+    /// The username or password contains a carriage return or newline,
+    /// which would split it over lines
+    LineBreak,
+    /// The username or password is longer than `MAX_USER_PASS_LEN`
+    TooLong,
+}
+
+impl fmt::Display for UserPassError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UserPassError::MissingUsername => f.write_str("the username is empty"),
+            UserPassError::LineBreak => f.write_str("the username or password contains a line break"),
+            UserPassError::TooLong => write!(f, "the username or password is longer than {} bytes", MAX_USER_PASS_LEN),
+        }
+    }
+}
+
+impl Error for UserPassError {}
+
+fn validate(value: &str) -> Result<(), UserPassError> {
+    if value.contains(['\r', '\n']) {
+        return Err(UserPassError::LineBreak);
+    }
+    if value.len() > MAX_USER_PASS_LEN {
+        return Err(UserPassError::TooLong);
+    }
+    Ok(())
+}
+
+impl UserPass {
+    /// A username and password, checking they can be written to a file
+    pub fn new(username: &str, password: Option<&str>) -> Result<UserPass, UserPassError> {
+        let user_pass = UserPass{username: username.to_string(), password: password.map(str::to_string)};
+        user_pass.validate()?;
+        Ok(user_pass)
+    }
+
+    /// Read a username and password file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> errors::Result<UserPass> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).chain_err(|| format!("Error reading {}", path.display()))?;
+        text.parse().chain_err(|| format!("Invalid username and password in {}", path.display()))
+    }
+
+    fn validate(&self) -> Result<(), UserPassError> {
+        if self.username.is_empty() {
+            return Err(UserPassError::MissingUsername);
+        }
+        validate(&self.username)?;
+        self.password.as_ref().map_or(Ok(()), |p| validate(p))
+    }
+
+    /// The contents of a file with this username and password, which
+    /// `from_str` parses
+    pub fn to_text(&self) -> String {
+        match self.password {
+            Some(ref password) => format!("{}\n{}\n", self.username, password),
+            None => format!("{}\n", self.username),
+        }
+    }
+}
+
+impl FromStr for UserPass {
+    type Err = UserPassError;
+    /// Parse the contents of a file, or of an inline
+    /// `<http-proxy-user-pass>`. Like openvpn, lines after the password
+    /// are ignored.
+    fn from_str(s: &str) -> Result<UserPass, UserPassError> {
+        let mut lines = s.lines();
+        let username = lines.next().unwrap_or("");
+        let password = lines.next().filter(|p| !p.is_empty());
+        UserPass::new(username, password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_pass() {
+        let user_pass: UserPass = "alice\r\nsecret\r\n".parse().unwrap();
+        assert_eq!(user_pass, UserPass::new("alice", Some("secret")).unwrap());
+        assert_eq!(user_pass.to_text(), "alice\nsecret\n");
+        assert_eq!(format!("{:?}", user_pass), "UserPass { username: \"alice\", password: Some(\"..\") }");
+
+        let username_only: UserPass = "alice\n".parse().unwrap();
+        assert_eq!(username_only.password, None);
+        assert_eq!(username_only.to_text().parse(), Ok(username_only));
+
+        assert_eq!("".parse::<UserPass>(), Err(UserPassError::MissingUsername));
+        assert_eq!("alice\rbob\nsecret".parse::<UserPass>(), Err(UserPassError::LineBreak));
+        assert_eq!(UserPass::new("alice", Some("a\nb")), Err(UserPassError::LineBreak));
+        assert_eq!(UserPass::new(&"a".repeat(MAX_USER_PASS_LEN + 1), None), Err(UserPassError::TooLong));
+    }
+}
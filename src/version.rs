@@ -0,0 +1,73 @@
+//! openvpn release versions and the versions in which options were
+//! introduced, deprecated and removed.
+use std::fmt;
+use std::str::FromStr;
+use ParsedConfigFile;
+
+/// An openvpn release, e.g `2.4`. Patch releases are not distinguished.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl Version {
+    pub const fn new(major: u8, minor: u8) -> Version {
+        Version{major, minor}
+    }
+}
+
+impl FromStr for Version {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Version, ()> {
+        let mut parts = s.split('.');
+        let major = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let minor = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        // Allow a patch version but ignore it
+        if let Some(patch) = parts.next() {
+            patch.parse::<u8>().map_err(|_| ())?;
+        }
+        if parts.next().is_some() {
+            return Err(());
+        }
+        Ok(Version::new(major, minor))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// The versions of openvpn in which an option was introduced, deprecated and
+/// removed. `introduced` is `None` for options which predate the versions
+/// the option table tracks.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct OptionVersions {
+    pub introduced: Option<Version>,
+    pub deprecated: Option<Version>,
+    pub removed: Option<Version>,
+    /// The option to use instead, if a deprecated option has a replacement
+    pub replaced_by: Option<&'static str>,
+}
+
+impl OptionVersions {
+    /// Whether the option can be used with the given version of openvpn
+    pub fn supported_in(&self, version: Version) -> bool {
+        self.introduced.is_none_or(|v| v <= version) && self.removed.is_none_or(|v| v > version)
+    }
+
+    /// Whether the option is deprecated (or removed) in the given version
+    pub fn deprecated_in(&self, version: Version) -> bool {
+        self.deprecated.is_some_and(|v| v <= version) || self.removed.is_some_and(|v| v <= version)
+    }
+}
+
+/// The lowest version of openvpn which supports every option in `config`
+/// with the arguments given, `None` if all of them predate the versions the
+/// option table tracks. This doesn't take removed options into account, see
+/// `lint::deprecated_options` for those.
+pub fn required_openvpn_version(config: &ParsedConfigFile) -> Option<Version> {
+    config.success_lines.iter().filter_map(|l| l.result.required_version()).max()
+}
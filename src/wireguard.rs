@@ -0,0 +1,270 @@
+//! Best effort conversion of client configs to WireGuard configs
+use std::net::Ipv4Addr;
+use network::dns_address;
+use {remote_endpoints, ConfigDirective, DnsDirective, DnsServerOption, Ipv4Network, Ipv6Network, ParsedConfigFile};
+
+/// Options a WireGuard config doesn't need anything for, because they're
+/// implied by WireGuard or only affect openvpn itself
+const IMPLIED_OPTIONS: &[&str] = &[
+    "client", "pull", "nobind", "persist-key", "persist-tun", "resolv-retry", "verb", "mute", "port", "rport",
+    "proto", "topology", "explicit-exit-notify", "float",
+];
+
+/// Options which configure openvpn's authentication and encryption, in
+/// addition to those which refer to a file
+const CRYPTO_OPTIONS: &[&str] = &[
+    "cipher", "data-ciphers", "data-ciphers-fallback", "ncp-ciphers", "auth", "keysize", "key-direction",
+    "remote-cert-tls", "ns-cert-type", "verify-x509-name", "auth-user-pass", "auth-nocache", "auth-token",
+    "auth-retry", "static-challenge", "reneg-sec", "reneg-bytes", "reneg-pkts",
+];
+
+/// Why an option couldn't be put in a WireGuard config
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum UntranslatedReason {
+    /// WireGuard only uses UDP
+    Tcp,
+    /// WireGuard tunnels IP packets, not ethernet frames
+    Layer2,
+    /// A WireGuard peer has a single endpoint, so only the first `remote`
+    /// is used
+    AdditionalRemote,
+    /// WireGuard's cryptography is fixed and peers are authenticated by
+    /// their public keys, so certificates, keys, ciphers and credentials
+    /// have no equivalent
+    Crypto,
+    /// The route can't be expressed as allowed IPs, such as routes which
+    /// bypass the tunnel or are given as a hostname
+    Route,
+    /// WireGuard has no equivalent of the option
+    Unsupported,
+}
+
+/// An option which couldn't be put in a WireGuard config
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Untranslated {
+    pub option: &'static str,
+    pub line: i32,
+    pub reason: UntranslatedReason,
+}
+
+/// The skeleton of a WireGuard config equivalent to a client config. The
+/// keys aren't included, as WireGuard's keys are unrelated to openvpn's,
+/// so `PrivateKey` and `PublicKey` are left empty in `to_ini`.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct WireGuardConfig {
+    /// The `Address`es of the interface
+    pub addresses: Vec<String>,
+    /// The `DNS` servers and search domains
+    pub dns: Vec<String>,
+    /// The interface's `MTU`
+    pub mtu: Option<String>,
+    /// The peer's `Endpoint`, as `host:port`
+    pub endpoint: Option<String>,
+    /// The networks routed to the peer, its `AllowedIPs`
+    pub allowed_ips: Vec<String>,
+    /// The `PersistentKeepalive` interval in seconds
+    pub persistent_keepalive: Option<String>,
+    /// The options which couldn't be translated
+    pub untranslated: Vec<Untranslated>,
+}
+
+impl WireGuardConfig {
+    fn push_unique(list: &mut Vec<String>, value: String) {
+        if !list.contains(&value) {
+            list.push(value);
+        }
+    }
+
+    fn untranslated(&mut self, directive: &ConfigDirective, line: i32, reason: UntranslatedReason) {
+        self.untranslated.push(Untranslated{option: directive.openvpn_option_name(), line, reason});
+    }
+
+    /// The config in the INI format used by `wg-quick`, with empty keys to
+    /// be filled in
+    pub fn to_ini(&self) -> String {
+        let mut result = "[Interface]\nPrivateKey = \n".to_string();
+        if !self.addresses.is_empty() {
+            result.push_str(&format!("Address = {}\n", self.addresses.join(", ")));
+        }
+        if !self.dns.is_empty() {
+            result.push_str(&format!("DNS = {}\n", self.dns.join(", ")));
+        }
+        if let Some(ref mtu) = self.mtu {
+            result.push_str(&format!("MTU = {}\n", mtu));
+        }
+        result.push_str("\n[Peer]\nPublicKey = \n");
+        if let Some(ref endpoint) = self.endpoint {
+            result.push_str(&format!("Endpoint = {}\n", endpoint));
+        }
+        if !self.allowed_ips.is_empty() {
+            result.push_str(&format!("AllowedIPs = {}\n", self.allowed_ips.join(", ")));
+        }
+        if let Some(ref keepalive) = self.persistent_keepalive {
+            result.push_str(&format!("PersistentKeepalive = {}\n", keepalive));
+        }
+        result
+    }
+}
+
+/// A `route` as an allowed IP, e.g `10.1.0.0/16`. Routes through
+/// `net_gateway` bypass the tunnel, so can't be allowed IPs.
+fn route(network: &str, netmask: Option<&String>, gateway: Option<&String>) -> Option<String> {
+    if gateway.map(|g| &g[..]) == Some("net_gateway") {
+        return None;
+    }
+    Ipv4Network::from_route(network, netmask.map(|n| &n[..])).map(|network| network.to_string())
+}
+
+/// A best effort conversion of a client config to a WireGuard config: the
+/// first `remote` becomes the endpoint, `route`s, `route-ipv6`s and
+/// `redirect-gateway` the allowed IPs, `dhcp-option` and `dns` options the
+/// DNS servers and search domains, `ifconfig` and `ifconfig-ipv6` the
+/// addresses, and `keepalive` or `ping` the persistent keepalive. Everything
+/// else which affects the tunnel is listed in `untranslated`.
+pub fn to_wireguard(config: &ParsedConfigFile) -> WireGuardConfig {
+    let mut wireguard = WireGuardConfig::default();
+    let subnet = config.success_lines.iter()
+        .rev()
+        .find_map(|line| match line.result {
+            ConfigDirective::Topology{ref mode} => Some(mode == "subnet"),
+            _ => None,
+        })
+        .unwrap_or(false);
+
+    let endpoints = remote_endpoints(config);
+    if let Some(&(number, ref endpoint)) = endpoints.first() {
+        let host = if endpoint.host.contains(':') { format!("[{}]", endpoint.host) } else { endpoint.host.clone() };
+        wireguard.endpoint = Some(format!("{}:{}", host, endpoint.port));
+        if endpoint.proto.starts_with("tcp") {
+            wireguard.untranslated.push(Untranslated{option: "remote", line: number, reason: UntranslatedReason::Tcp});
+        }
+    }
+    for &(number, _) in endpoints.iter().skip(1) {
+        wireguard.untranslated.push(Untranslated{option: "remote", line: number, reason: UntranslatedReason::AdditionalRemote});
+    }
+
+    let tap = config.is_tap();
+    for line in config.success_lines.iter() {
+        let directive = &line.result;
+        match *directive {
+            ConfigDirective::Remote{..} => {},
+            ConfigDirective::Dev{..} | ConfigDirective::DevType{..} if tap => {
+                wireguard.untranslated(directive, line.number, UntranslatedReason::Layer2);
+            },
+            ConfigDirective::Dev{..} | ConfigDirective::DevType{..} => {},
+            ConfigDirective::Ifconfig{ref l, ref rn} => {
+                let address = l.parse().ok().and_then(|l| {
+                    if subnet {
+                        let netmask = rn.parse().ok()?;
+                        Ipv4Network::with_netmask(l, netmask).map(|network| (l, network.prefix_len(), network.to_string()))
+                    } else {
+                        let remote: Ipv4Addr = rn.parse().ok()?;
+                        Some((l, 32, format!("{}/32", remote)))
+                    }
+                });
+                match address {
+                    Some((l, prefix_len, peer)) => {
+                        WireGuardConfig::push_unique(&mut wireguard.addresses, format!("{}/{}", l, prefix_len));
+                        WireGuardConfig::push_unique(&mut wireguard.allowed_ips, peer);
+                    },
+                    None => wireguard.untranslated(directive, line.number, UntranslatedReason::Unsupported),
+                }
+            },
+            ConfigDirective::IfconfigIpv6{ref ipv6addr, ..} => {
+                match ipv6addr.parse::<Ipv6Network>() {
+                    Ok(network) => {
+                        WireGuardConfig::push_unique(&mut wireguard.addresses, ipv6addr.clone());
+                        WireGuardConfig::push_unique(&mut wireguard.allowed_ips, network.to_string());
+                    },
+                    Err(_) => wireguard.untranslated(directive, line.number, UntranslatedReason::Unsupported),
+                }
+            },
+            ConfigDirective::Route{ref network_or_ip, ref netmask, ref gateway, ..} => {
+                match route(network_or_ip, netmask.as_ref(), gateway.as_ref()) {
+                    Some(route) => WireGuardConfig::push_unique(&mut wireguard.allowed_ips, route),
+                    None => wireguard.untranslated(directive, line.number, UntranslatedReason::Route),
+                }
+            },
+            ConfigDirective::RouteIpv6{ref ipv6addr, ref gateway, ..} => {
+                match ipv6addr.parse::<Ipv6Network>() {
+                    Ok(ref network) if gateway.as_ref().map(|g| &g[..]) != Some("net_gateway") => {
+                        WireGuardConfig::push_unique(&mut wireguard.allowed_ips, network.to_string());
+                    },
+                    _ => wireguard.untranslated(directive, line.number, UntranslatedReason::Route),
+                }
+            },
+            ConfigDirective::RedirectGateway{ref flags} => {
+                if !flags.iter().any(|f| f == "!ipv4") {
+                    WireGuardConfig::push_unique(&mut wireguard.allowed_ips, "0.0.0.0/0".to_string());
+                }
+                if flags.iter().any(|f| f == "ipv6") {
+                    WireGuardConfig::push_unique(&mut wireguard.allowed_ips, "::/0".to_string());
+                }
+            },
+            ConfigDirective::DhcpOption{ref dhcp_option_type, parm: Some(ref parm)}
+                if ["DNS", "DNS6", "DOMAIN", "DOMAIN-SEARCH"].contains(&&dhcp_option_type[..]) =>
+            {
+                WireGuardConfig::push_unique(&mut wireguard.dns, parm.clone());
+            },
+            ConfigDirective::Dns(DnsDirective::SearchDomains(ref domains)) => {
+                for domain in domains.iter() {
+                    WireGuardConfig::push_unique(&mut wireguard.dns, domain.clone());
+                }
+            },
+            ConfigDirective::Dns(DnsDirective::Server{option: DnsServerOption::Address(ref addresses), ..}) => {
+                for address in addresses.iter().filter_map(|a| dns_address(a)) {
+                    WireGuardConfig::push_unique(&mut wireguard.dns, address.to_string());
+                }
+            },
+            ConfigDirective::TunMtu{ref n} => wireguard.mtu = Some(n.clone()),
+            ConfigDirective::Keepalive{ref interval, ..} => wireguard.persistent_keepalive = Some(interval.clone()),
+            ConfigDirective::Ping{ref n} => wireguard.persistent_keepalive = Some(n.clone()),
+            ref directive if IMPLIED_OPTIONS.contains(&directive.openvpn_option_name()) => {},
+            ref directive if directive.file().is_some() || directive.openvpn_option_name().starts_with("tls-")
+                || CRYPTO_OPTIONS.contains(&directive.openvpn_option_name()) =>
+            {
+                wireguard.untranslated(directive, line.number, UntranslatedReason::Crypto);
+            },
+            _ => wireguard.untranslated(directive, line.number, UntranslatedReason::Unsupported),
+        }
+    }
+    wireguard.untranslated.sort_by_key(|u| u.line);
+    wireguard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    #[test]
+    fn test_to_wireguard() {
+        let contents = "client\ndev tun\nproto udp\nremote vpn.example.com 1194\nremote backup.example.com 443 tcp\n\
+                        topology subnet\nifconfig 10.8.0.2 255.255.255.0\nredirect-gateway def1 ipv6\n\
+                        route 10.1.0.0 255.255.0.0\nroute 192.0.2.1 255.255.255.255 net_gateway\n\
+                        dhcp-option DNS 10.8.0.1\ndns server 0 address [2001:db8::53]:53 10.8.0.1:53\n\
+                        dns search-domains example.com\nkeepalive 10 60\ntun-mtu 1420\nca ca.crt\ncipher AES-256-GCM\n\
+                        route-delay 5\n";
+        let config = parse_str(contents);
+        let wireguard = to_wireguard(&config);
+        assert_eq!(wireguard.to_ini(), "[Interface]\nPrivateKey = \nAddress = 10.8.0.2/24\n\
+                                        DNS = 10.8.0.1, 2001:db8::53, example.com\nMTU = 1420\n\n\
+                                        [Peer]\nPublicKey = \nEndpoint = vpn.example.com:1194\n\
+                                        AllowedIPs = 10.8.0.0/24, 0.0.0.0/0, ::/0, 10.1.0.0/16\nPersistentKeepalive = 10\n");
+        assert_eq!(wireguard.untranslated, vec![
+            Untranslated{option: "remote", line: 4, reason: UntranslatedReason::AdditionalRemote},
+            Untranslated{option: "route", line: 9, reason: UntranslatedReason::Route},
+            Untranslated{option: "ca", line: 15, reason: UntranslatedReason::Crypto},
+            Untranslated{option: "cipher", line: 16, reason: UntranslatedReason::Crypto},
+            Untranslated{option: "route-delay", line: 17, reason: UntranslatedReason::Unsupported},
+        ]);
+
+        let config = parse_str("dev tap\nremote 2001:db8::1 1194 tcp\n");
+        let wireguard = to_wireguard(&config);
+        assert_eq!(wireguard.endpoint, Some("[2001:db8::1]:1194".to_string()));
+        assert_eq!(wireguard.untranslated, vec![
+            Untranslated{option: "dev", line: 0, reason: UntranslatedReason::Layer2},
+            Untranslated{option: "remote", line: 1, reason: UntranslatedReason::Tcp},
+        ]);
+    }
+}
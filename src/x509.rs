@@ -0,0 +1,247 @@
+//! Details of the certificates included inline in a config file, enabled by
+//! the `x509` feature.
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use openssl::asn1::{Asn1Time, Asn1TimeRef};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::x509::{GeneralNameRef, X509, X509NameRef};
+use {CertHash, ConfigDirective, File, ParsedConfigFile};
+
+/// The details of a certificate
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Certificate {
+    /// The subject, e.g `CN=client`
+    pub subject: String,
+    /// The issuer, e.g `CN=Test CA`
+    pub issuer: String,
+    /// The subject alternative names, prefixed with their type as openssl
+    /// shows them, e.g `DNS:vpn.example.com` or `IP:10.8.0.1`
+    pub subject_alt_names: Vec<String>,
+    /// The serial number in upper case hex
+    pub serial: String,
+    /// The start of the validity period
+    pub not_before: SystemTime,
+    /// The end of the validity period
+    pub not_after: SystemTime,
+}
+
+impl Certificate {
+    /// Whether the certificate has expired at `now`
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.not_after < now
+    }
+
+    /// Whether the certificate is not yet valid at `now`
+    pub fn is_not_yet_valid(&self, now: SystemTime) -> bool {
+        self.not_before > now
+    }
+}
+
+fn system_time(time: &Asn1TimeRef) -> Result<SystemTime, ErrorStack> {
+    let epoch = Asn1Time::from_unix(0)?;
+    let diff = epoch.diff(time)?;
+    let secs = i64::from(diff.days) * 86400 + i64::from(diff.secs);
+    if secs >= 0 {
+        Ok(UNIX_EPOCH + Duration::from_secs(secs as u64))
+    } else {
+        Ok(UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()))
+    }
+}
+
+fn name(name: &X509NameRef) -> String {
+    let entries: Vec<String> = name.entries().map(|entry| {
+        let name = entry.object().nid().short_name().unwrap_or("?");
+        let value = entry.data().to_string().unwrap_or_default();
+        format!("{}={}", name, value)
+    }).collect();
+    entries.join(", ")
+}
+
+fn general_name(name: &GeneralNameRef) -> Option<String> {
+    if let Some(dns) = name.dnsname() {
+        Some(format!("DNS:{}", dns))
+    } else if let Some(email) = name.email() {
+        Some(format!("email:{}", email))
+    } else if let Some(uri) = name.uri() {
+        Some(format!("URI:{}", uri))
+    } else {
+        let address = match name.ipaddress()?.len() {
+            4 => {
+                let mut octets = [0; 4];
+                octets.copy_from_slice(name.ipaddress()?);
+                IpAddr::from(octets)
+            },
+            16 => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(name.ipaddress()?);
+                IpAddr::from(octets)
+            },
+            _ => return None,
+        };
+        Some(format!("IP:{}", address))
+    }
+}
+
+fn certificate(cert: &X509) -> Result<Certificate, ErrorStack> {
+    let subject_alt_names = cert.subject_alt_names()
+        .map(|names| names.iter().filter_map(general_name).collect())
+        .unwrap_or_default();
+    Ok(Certificate{
+        subject: name(cert.subject_name()),
+        issuer: name(cert.issuer_name()),
+        subject_alt_names,
+        serial: cert.serial_number().to_bn()?.to_hex_str()?.to_string(),
+        not_before: system_time(cert.not_before())?,
+        not_after: system_time(cert.not_after())?,
+    })
+}
+
+/// Parse the PEM encoded certificates in `pem`
+pub fn parse_certificates(pem: &str) -> Result<Vec<Certificate>, ErrorStack> {
+    X509::stack_from_pem(pem.as_bytes())?.iter().map(certificate).collect()
+}
+
+/// The SHA-256 fingerprints of the PEM encoded certificates in `pem`, as
+/// `peer-fingerprint` and `verify-hash` take them
+pub fn certificate_fingerprints(pem: &str) -> Result<Vec<CertHash>, ErrorStack> {
+    X509::stack_from_pem(pem.as_bytes())?.iter()
+        .map(|cert| Ok(CertHash::new(cert.digest(MessageDigest::sha256())?.to_vec())))
+        .collect()
+}
+
+impl ConfigDirective {
+    /// The SHA-256 fingerprints of the certificates of an inline `ca`,
+    /// `cert` or `extra-certs` directive. Returns `None` for any other
+    /// directive, or if the certificates are in a separate file.
+    pub fn certificate_fingerprints(&self) -> Option<Result<Vec<CertHash>, ErrorStack>> {
+        match *self {
+            ConfigDirective::Ca{file: File::InlineFileContents(ref contents)} |
+            ConfigDirective::Cert{file: File::InlineFileContents(ref contents)} |
+            ConfigDirective::ExtraCerts{file: File::InlineFileContents(ref contents)} => {
+                Some(certificate_fingerprints(contents))
+            },
+            _ => None,
+        }
+    }
+
+    /// The certificates of an inline `ca`, `cert` or `extra-certs`
+    /// directive. Returns `None` for any other directive, or if the
+    /// certificates are in a separate file.
+    pub fn certificates(&self) -> Option<Result<Vec<Certificate>, ErrorStack>> {
+        match *self {
+            ConfigDirective::Ca{file: File::InlineFileContents(ref contents)} |
+            ConfigDirective::Cert{file: File::InlineFileContents(ref contents)} |
+            ConfigDirective::ExtraCerts{file: File::InlineFileContents(ref contents)} => {
+                Some(parse_certificates(contents))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A certificate included inline in a config file
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct InlineCertificate {
+    /// The line the inline block starts on
+    pub line: i32,
+    /// The option the certificate was given for, `ca`, `cert` or `extra-certs`
+    pub option: &'static str,
+    /// The certificate itself
+    pub certificate: Certificate,
+}
+
+/// The certificates in the inline `ca`, `cert` and `extra-certs` blocks of a
+/// config. Blocks which can't be parsed are skipped.
+pub fn inline_certificates(config: &ParsedConfigFile) -> Vec<InlineCertificate> {
+    let mut result = Vec::new();
+    for line in config.success_lines.iter() {
+        if let Some(Ok(certificates)) = line.result.certificates() {
+            let option = line.result.openvpn_option_name();
+            result.extend(certificates.into_iter().map(|certificate| {
+                InlineCertificate{line: line.number, option, certificate}
+            }));
+        }
+    }
+    result
+}
+
+/// A `peer-fingerprint` directive accepting the certificates in `pems`,
+/// e.g the `cert`s of a server's clients, for moving a small deployment
+/// from a CA to fingerprints
+pub fn peer_fingerprint_directive(pems: &[&str]) -> Result<ConfigDirective, ErrorStack> {
+    let mut fingerprints = Vec::new();
+    for pem in pems.iter() {
+        fingerprints.extend(certificate_fingerprints(pem)?.iter().map(|f| f.to_string()));
+    }
+    Ok(ConfigDirective::PeerFingerprint{fingerprints})
+}
+
+impl ParsedConfigFile {
+    /// The `peer-fingerprint` directive the peers of this config need to
+    /// accept its inline `cert` without a CA. `None` if there is no inline
+    /// `cert`.
+    pub fn own_peer_fingerprint(&self) -> Option<Result<ConfigDirective, ErrorStack>> {
+        self.success_lines.iter().rev().find_map(|line| match line.result {
+            ConfigDirective::Cert{file: File::InlineFileContents(ref contents)} => {
+                Some(peer_fingerprint_directive(&[contents]))
+            },
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parse_str;
+
+    const CA: &str = include_str!("../tests/certs/ca.crt");
+    const CERT: &str = include_str!("../tests/certs/client.crt");
+    const SERVER_CERT: &str = include_str!("../tests/certs/server.crt");
+
+    #[test]
+    fn test_inline_certificates() {
+        let contents = format!("<ca>\n{}</ca>\n<cert>\n{}</cert>\n", CA, CERT);
+        let config = parse_str(&contents);
+        let certificates = inline_certificates(&config);
+        assert_eq!(certificates.len(), 2);
+        assert_eq!(certificates[0].option, "ca");
+        assert_eq!(certificates[0].certificate.subject, "CN=Test CA");
+        assert_eq!(certificates[1].option, "cert");
+        assert_eq!(certificates[1].certificate.subject, "CN=client");
+        let now = SystemTime::now();
+        assert!(certificates[1].certificate.not_before < now);
+        assert!(!certificates[1].certificate.is_expired(now));
+        assert!(certificates[1].certificate.is_expired(now + Duration::from_secs(200 * 365 * 86400)));
+    }
+
+    #[test]
+    fn test_certificate_details() {
+        let directive = ConfigDirective::Cert{file: File::InlineFileContents(SERVER_CERT.to_string())};
+        let certificates = directive.certificates().unwrap().unwrap();
+        assert_eq!(certificates[0].subject, "CN=vpn.example.com");
+        assert_eq!(certificates[0].issuer, "CN=Test CA");
+        assert_eq!(certificates[0].subject_alt_names, vec!["DNS:vpn.example.com", "IP:10.8.0.1"]);
+        assert_eq!(certificates[0].serial, "6E9637F573B299FAB205C5C36E5361F8781A0D8D");
+        assert!(ConfigDirective::Cert{file: File::FilePath("client.crt".to_string())}.certificates().is_none());
+    }
+
+    #[test]
+    fn test_peer_fingerprints() {
+        let directive = ConfigDirective::Cert{file: File::InlineFileContents(CERT.to_string())};
+        let fingerprints = directive.certificate_fingerprints().unwrap().unwrap();
+        assert_eq!(fingerprints.len(), 1);
+        // As given by `openssl x509 -noout -fingerprint -sha256`
+        assert_eq!(
+            fingerprints[0].to_string(),
+            "15:2A:6F:3A:56:BC:BD:D0:42:5D:A9:CE:A5:15:87:8C:AD:74:22:D6:C8:6E:E5:66:D8:CE:0D:5A:79:90:3C:0B",
+        );
+
+        let config = parse_str(&format!("client\n<cert>\n{}</cert>\n", CERT));
+        let directive = config.own_peer_fingerprint().unwrap().unwrap();
+        assert_eq!(directive, ConfigDirective::PeerFingerprint{fingerprints: vec![fingerprints[0].to_string()]});
+        assert_eq!(peer_fingerprint_directive(&[CERT, SERVER_CERT]).unwrap().as_ovpn_config().lines().count(), 4);
+        assert!(parse_str("client\n").own_peer_fingerprint().is_none());
+    }
+}
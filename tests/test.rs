@@ -3,14 +3,21 @@ extern crate spectral;
 
 use spectral::boolean::BooleanAssertions;
 use std::io::{BufReader};
-use ovpnfile::{ConfigDirective, ServerBridgeArg, File};
+use std::net::Ipv4Addr;
+use ovpnfile::{ConfigDirective, ConfigLine, ServerBridgeArg, File, ParseWarning};
+use ovpnfile::{DnsDirective, DnsServerOption, DnsSecMode, DnsTransport};
+use ovpnfile::{Mode, HttpProxyAuth, HttpProxyAuthMethod, X509Field, EchoCommand, TlsCryptV2CookieMode};
+use ovpnfile::{TlsCertProfile, VlanAccept, VlanId, WindowsDriver, GenkeyType, HashAlgorithm, CertHash};
+use ovpnfile::{MssfixFlag, AuthGenTokenFlag, FragmentFlag, CrlVerifyFlag, IpWin32Method};
+use ovpnfile::Version;
+use std::time::Duration;
 
 #[test]
 fn test_reads_ovpnfile() {
     let expected_result: Vec<ConfigDirective> = vec![
         ConfigDirective::Help,
         ConfigDirective::Config{file: "some_file".to_string()},
-        ConfigDirective::Mode{m: "some_mode".to_string()},
+        ConfigDirective::Mode{m: Mode::Server},
         ConfigDirective::Local{host: "somehost".to_string()},
         ConfigDirective::Remote{host: "somehost".to_string(), port: None, proto: None},
         ConfigDirective::Remote{host: "somehost".to_string(), port: Some("someport".to_string()), proto: None},
@@ -23,14 +30,17 @@ fn test_reads_ovpnfile() {
         ConfigDirective::ConnectRetry{n: "10".to_string(), max: Some("5".to_string())},
         ConfigDirective::ConnectRetryMax{n: "10".to_string()},
         ConfigDirective::ShowProxySettings,
-        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), authfile_or_auto_or_auto_nct: None, auth_method: None},
-        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), authfile_or_auto_or_auto_nct: Some("authfile".to_string()), auth_method: None},
-        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), authfile_or_auto_or_auto_nct: Some("authfile".to_string()), auth_method: Some("someathmethod".to_string())},
+        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), auth: None, auth_method: None},
+        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), auth: Some(HttpProxyAuth::AuthFile("authfile".to_string())), auth_method: None},
+        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), auth: Some(HttpProxyAuth::AuthFile("authfile".to_string())), auth_method: Some(HttpProxyAuthMethod::Basic)},
+        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), auth: Some(HttpProxyAuth::Auto), auth_method: None},
+        ConfigDirective::HttpProxy{server: "server".to_string(), port: "port".to_string(), auth: Some(HttpProxyAuth::AutoNct), auth_method: Some(HttpProxyAuthMethod::Ntlm)},
         ConfigDirective::HttpProxyOption{http_proxy_option_type: "sometype".to_string(), parm: None},
         ConfigDirective::HttpProxyOption{http_proxy_option_type: "sometype".to_string(), parm: Some("someparm".to_string())},
+        ConfigDirective::HttpProxyUserPass{file: File::FilePath("somefile".to_string())},
         ConfigDirective::SocksProxy{server: "someserver".to_string(), port: None, authfile: None},
-        ConfigDirective::SocksProxy{server: "someserver".to_string(), port: Some("someport".to_string()), authfile: None},
-        ConfigDirective::SocksProxy{server: "someserver".to_string(), port: Some("someport".to_string()), authfile: Some("someauthfile".to_string())},
+        ConfigDirective::SocksProxy{server: "someserver".to_string(), port: Some(9050), authfile: None},
+        ConfigDirective::SocksProxy{server: "someserver".to_string(), port: Some(9050), authfile: Some("someauthfile".to_string())},
         ConfigDirective::ResolvRetry{n: "10".to_string()},
         ConfigDirective::Float,
         ConfigDirective::Ipchange{cmd: "somecmd".to_string()},
@@ -70,10 +80,16 @@ fn test_reads_ovpnfile() {
         ConfigDirective::RedirectPrivate{flags: Some(vec!["flag1".to_string(), "flag2".to_string()])},
         ConfigDirective::TunMtu{n: "10".to_string()},
         ConfigDirective::TunMtuExtra{n: "10".to_string()},
+        ConfigDirective::TunIpv6,
         ConfigDirective::MtuDisc{mtu_disc_type: "sometype".to_string()},
         ConfigDirective::MtuTest,
-        ConfigDirective::Fragment{max: "10".to_string()},
-        ConfigDirective::Mssfix{max: "10".to_string()},
+        ConfigDirective::Fragment{max: "10".to_string(), flag: None},
+        ConfigDirective::Fragment{max: "1300".to_string(), flag: Some(FragmentFlag::Mtu)},
+        ConfigDirective::Mssfix{max: Some("10".to_string()), flag: None},
+        ConfigDirective::Mssfix{max: None, flag: None},
+        ConfigDirective::Mssfix{max: Some("1400".to_string()), flag: Some(MssfixFlag::Mtu)},
+        ConfigDirective::Mssfix{max: Some("1400".to_string()), flag: Some(MssfixFlag::Fixed)},
+        ConfigDirective::MaxPacketSize{size: 1400},
         ConfigDirective::Sndbuf{size: "10".to_string()},
         ConfigDirective::Rcvbuf{size: "10".to_string()},
         ConfigDirective::Mark{value: "somevalue".to_string()},
@@ -128,7 +144,7 @@ fn test_reads_ovpnfile() {
         ConfigDirective::RemapUsr1{signal: "somesignal".to_string()},
         ConfigDirective::Verb{n: "10".to_string()},
         ConfigDirective::Status{file: "somefile".to_string(), n: None},
-        ConfigDirective::Status{file: "somefile".to_string(), n: Some("10".to_string())},
+        ConfigDirective::Status{file: "somefile".to_string(), n: Some(10)},
         ConfigDirective::StatusVersion{n: None},
         ConfigDirective::StatusVersion{n: Some("1".to_string())},
         ConfigDirective::Mute{n: "10".to_string()},
@@ -179,6 +195,9 @@ fn test_reads_ovpnfile() {
         ConfigDirective::ClientDisconnect{cmd: "somecmd".to_string()},
         ConfigDirective::ClientConfigDir{dir: "somedir".to_string()},
         ConfigDirective::CcdExclusive,
+        ConfigDirective::VlanTagging,
+        ConfigDirective::VlanAccept{accept: VlanAccept::Tagged},
+        ConfigDirective::VlanPvid{id: VlanId::new(10).unwrap()},
         ConfigDirective::TmpDir{dir: "somedir".to_string()},
         ConfigDirective::HashSize{r: "somer".to_string(), v: "somev".to_string()},
         ConfigDirective::BcastBuffers{n: "10".to_string()},
@@ -191,8 +210,13 @@ fn test_reads_ovpnfile() {
         ConfigDirective::ConnectFreq{n: "10".to_string(), sec: "15".to_string()},
         ConfigDirective::LearnAddress{cmd: "somecmd".to_string()},
         ConfigDirective::AuthUserPassVerify{cmd: "somecmd".to_string(), method: "somemethod".to_string()},
-        ConfigDirective::AuthGenToken{lifetime: None},
-        ConfigDirective::AuthGenToken{lifetime: Some("somelifetime".to_string())},
+        ConfigDirective::AuthGenToken{lifetime: None, renewal_time: None, flag: None},
+        ConfigDirective::AuthGenToken{lifetime: Some(Duration::from_secs(3600)), renewal_time: None, flag: None},
+        ConfigDirective::AuthGenToken{
+            lifetime: Some(Duration::from_secs(0)),
+            renewal_time: Some(Duration::from_secs(600)),
+            flag: Some(AuthGenTokenFlag::ExternalAuth),
+        },
         ConfigDirective::OptVerify,
         ConfigDirective::AuthUserPassOptional,
         ConfigDirective::ClientCertNotRequired,
@@ -207,13 +231,14 @@ fn test_reads_ovpnfile() {
         ConfigDirective::Pull,
         ConfigDirective::PullFilter{accept_or_ignore_or_reject: "accept".to_string(), text: "sometext".to_string()},
         ConfigDirective::AuthUserPass{up: None},
-        ConfigDirective::AuthUserPass{up: Some("somefile".to_string())},
+        ConfigDirective::AuthUserPass{up: Some(File::FilePath("somefile".to_string()))},
         ConfigDirective::AuthRetry{auth_retry_type: "sometype".to_string()},
-        ConfigDirective::StaticChallenge{t: "sometext".to_string(), e: "someflag".to_string()},
+        ConfigDirective::StaticChallenge{t: "sometext".to_string(), e: false},
+        ConfigDirective::StaticChallenge{t: "Enter your PIN".to_string(), e: true},
         ConfigDirective::ServerPollTimeout{n: "10".to_string()},
         ConfigDirective::ConnectTimeout{n: "10".to_string()},
         ConfigDirective::ExplicitExitNotify{n: None},
-        ConfigDirective::ExplicitExitNotify{n: Some("10".to_string())},
+        ConfigDirective::ExplicitExitNotify{n: Some(10)},
         ConfigDirective::AllowRecursiveRouting,
         ConfigDirective::Secret{file: File::FilePath("somefile".to_string()), direction: None},
         ConfigDirective::Secret{file: File::FilePath("somefile".to_string()), direction: Some("somedirection".to_string())},
@@ -240,6 +265,7 @@ fn test_reads_ovpnfile() {
         ConfigDirective::Ca{file: File::FilePath("somefile".to_string())},
         ConfigDirective::Capath{dir: "dir".to_string()},
         ConfigDirective::Dh{file: File::FilePath("somefile".to_string())},
+        ConfigDirective::Dh{file: File::None},
         ConfigDirective::EcdhCurve{name: "somename".to_string()},
         ConfigDirective::Cert{file: File::FilePath("somefile".to_string())},
         ConfigDirective::ExtraCerts{file: File::FilePath("somefile".to_string())},
@@ -247,8 +273,11 @@ fn test_reads_ovpnfile() {
         ConfigDirective::TlsVersionMin{version: "someversion".to_string(), or_highest: None},
         ConfigDirective::TlsVersionMin{version: "someversion".to_string(), or_highest: Some("or-highest".to_string())},
         ConfigDirective::TlsVersionMax{version: "someversion".to_string()},
+        ConfigDirective::TlsCertProfile{profile: TlsCertProfile::Preferred},
         ConfigDirective::Pkcs12{file: File::FilePath("somefile".to_string())},
-        ConfigDirective::VerifyHash{hash: "somehash".to_string()},
+        ConfigDirective::VerifyHash{hash: CertHash::new(vec![0xab, 0xcd, 0xef]), algorithm: None},
+        ConfigDirective::VerifyHash{hash: CertHash::new(vec![0xab, 0xcd, 0xef]), algorithm: Some(HashAlgorithm::Sha256)},
+        ConfigDirective::PeerFingerprint{fingerprints: vec!["AB:CD:EF".to_string()]},
         ConfigDirective::Pkcs11CertPrivate{providers: vec!["arg1".to_string(), "arg2".to_string()]},
         ConfigDirective::Pkcs11Id{name: "somename".to_string()},
         ConfigDirective::Pkcs11IdManagement,
@@ -270,43 +299,61 @@ fn test_reads_ovpnfile() {
         ConfigDirective::TlsAuth{file: File::FilePath("somefile".to_string()), direction: None},
         ConfigDirective::TlsAuth{file: File::FilePath("somefile".to_string()), direction: Some("somedirection".to_string())},
         ConfigDirective::TlsCrypt{file: File::FilePath("somekeyfile".to_string())},
+        ConfigDirective::TlsCryptV2{file: File::FilePath("somekeyfile".to_string()), cookie_mode: None},
+        ConfigDirective::TlsCryptV2{file: File::FilePath("somekeyfile".to_string()), cookie_mode: Some(TlsCryptV2CookieMode::ForceCookie)},
+        ConfigDirective::TlsCryptV2Verify{cmd: "somecmd".to_string()},
         ConfigDirective::Askpass{file: None},
         ConfigDirective::Askpass{file: Some("somefile".to_string())},
         ConfigDirective::AuthNocache,
         ConfigDirective::AuthToken{token: "sometoken".to_string()},
+        ConfigDirective::AuthTokenUser{base64_username: "c29tZXVzZXI=".to_string()},
         ConfigDirective::TlsVerify{cmd: "somecmd".to_string()},
         ConfigDirective::TlsExportCert{directory: "somedirectory".to_string()},
-        ConfigDirective::X509UsernameField{fieldname: "someieldname".to_string()},
+        ConfigDirective::X509UsernameField{fields: vec!["someieldname".to_string()]},
+        ConfigDirective::X509UsernameField{fields: vec!["ext:subjectAltName".to_string(), "CN".to_string()]},
         ConfigDirective::VerifyX509Name{name: "name".to_string(), verify_x509_name_type: "type".to_string()},
         ConfigDirective::X509Track{attribute: "attribute".to_string()},
         ConfigDirective::NsCertType{client_or_server: "client".to_string()},
         ConfigDirective::RemoteCertKu{values: vec!["v1".to_string(), "v2".to_string()]},
         ConfigDirective::RemoteCertEku{oid: "oid".to_string()},
         ConfigDirective::RemoteCertTls{client_or_server: "client|server".to_string()},
-        ConfigDirective::CrlVerify{file: File::FilePath("crl".to_string()), direction: Some("somedir".to_string())},
+        ConfigDirective::CrlVerify{file: File::FilePath("crl".to_string()), flag: Some(CrlVerifyFlag::Dir)},
+        ConfigDirective::CrlVerify{file: File::FilePath("crl.pem".to_string()), flag: None},
         ConfigDirective::ShowCiphers,
         ConfigDirective::ShowDigests,
         ConfigDirective::ShowTls,
         ConfigDirective::ShowEngines,
         ConfigDirective::ShowCurves,
-        ConfigDirective::Genkey,
+        ConfigDirective::Genkey{key_type: None, file: None, metadata: None},
+        ConfigDirective::Genkey{key_type: Some(GenkeyType::Secret), file: Some("somefile".to_string()), metadata: None},
+        ConfigDirective::Genkey{
+            key_type: Some(GenkeyType::TlsCryptV2Client),
+            file: Some("somefile".to_string()),
+            metadata: Some("somemetadata".to_string()),
+        },
+        ConfigDirective::Genkey{key_type: Some(GenkeyType::AuthToken), file: None, metadata: None},
         ConfigDirective::Secret{file: File::FilePath("file".to_string()), direction: None},
         ConfigDirective::Mktun,
+        ConfigDirective::DisableDco,
         ConfigDirective::Rmtun,
         ConfigDirective::User{user: "user".to_string()},
         ConfigDirective::Group{group: "group".to_string()},
         ConfigDirective::WinSys{path: "path".to_string()},
-        ConfigDirective::IpWin32{method: "method".to_string()},
+        ConfigDirective::IpWin32{method: IpWin32Method::Netsh, offset: None, lease_time: None},
+        ConfigDirective::IpWin32{method: IpWin32Method::Dynamic, offset: Some(-4), lease_time: Some(Duration::from_secs(86400))},
         ConfigDirective::RouteMethod{m: "m".to_string()},
         ConfigDirective::DhcpOption{dhcp_option_type: "type".to_string(), parm: None},
         ConfigDirective::DhcpOption{dhcp_option_type: "type".to_string(), parm: Some("parm1".to_string())},
         ConfigDirective::TapSleep{n: "10".to_string()},
         ConfigDirective::ShowNetUp,
         ConfigDirective::BlockOutsideDns,
+        ConfigDirective::WindowsDriver{driver: WindowsDriver::Wintun},
         ConfigDirective::DhcpRenew,
         ConfigDirective::DhcpRelease,
+        ConfigDirective::DhcpPreRelease,
         ConfigDirective::RegisterDns,
         ConfigDirective::PauseExit,
+        ConfigDirective::MsgChannel{handle: 1234},
         ConfigDirective::Service{exit_event: "some_exit_event".to_string(), initial_state_of_event: None},
         ConfigDirective::Service{exit_event: "some_exit_event".to_string(), initial_state_of_event: Some("0".to_string())},
         ConfigDirective::ShowAdapters,
@@ -328,12 +375,28 @@ fn test_reads_ovpnfile() {
         ConfigDirective::IrouteIpv6{ipv6addr: "someipv6addr".to_string()},
         ConfigDirective::KeyDirection{direction: "1".to_string()},
         ConfigDirective::ServerBridge(ServerBridgeArg::GatewayConfig{
-            gateway: "somegateway".to_string(),
-            netmask: "somenetmask".to_string(),
-            pool_start_ip: "some_start_ip".to_string(),
-            pool_end_ip: "some_end_ip".to_string(),
+            gateway: Ipv4Addr::new(10, 8, 0, 4),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+            pool_start_ip: Ipv4Addr::new(10, 8, 0, 50),
+            pool_end_ip: Ipv4Addr::new(10, 8, 0, 100),
         }),
         ConfigDirective::ServerBridge(ServerBridgeArg::NoGateway),
+        ConfigDirective::ServerBridge(ServerBridgeArg::DhcpProxy{
+            gateway: Ipv4Addr::new(10, 8, 0, 4),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+        }),
+        ConfigDirective::Dns(DnsDirective::SearchDomains(vec!["example.com".to_string(), "example.net".to_string()])),
+        ConfigDirective::Dns(DnsDirective::Server{
+            priority: 1,
+            option: DnsServerOption::Address(vec!["10.8.0.1".to_string(), "[fd00::1]:53".to_string()]),
+        }),
+        ConfigDirective::Dns(DnsDirective::Server{
+            priority: -2,
+            option: DnsServerOption::ResolveDomains(vec!["corp.example.com".to_string()]),
+        }),
+        ConfigDirective::Dns(DnsDirective::Server{priority: 1, option: DnsServerOption::Dnssec(DnsSecMode::Optional)}),
+        ConfigDirective::Dns(DnsDirective::Server{priority: 1, option: DnsServerOption::Transport(DnsTransport::DnsOverTls)}),
+        ConfigDirective::Dns(DnsDirective::Server{priority: 1, option: DnsServerOption::Sni("dns.example.com".to_string())}),
         ];
     let test_ovpnfile = include_str!("test.ovpn");
     let test_reader = BufReader::new(test_ovpnfile.as_bytes());
@@ -382,10 +445,227 @@ fn test_reads_inline_file_contents() {
         ConfigDirective::Key{file: File::InlineFileContents("key1\nkey2".to_string())},
         ConfigDirective::Pkcs12{file: File::InlineFileContents("pkcs12-1\npkcs12-2".to_string())},
         ConfigDirective::Secret{file: File::InlineFileContents("secret1\nsecret2".to_string()), direction: None},
-        ConfigDirective::CrlVerify{file: File::InlineFileContents("crl-verify1\ncrl-verify2".to_string()), direction: None},
+        ConfigDirective::CrlVerify{file: File::InlineFileContents("crl-verify1\ncrl-verify2".to_string()), flag: None},
         ConfigDirective::HttpProxyUserPass{file: File::InlineFileContents("http-proxy-user-pass-1\nhttp-proxy-user-pass-2".to_string())},
         ConfigDirective::TlsAuth{file: File::InlineFileContents("tls-auth1\ntls-auth2".to_string()), direction: None},
         ConfigDirective::TlsCrypt{file: File::InlineFileContents("tls-crypt1\ntls-crypt2".to_string())},
+        ConfigDirective::TlsCryptV2{file: File::InlineFileContents("tls-crypt-v2-1\ntls-crypt-v2-2".to_string()), cookie_mode: None},
+        ConfigDirective::PeerFingerprint{fingerprints: vec!["AB:CD:EF".to_string(), "01:23:45".to_string()]},
         ]
         )
 }
+
+#[test]
+fn test_invalid_arguments_produce_warnings() {
+    let test_ovpnfile = include_str!("test-invalid.ovpn");
+    let test_reader = BufReader::new(test_ovpnfile.as_bytes());
+    let result = ovpnfile::parse(test_reader).unwrap();
+    spectral::assert_that(&result.success_lines.len()).is_equal_to(0);
+    for warning in result.warning_lines.iter() {
+        spectral::asserting(&format!("Line {}", warning.number)).that(&warning.result).is_equal_to(&ParseWarning::InvalidArgument);
+    }
+    spectral::assert_that(&result.warning_lines.len()).is_equal_to(test_ovpnfile.lines().count());
+}
+
+#[test]
+fn test_socks_proxy_port_defaults_to_1080() {
+    let result = ovpnfile::parse("socks-proxy someserver\nsocks-proxy someserver 9050".as_bytes()).unwrap();
+    let ports: Vec<Option<u16>> = result.directives().iter().map(|d| d.socks_proxy_port()).collect();
+    spectral::assert_that(&ports).is_equal_to(vec![Some(1080), Some(9050)]);
+}
+
+#[test]
+fn test_x509_username_fields() {
+    let result = ovpnfile::parse("x509-username-field ext:subjectAltName CN".as_bytes()).unwrap();
+    spectral::assert_that(&result.directives()[0].x509_username_fields()).is_equal_to(Some(vec![
+        X509Field::Extension("subjectAltName".to_string()),
+        X509Field::Subject("CN".to_string()),
+    ]));
+}
+
+#[test]
+fn test_echo_messages() {
+    let contents = "echo msg-window Connected\necho msg Welcome to the  VPN\necho some message";
+    let result = ovpnfile::parse(contents.as_bytes()).unwrap();
+    let directives = result.directives();
+    let messages: Vec<Option<String>> = directives.iter().map(|d| d.message()).collect();
+    spectral::assert_that(&messages).is_equal_to(vec![
+        Some("msg-window Connected".to_string()),
+        Some("msg Welcome to the VPN".to_string()),
+        Some("some message".to_string()),
+    ]);
+    let commands: Vec<Option<EchoCommand>> = directives.iter().map(|d| d.echo_command()).collect();
+    spectral::assert_that(&commands).is_equal_to(vec![
+        Some(EchoCommand::MsgWindow("Connected".to_string())),
+        Some(EchoCommand::Msg("Welcome to the VPN".to_string())),
+        Some(EchoCommand::Other("some message".to_string())),
+    ]);
+}
+
+#[test]
+fn test_required_openvpn_version() {
+    let required = |contents: &str| {
+        ovpnfile::required_openvpn_version(&ovpnfile::parse(contents.as_bytes()).unwrap())
+    };
+    spectral::assert_that(&required("client\nremote somehost")).is_equal_to(None);
+    spectral::assert_that(&required("client\ntls-crypt tc.key")).is_equal_to(Some(Version::new(2, 4)));
+    spectral::assert_that(&required("tls-crypt tc.key\ndata-ciphers AES-256-GCM")).is_equal_to(Some(Version::new(2, 5)));
+    spectral::assert_that(&required("peer-fingerprint AB:CD\ncomp-lzo")).is_equal_to(Some(Version::new(2, 6)));
+    spectral::assert_that(&required("mssfix 1400 mtu")).is_equal_to(Some(Version::new(2, 6)));
+    spectral::assert_that(&required("verify-x509-name server name")).is_equal_to(Some(Version::new(2, 3)));
+    spectral::assert_that(&required("connect-retry 5")).is_equal_to(None);
+    spectral::assert_that(&required("connect-retry 5 300\ndh none")).is_equal_to(Some(Version::new(2, 4)));
+    spectral::assert_that(&required("tls-crypt-v2 tc.key force-cookie")).is_equal_to(Some(Version::new(2, 6)));
+}
+
+#[test]
+fn test_long_lines_produce_warnings() {
+    let long_arg = "a".repeat(ovpnfile::MAX_LINE_LEN + 1);
+    let contents = format!("client\nremote {}\n<ca>\n{}\n</ca>\n", long_arg, long_arg);
+    let result = ovpnfile::parse(contents.as_bytes()).unwrap();
+    spectral::assert_that(&result.warning_lines).is_equal_to(vec![
+        ConfigLine{number: 1, result: ParseWarning::LineTooLong},
+        ConfigLine{number: 3, result: ParseWarning::LineTooLong},
+    ]);
+    spectral::assert_that(&result.directives()).is_equal_to(vec![
+        ConfigDirective::Client,
+        ConfigDirective::Remote{host: long_arg.clone(), port: None, proto: None},
+        ConfigDirective::Ca{file: File::InlineFileContents(long_arg.clone())},
+    ]);
+
+    let raw = ovpnfile::tokenize(contents.as_bytes()).unwrap().parse();
+    spectral::assert_that(&raw.warning_lines).is_equal_to(&result.warning_lines);
+    spectral::assert_that(&raw.success_lines).is_equal_to(&result.success_lines);
+    let document = ovpnfile::ConfigDocument::parse(&contents).to_parsed();
+    spectral::assert_that(&document.warning_lines).is_equal_to(&result.warning_lines);
+    spectral::assert_that(&document.success_lines).is_equal_to(&result.success_lines);
+}
+
+#[test]
+fn test_connection_blocks() {
+    let contents = "client\n<connection>\nremote a.example.com 1194\n# comment\nproto tcp\n</connection>\n\
+                    <connection>\nremote b.example.com\nnot-an-option\n</connection>\n";
+    let result = ovpnfile::parse(contents.as_bytes()).unwrap();
+    let connection = ConfigDirective::Connection(vec![
+        ConfigDirective::Remote{host: "a.example.com".to_string(), port: Some("1194".to_string()), proto: None},
+        ConfigDirective::Proto{p: "tcp".to_string()},
+    ]);
+    spectral::assert_that(&result.directives()).is_equal_to(vec![ConfigDirective::Client, connection.clone()]);
+    spectral::assert_that(&result.warning_lines).is_equal_to(vec![
+        ConfigLine{number: 6, result: ParseWarning::NoMatchingCommand},
+    ]);
+    spectral::assert_that(&connection.as_ovpn_config())
+        .is_equal_to("<connection>\nremote a.example.com 1194\nproto tcp\n</connection>".to_string());
+
+    let raw = ovpnfile::tokenize(contents.as_bytes()).unwrap().parse();
+    spectral::assert_that(&raw.success_lines).is_equal_to(&result.success_lines);
+    spectral::assert_that(&raw.warning_lines).is_equal_to(&result.warning_lines);
+}
+
+#[test]
+fn test_inline_tags_and_comments() {
+    let contents = "<ca> trailing text\nca1\n</ca>\tmore\n<unknown>\nremote somehost ;comment\n<key>\nkey1\n</ke>\n</key>\n";
+    let result = ovpnfile::parse(contents.as_bytes()).unwrap();
+    spectral::assert_that(&result.directives()).is_equal_to(vec![
+        ConfigDirective::Ca{file: File::InlineFileContents("ca1".to_string())},
+        ConfigDirective::Remote{host: "somehost".to_string(), port: None, proto: None},
+        ConfigDirective::Key{file: File::InlineFileContents("key1\n</ke>".to_string())},
+    ]);
+    spectral::assert_that(&result.warning_lines).is_equal_to(vec![
+        ConfigLine{number: 3, result: ParseWarning::NoMatchingCommand},
+    ]);
+}
+
+#[test]
+fn test_comments_outside_quotes() {
+    let contents = "; comment\nstatic-challenge \"Enter code # from app\" 1 # comment\nsetenv FOO \"a#b\"\nsetenv BAR c#d;e\n";
+    let result = ovpnfile::parse(contents.as_bytes()).unwrap();
+    spectral::assert_that(&result.warning_lines.len()).is_equal_to(0);
+    spectral::assert_that(&result.directives()).is_equal_to(vec![
+        ConfigDirective::StaticChallenge{t: "Enter code # from app".to_string(), e: true},
+        ConfigDirective::Setenv{name: "FOO".to_string(), value: "a#b".to_string()},
+        ConfigDirective::Setenv{name: "BAR".to_string(), value: "c#d;e".to_string()},
+    ]);
+}
+
+#[test]
+fn test_borrowing_directives() {
+    let result = ovpnfile::parse("client\n<ca>\ncacontent\n</ca>\n".as_bytes()).unwrap();
+    let directives = result.directives();
+    spectral::assert_that(&result.iter().cloned().collect::<Vec<_>>()).is_equal_to(&directives);
+    spectral::assert_that(&result.directives_ref()).is_equal_to(directives.iter().collect::<Vec<_>>());
+    spectral::assert_that(&result.into_directives()).is_equal_to(directives);
+}
+
+#[test]
+fn test_parse_bytes() {
+    for contents in [include_str!("test.ovpn"), include_str!("test-inline.ovpn"), "client\r\nremote somehost\r\n<ca>\r\nca1\r\n</ca>"].iter() {
+        let from_reader = ovpnfile::parse(BufReader::new(contents.as_bytes())).unwrap();
+        let from_bytes = ovpnfile::parse_bytes(contents.as_bytes()).unwrap();
+        spectral::assert_that(&from_bytes.success_lines).is_equal_to(&from_reader.success_lines);
+        spectral::assert_that(&from_bytes.warning_lines).is_equal_to(&from_reader.warning_lines);
+    }
+    spectral::assert_that(&ovpnfile::parse_bytes(b"client\n\xff\n").is_err()).is_true();
+}
+
+#[test]
+fn test_warning_callback() {
+    let contents = "client\nunknown-option\nverb\nexplicit-exit-notify lots\n";
+    let mut warnings = Vec::new();
+    let result = ovpnfile::Parser::new()
+        .on_warning(|warning| warnings.push(warning.clone()))
+        .parse(contents.as_bytes())
+        .unwrap();
+    spectral::assert_that(&warnings).is_equal_to(&result.warning_lines);
+    spectral::assert_that(&warnings).is_equal_to(vec![
+        ConfigLine{number: 1, result: ParseWarning::NoMatchingCommand},
+        ConfigLine{number: 2, result: ParseWarning::NotEnoughArguments},
+        ConfigLine{number: 3, result: ParseWarning::InvalidArgument},
+    ]);
+
+    let mut count = 0;
+    ovpnfile::Parser::new().on_warning(|_| count += 1).parse_bytes(contents.as_bytes()).unwrap();
+    spectral::assert_that(&count).is_equal_to(3);
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_cli() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let run = |args: &[&str], input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ovpnfile")).args(args)
+            .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+            .spawn().unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        (output.status.code(), String::from_utf8(output.stdout).unwrap())
+    };
+    let config = "client\nremote   vpn.example.com 1194 # comment\n<ca>\nca \"contents\"\n</ca>\n";
+
+    let (status, stdout) = run(&["fmt"], config);
+    assert_eq!(status, Some(0));
+    assert_eq!(stdout, "client\nremote vpn.example.com 1194 # comment\n<ca>\nca \"contents\"\n</ca>\n");
+
+    let (status, stdout) = run(&["fmt"], "# My VPN\n\nclient\nunknown-option\n");
+    assert_eq!(status, Some(1));
+    assert_eq!(stdout, "# My VPN\n\nclient\nunknown-option\n");
+
+    assert_eq!(run(&["fmt", "--normalize"], config).0, Some(2));
+    let (status, stdout) = run(&["fmt", "--normalize"], "verb 3\nclient\n");
+    assert_eq!(status, Some(0));
+    assert_eq!(stdout, "client\nverb 3\n");
+
+    let (status, stdout) = run(&["json"], config);
+    assert_eq!(status, Some(0));
+    assert_eq!(stdout, "{\"options\": [{\"line\": 1, \"option\": \"client\", \"args\": [], \"inline\": null}, \
+                        {\"line\": 2, \"option\": \"remote\", \"args\": [\"vpn.example.com\", \"1194\"], \"inline\": null}, \
+                        {\"line\": 3, \"option\": \"ca\", \"args\": [], \"inline\": \"ca \\\"contents\\\"\"}], \
+                        \"warnings\": []}\n");
+
+    let (status, stdout) = run(&["lint"], "client\ntls-auth ta.key 1\ntls-crypt tc.key\n");
+    assert_eq!(status, Some(1));
+    assert!(stdout.contains("error"));
+
+    assert_eq!(run(&["frobnicate"], config).0, Some(2));
+}